@@ -5,33 +5,48 @@
 // Credentials are stored in user settings (configured during first launch)
 // ============================================================================
 
+use futures_util::future::{self, Either};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_store::StoreExt;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
-use crate::shell::{get_robust_path_env, resolve_binary_path};
+use tokio::sync::oneshot;
+use crate::gemini::{validate_gemini_api_key, ValidateApiKeyResult};
+use crate::settings::{keys, SETTINGS_STORE};
+use crate::shell::{emit_tagged_stream_event, get_robust_path_env, resolve_binary_path};
 
 const OAUTH_CALLBACK_PORT: u16 = 23847;
+// How long reset_auth_provider waits for a cancelled flow to release the callback port
+const PORT_RELEASE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+// Providers whose OAuth state lives in auth.json (Claude credentials belong to its CLI)
+const OAUTH_PROVIDERS: &[&str] = &["google"];
 
 // Google OAuth
 const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
-const GOOGLE_SCOPES: &str = "https://www.googleapis.com/auth/cloud-platform";
+const GOOGLE_USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+// Requested when the `googleScopes` setting is unset or empty
+const DEFAULT_GOOGLE_SCOPES: &[&str] = &["https://www.googleapis.com/auth/cloud-platform"];
 
 /// Get Google OAuth credentials from user settings
 fn get_google_credentials_from_store(app: &AppHandle) -> Result<(String, String), String> {
     let store = app
-        .store("settings.json")
+        .store(SETTINGS_STORE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
     let client_id = store
-        .get("googleClientId")
+        .get(keys::GOOGLE_CLIENT_ID)
         .and_then(|v| v.as_str().map(|s| s.to_string()))
         .ok_or("Google Client ID not configured. Please complete the setup wizard.")?;
 
     let client_secret = store
-        .get("googleClientSecret")
+        .get(keys::GOOGLE_CLIENT_SECRET)
         .and_then(|v| v.as_str().map(|s| s.to_string()))
         .ok_or("Google Client Secret not configured. Please complete the setup wizard.")?;
 
@@ -63,12 +78,131 @@ struct AuthEvent {
     message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetAuthResult {
+    pub provider: String,
+    /// A pending OAuth flow was waiting and has been cancelled
+    pub flow_cancelled: bool,
+    /// auth.json keys that were deleted
+    pub keys_removed: Vec<String>,
+    /// The OAuth callback port is free to bind again
+    pub port_released: bool,
+}
+
+/// Signed-in Google user, from the OpenID Connect userinfo endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoogleIdentity {
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthStatusResponse {
     pub google: bool,
     pub anthropic: bool,
 }
 
+/// Payload of `provider:status` events emitted by startup_auth_check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderStatus {
+    /// "google", "anthropic" or "gemini"
+    pub provider: String,
+    pub ready: bool,
+    /// "ready", "not_authenticated", "not_configured", "invalid" or "error"
+    pub status: String,
+    pub message: String,
+}
+
+/// Shutdown channels for OAuth flows waiting on the callback, keyed by provider
+/// Each flow gets an id so a replaced flow can't unregister its successor
+pub struct OAuthRegistry {
+    pending: Mutex<HashMap<String, (u64, oneshot::Sender<()>)>>,
+    next_id: AtomicU64,
+}
+
+impl OAuthRegistry {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Register a new flow, cancelling any flow for the provider that is still running
+    /// Returns the flow id (for remove) and the receiver signalled by cancel_oauth
+    fn register(&self, provider: &str) -> (u64, oneshot::Receiver<()>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        let previous = self
+            .pending
+            .lock()
+            .unwrap()
+            .insert(provider.to_string(), (id, tx));
+        if let Some((_, previous)) = previous {
+            let _ = previous.send(());
+        }
+        (id, rx)
+    }
+
+    /// Signal the pending flow for this provider; returns false if none is waiting
+    fn cancel(&self, provider: &str) -> bool {
+        match self.pending.lock().unwrap().remove(provider) {
+            Some((_, tx)) => tx.send(()).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Unregister a finished flow, unless a newer flow has already replaced it
+    fn remove(&self, provider: &str, id: u64) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending
+            .get(provider)
+            .is_some_and(|(current, _)| *current == id)
+        {
+            pending.remove(provider);
+        }
+    }
+}
+
+impl Default for OAuthRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// OAuth scopes from the `googleScopes` setting, falling back to DEFAULT_GOOGLE_SCOPES
+fn get_google_scopes(app: &AppHandle) -> Vec<String> {
+    let configured = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::GOOGLE_SCOPES));
+    scopes_or_default(configured)
+}
+
+fn scopes_or_default(configured: Option<serde_json::Value>) -> Vec<String> {
+    let scopes: Vec<String> = configured
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|s| s.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if scopes.is_empty() {
+        DEFAULT_GOOGLE_SCOPES
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        scopes
+    }
+}
+
 /// Check if Google OAuth credentials are configured in settings
 #[tauri::command]
 pub fn check_google_oauth_configured(app: AppHandle) -> bool {
@@ -82,6 +216,7 @@ pub fn check_google_oauth_configured(app: AppHandle) -> bool {
 #[tauri::command]
 pub async fn start_google_oauth(app: AppHandle) -> Result<AuthResult, String> {
     let (client_id, client_secret) = get_google_credentials_from_store(&app)?;
+    let scopes = get_google_scopes(&app);
 
     run_oauth_flow(
         &app,
@@ -90,11 +225,18 @@ pub async fn start_google_oauth(app: AppHandle) -> Result<AuthResult, String> {
         GOOGLE_TOKEN_URL,
         &client_id,
         &client_secret,
-        GOOGLE_SCOPES,
+        &scopes,
     )
     .await
 }
 
+/// Cancel an OAuth flow that is waiting for the browser callback
+/// Returns false if no flow is pending for this provider
+#[tauri::command]
+pub fn cancel_oauth(app: AppHandle, provider: String) -> bool {
+    app.state::<OAuthRegistry>().cancel(&provider)
+}
+
 #[tauri::command]
 pub async fn check_google_auth(app: AppHandle) -> Result<bool, String> {
     check_auth(&app, "google").await
@@ -105,11 +247,118 @@ pub async fn get_google_access_token(app: AppHandle) -> Result<String, String> {
     get_access_token(&app, "google", GOOGLE_TOKEN_URL).await
 }
 
+/// Email, name and picture of the signed-in Google user
+/// Cached in auth.json until logout or the next sign-in. The default scopes don't
+/// cover userinfo, so without `email`/`profile` in googleScopes this returns an error
+/// saying so.
+#[tauri::command]
+pub async fn get_google_identity(app: AppHandle) -> Result<GoogleIdentity, String> {
+    let store = app
+        .store("auth.json")
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let key = identity_key("google");
+    if let Some(cached) = store
+        .get(&key)
+        .and_then(|v| serde_json::from_value::<GoogleIdentity>(v).ok())
+    {
+        return Ok(cached);
+    }
+
+    let access_token = get_access_token(&app, "google", GOOGLE_TOKEN_URL).await?;
+    let response = crate::http::get(GOOGLE_USERINFO_URL)
+        .bearer_auth(&access_token)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch user info: {}", e))?;
+
+    let status = response.status().as_u16();
+    let www_authenticate = response
+        .headers()
+        .get(reqwest::header::WWW_AUTHENTICATE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let body = response.text().await.unwrap_or_default();
+
+    if is_insufficient_scope(status, www_authenticate.as_deref(), &body) {
+        // Not cached, so a sign-in with broader scopes is picked up
+        log::warn!("[auth] Google token lacks userinfo scopes");
+        return Err(
+            "Signed in without access to your Google profile. Add \"openid\", \"email\" and \
+             \"profile\" to googleScopes in settings, then sign in again."
+                .to_string(),
+        );
+    }
+    if !(200..300).contains(&status) {
+        return Err(format!("User info request failed ({}): {}", status, body));
+    }
+
+    let identity: GoogleIdentity =
+        serde_json::from_str(&body).map_err(|e| format!("Failed to parse user info: {}", e))?;
+    store.set(
+        &key,
+        serde_json::to_value(&identity).map_err(|e| e.to_string())?,
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+
+    Ok(identity)
+}
+
 #[tauri::command]
 pub async fn logout_google(app: AppHandle) -> Result<(), String> {
     logout(&app, "google").await
 }
 
+/// Clear a provider's (possibly partial) auth state so login can be retried cleanly
+/// Cancels any pending flow, deletes its auth.json entries, waits for the callback
+/// port to be released and emits `logged_out`
+/// Only OAuth providers can be reset; Claude is signed out with logout_anthropic
+#[tauri::command]
+pub async fn reset_auth_provider(
+    app: AppHandle,
+    provider: String,
+) -> Result<ResetAuthResult, String> {
+    validate_reset_provider(&provider)?;
+    let flow_cancelled = app.state::<OAuthRegistry>().cancel(&provider);
+
+    let store = app
+        .store("auth.json")
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    let keys_removed = provider_auth_keys(&provider, store.keys());
+    for key in &keys_removed {
+        store.delete(key);
+    }
+    store
+        .save()
+        .map_err(|e| format!("Failed to save store: {}", e))?;
+
+    let port_released = wait_for_port_release(OAUTH_CALLBACK_PORT, PORT_RELEASE_TIMEOUT).await;
+    if !port_released {
+        log::warn!(
+            "OAuth callback port {} is still in use after resetting {}",
+            OAUTH_CALLBACK_PORT,
+            provider
+        );
+    }
+
+    let _ = app.emit(
+        "auth:status",
+        AuthEvent {
+            provider: provider.clone(),
+            status: "logged_out".to_string(),
+            message: "Authentication state was reset".to_string(),
+        },
+    );
+
+    Ok(ResetAuthResult {
+        provider,
+        flow_cancelled,
+        keys_removed,
+        port_released,
+    })
+}
+
 // ============================================================================
 // Claude Code CLI Authentication
 // Uses `claude auth status` and `claude auth login` commands
@@ -147,9 +396,13 @@ pub async fn check_anthropic_auth() -> Result<bool, String> {
 
 /// Start Claude Code CLI OAuth by running `claude auth login`
 /// This opens a browser for the user to authenticate
+/// CLI output is streamed on rpc:stream:data tagged with `stream_id` (generated when omitted)
 #[tauri::command]
-pub async fn start_anthropic_oauth(app: AppHandle) -> Result<AuthResult, String> {
-    use std::process::Command;
+pub async fn start_anthropic_oauth(
+    app: AppHandle,
+    stream_id: Option<String>,
+) -> Result<AuthResult, String> {
+    use std::process::{Command, Stdio};
 
     let _ = app.emit(
         "auth:status",
@@ -164,14 +417,46 @@ pub async fn start_anthropic_oauth(app: AppHandle) -> Result<AuthResult, String>
     let claude_path = resolve_binary_path("claude");
     let robust_path = get_robust_path_env();
 
-    // Run claude auth login - this will open browser and wait for completion
-    let output = Command::new(&claude_path)
+    // Run claude auth login - this will open browser and wait for completion.
+    // Output is streamed via rpc:stream:data so any printed login URL shows up immediately.
+    let mut child = Command::new(&claude_path)
         .args(["auth", "login"])
         .env("PATH", robust_path)
-        .output()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .map_err(|e| format!("Failed to run claude auth login: {}", e))?;
 
-    if output.status.success() {
+    let stream_id =
+        stream_id.unwrap_or_else(|| format!("auth_{}", chrono::Utc::now().timestamp_millis()));
+    let stdout_thread = child.stdout.take().map(|stdout| {
+        let app = app.clone();
+        let stream_id = stream_id.clone();
+        thread::spawn(move || stream_auth_output(&app, &stream_id, stdout))
+    });
+    let stderr_thread = child.stderr.take().map(|stderr| {
+        let app = app.clone();
+        let stream_id = stream_id.clone();
+        thread::spawn(move || stream_auth_output(&app, &stream_id, stderr))
+    });
+
+    // Login waits on the browser for as long as the user takes; keep it off the runtime
+    let (status, stderr) = tokio::task::spawn_blocking(move || {
+        let status = child.wait();
+        if let Some(t) = stdout_thread {
+            let _ = t.join();
+        }
+        let stderr = stderr_thread
+            .and_then(|t| t.join().ok())
+            .unwrap_or_default();
+        (status, stderr)
+    })
+    .await
+    .map_err(|e| format!("claude auth login task failed: {}", e))?;
+    let status = status.map_err(|e| format!("Failed to wait for claude auth login: {}", e))?;
+
+    if status.success() {
         let _ = app.emit(
             "auth:status",
             AuthEvent {
@@ -187,11 +472,10 @@ pub async fn start_anthropic_oauth(app: AppHandle) -> Result<AuthResult, String>
             message: "Successfully authenticated with Claude Code CLI".to_string(),
         })
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let error_msg = if stderr.is_empty() {
+        let error_msg = if stderr.trim().is_empty() {
             "Authentication failed or was cancelled".to_string()
         } else {
-            stderr.to_string()
+            stderr
         };
 
         let _ = app.emit(
@@ -207,6 +491,42 @@ pub async fn start_anthropic_oauth(app: AppHandle) -> Result<AuthResult, String>
     }
 }
 
+/// Forward CLI output line by line, announcing any login URL via auth:status
+/// Returns everything read, for error reporting
+fn stream_auth_output(app: &AppHandle, stream_id: &str, reader: impl Read) -> String {
+    let mut collected = String::new();
+
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else { break };
+
+        emit_tagged_stream_event(app, stream_id, "output", &format!("{}\n", line));
+        if let Some(url) = extract_url(&line) {
+            let _ = app.emit(
+                "auth:status",
+                AuthEvent {
+                    provider: "anthropic".to_string(),
+                    status: "pending".to_string(),
+                    message: format!("Open this URL to authenticate: {}", url),
+                },
+            );
+        }
+
+        collected.push_str(&line);
+        collected.push('\n');
+    }
+
+    collected
+}
+
+/// Find the first http(s) URL in a line of CLI output
+fn extract_url(line: &str) -> Option<&str> {
+    let start = line.find("https://").or_else(|| line.find("http://"))?;
+    let url = line[start..]
+        .split(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == '>')
+        .next()?;
+    Some(url.trim_end_matches(['.', ',', ')']))
+}
+
 /// Logout from Claude Code CLI by running `claude auth logout`
 #[tauri::command]
 pub async fn logout_anthropic(app: AppHandle) -> Result<(), String> {
@@ -250,6 +570,31 @@ pub async fn check_all_auth(app: AppHandle) -> Result<AuthStatusResponse, String
     Ok(AuthStatusResponse { google, anthropic })
 }
 
+/// Check every provider once (e.g. at launch) so the UI can show readiness before
+/// any action fails. Emits one `provider:status` event per provider and returns them.
+/// The Gemini key is only validated against the API when one is configured.
+#[tauri::command]
+pub async fn startup_auth_check(app: AppHandle) -> Result<Vec<ProviderStatus>, String> {
+    let auth = check_all_auth(app.clone()).await?;
+
+    let gemini_key = app
+        .store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::GEMINI_API_KEY))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .filter(|s| !s.is_empty());
+    let gemini = match gemini_key {
+        Some(api_key) => Some(validate_gemini_api_key(api_key).await),
+        None => None,
+    };
+
+    let statuses = provider_statuses(&auth, gemini);
+    for status in &statuses {
+        let _ = app.emit("provider:status", status);
+    }
+    Ok(statuses)
+}
+
 // ============================================================================
 // Shared OAuth Implementation
 // ============================================================================
@@ -261,17 +606,10 @@ async fn run_oauth_flow(
     token_url: &str,
     client_id: &str,
     client_secret: &str,
-    scopes: &str,
+    scopes: &[String],
 ) -> Result<AuthResult, String> {
     let redirect_uri = format!("http://127.0.0.1:{}", OAUTH_CALLBACK_PORT);
-
-    let full_auth_url = format!(
-        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent",
-        auth_url,
-        urlencoding::encode(client_id),
-        urlencoding::encode(&redirect_uri),
-        urlencoding::encode(scopes)
-    );
+    let full_auth_url = build_auth_url(auth_url, client_id, &redirect_uri, scopes);
 
     let _ = app.emit(
         "auth:status",
@@ -282,11 +620,25 @@ async fn run_oauth_flow(
         },
     );
 
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", OAUTH_CALLBACK_PORT))
-        .await
-        .map_err(|e| format!("Failed to start OAuth callback server: {}", e))?;
+    // A flow still waiting on the callback holds the port; cancel it and let it shut down
+    let registry = app.state::<OAuthRegistry>();
+    if registry.cancel(provider) {
+        wait_for_port_release(OAUTH_CALLBACK_PORT, PORT_RELEASE_TIMEOUT).await;
+    }
+    let (flow_id, cancel_rx) = registry.register(provider);
 
-    open::that(&full_auth_url).map_err(|e| format!("Failed to open browser: {}", e))?;
+    let listener = match TcpListener::bind(format!("127.0.0.1:{}", OAUTH_CALLBACK_PORT)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            registry.remove(provider, flow_id);
+            return Err(format!("Failed to start OAuth callback server: {}", e));
+        }
+    };
+
+    if let Err(e) = open::that(&full_auth_url) {
+        registry.remove(provider, flow_id);
+        return Err(format!("Failed to open browser: {}", e));
+    }
 
     let app_handle = app.clone();
     let provider_str = provider.to_string();
@@ -294,7 +646,7 @@ async fn run_oauth_flow(
     let client_id_str = client_id.to_string();
     let client_secret_str = client_secret.to_string();
 
-    let result = tokio::time::timeout(std::time::Duration::from_secs(300), async {
+    let flow = tokio::time::timeout(std::time::Duration::from_secs(300), async {
         let (mut socket, _) = listener
             .accept()
             .await
@@ -327,14 +679,34 @@ async fn run_oauth_flow(
         .await?;
 
         store_credentials(&app_handle, &provider_str, &tokens).await?;
+        // The new tokens may belong to a different account
+        clear_cached_identity(&app_handle, &provider_str)?;
 
         Ok::<AuthResult, String>(AuthResult {
             success: true,
             provider: provider_str.clone(),
             message: format!("Successfully authenticated with {}", provider_str),
         })
-    })
-    .await;
+    });
+
+    // Race the callback against cancel_oauth; the listener is dropped on return either way
+    let result = match future::select(std::pin::pin!(flow), cancel_rx).await {
+        Either::Left((result, _)) => Some(result),
+        Either::Right(_) => None,
+    };
+    registry.remove(provider, flow_id);
+
+    let Some(result) = result else {
+        let _ = app.emit(
+            "auth:status",
+            AuthEvent {
+                provider: provider.to_string(),
+                status: "cancelled".to_string(),
+                message: "Authentication cancelled".to_string(),
+            },
+        );
+        return Err("Authentication cancelled".to_string());
+    };
 
     match result {
         Ok(Ok(auth_result)) => {
@@ -427,6 +799,7 @@ async fn logout(app: &AppHandle, provider: &str) -> Result<(), String> {
 
     let key = format!("{}_credentials", provider);
     store.delete(&key);
+    store.delete(identity_key(provider));
     store
         .save()
         .map_err(|e| format!("Failed to save store: {}", e))?;
@@ -447,6 +820,62 @@ async fn logout(app: &AppHandle, provider: &str) -> Result<(), String> {
 // Helper Functions
 // ============================================================================
 
+/// Summarize auth checks and the Gemini key validation (None when no key is set)
+fn provider_statuses(
+    auth: &AuthStatusResponse,
+    gemini: Option<Result<ValidateApiKeyResult, String>>,
+) -> Vec<ProviderStatus> {
+    let status = |provider: &str, ready: bool, status: &str, message: String| ProviderStatus {
+        provider: provider.to_string(),
+        ready,
+        status: status.to_string(),
+        message,
+    };
+    let signed_in = |provider: &str, name: &str, ready: bool| {
+        if ready {
+            status(provider, true, "ready", format!("Signed in to {}", name))
+        } else {
+            status(
+                provider,
+                false,
+                "not_authenticated",
+                format!("Not signed in to {}", name),
+            )
+        }
+    };
+
+    let gemini = match gemini {
+        None => status(
+            "gemini",
+            false,
+            "not_configured",
+            "Gemini API key not configured".to_string(),
+        ),
+        Some(Ok(result)) if result.valid => status(
+            "gemini",
+            true,
+            "ready",
+            "Gemini API key is valid".to_string(),
+        ),
+        Some(Ok(result)) => status(
+            "gemini",
+            false,
+            "invalid",
+            result
+                .error
+                .unwrap_or_else(|| "Invalid API key".to_string()),
+        ),
+        // Network failures say nothing about the key itself
+        Some(Err(e)) => status("gemini", false, "error", e),
+    };
+
+    vec![
+        signed_in("google", "Google", auth.google),
+        signed_in("anthropic", "Claude", auth.anthropic),
+        gemini,
+    ]
+}
+
 fn extract_code_from_request(request: &str) -> Result<String, String> {
     let first_line = request.lines().next().ok_or("Empty request")?;
 
@@ -485,8 +914,6 @@ async fn exchange_code_for_tokens(
     redirect_uri: &str,
     token_url: &str,
 ) -> Result<OAuthCredentials, String> {
-    let client = reqwest::Client::new();
-
     let mut params = vec![
         ("code", code),
         ("client_id", client_id),
@@ -498,8 +925,7 @@ async fn exchange_code_for_tokens(
         params.push(("client_secret", client_secret));
     }
 
-    let response = client
-        .post(token_url)
+    let response = crate::http::post(token_url)
         .form(&params)
         .send()
         .await
@@ -543,8 +969,6 @@ async fn refresh_access_token(
     client_secret: &str,
     token_url: &str,
 ) -> Result<OAuthCredentials, String> {
-    let client = reqwest::Client::new();
-
     let mut params = vec![
         ("refresh_token", refresh_token),
         ("client_id", client_id),
@@ -555,8 +979,7 @@ async fn refresh_access_token(
         params.push(("client_secret", client_secret));
     }
 
-    let response = client
-        .post(token_url)
+    let response = crate::http::post(token_url)
         .form(&params)
         .send()
         .await
@@ -593,6 +1016,43 @@ async fn refresh_access_token(
     })
 }
 
+/// reset_auth_provider only clears state this app owns
+fn validate_reset_provider(provider: &str) -> Result<(), String> {
+    if OAUTH_PROVIDERS.contains(&provider) {
+        Ok(())
+    } else if provider == "anthropic" {
+        Err(
+            "Claude credentials are managed by the Claude CLI; log out of Claude instead"
+                .to_string(),
+        )
+    } else {
+        Err(format!("Unknown auth provider: {}", provider))
+    }
+}
+
+/// auth.json keys belonging to a provider (`<provider>_credentials` and any other
+/// `<provider>_*` entries left by an interrupted flow)
+fn provider_auth_keys(provider: &str, keys: Vec<String>) -> Vec<String> {
+    let prefix = format!("{}_", provider);
+    keys.into_iter()
+        .filter(|key| !provider.is_empty() && key.starts_with(&prefix))
+        .collect()
+}
+
+/// Poll until the port can be bound again, or the timeout passes
+async fn wait_for_port_release(port: u16, timeout: std::time::Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+}
+
 async fn store_credentials(app: &AppHandle, provider: &str, creds: &OAuthCredentials) -> Result<(), String> {
     let store = app
         .store("auth.json")
@@ -623,3 +1083,230 @@ async fn load_credentials(app: &AppHandle, provider: &str) -> Result<Option<OAut
         None => Ok(None),
     }
 }
+
+/// auth.json key of a provider's cached user identity
+fn identity_key(provider: &str) -> String {
+    format!("{}_identity", provider)
+}
+
+fn clear_cached_identity(app: &AppHandle, provider: &str) -> Result<(), String> {
+    let store = app
+        .store("auth.json")
+        .map_err(|e| format!("Failed to open store: {}", e))?;
+    if store.delete(identity_key(provider)) {
+        store
+            .save()
+            .map_err(|e| format!("Failed to save store: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Whether a userinfo failure is due to the token missing the openid/email/profile scopes
+/// Google reports this as 401/403 with `insufficient_scope` or ACCESS_TOKEN_SCOPE_INSUFFICIENT
+fn is_insufficient_scope(status: u16, www_authenticate: Option<&str>, body: &str) -> bool {
+    if status != 401 && status != 403 {
+        return false;
+    }
+    www_authenticate.is_some_and(|v| v.contains("insufficient_scope"))
+        || body.contains("insufficient_scope")
+        || body.contains("ACCESS_TOKEN_SCOPE_INSUFFICIENT")
+}
+
+/// Authorization URL for the consent screen; scopes are space-separated per RFC 6749
+fn build_auth_url(
+    auth_url: &str,
+    client_id: &str,
+    redirect_uri: &str,
+    scopes: &[String],
+) -> String {
+    format!(
+        "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent",
+        auth_url,
+        urlencoding::encode(client_id),
+        urlencoding::encode(redirect_uri),
+        urlencoding::encode(&scopes.join(" "))
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_url() {
+        assert_eq!(
+            extract_url("Visit https://claude.ai/oauth?code=abc to log in."),
+            Some("https://claude.ai/oauth?code=abc")
+        );
+        assert_eq!(
+            extract_url("Opening browser (http://localhost:54545/callback)."),
+            Some("http://localhost:54545/callback")
+        );
+        assert_eq!(extract_url("Waiting for authentication..."), None);
+    }
+
+    #[test]
+    fn test_provider_auth_keys() {
+        let keys = vec![
+            "google_credentials".to_string(),
+            "google_pending_state".to_string(),
+            "googleish_credentials".to_string(),
+            "anthropic_credentials".to_string(),
+        ];
+
+        assert_eq!(
+            provider_auth_keys("google", keys.clone()),
+            vec!["google_credentials", "google_pending_state"]
+        );
+        assert_eq!(
+            provider_auth_keys("anthropic", keys.clone()),
+            vec!["anthropic_credentials"]
+        );
+        assert!(provider_auth_keys("", keys).is_empty());
+    }
+
+    #[test]
+    fn test_validate_reset_provider() {
+        assert!(validate_reset_provider("google").is_ok());
+        assert!(validate_reset_provider("anthropic")
+            .unwrap_err()
+            .contains("Claude CLI"));
+        assert!(validate_reset_provider("gogle")
+            .unwrap_err()
+            .starts_with("Unknown auth provider"));
+        assert!(validate_reset_provider("").is_err());
+    }
+
+    #[test]
+    fn test_wait_for_port_release() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let timeout = std::time::Duration::from_millis(100);
+
+        assert!(!runtime.block_on(wait_for_port_release(port, timeout)));
+        drop(listener);
+        assert!(runtime.block_on(wait_for_port_release(port, timeout)));
+    }
+
+    #[test]
+    fn test_scopes_assembled_into_auth_url() {
+        let scopes = scopes_or_default(Some(serde_json::json!([
+            "https://www.googleapis.com/auth/cloud-platform.read-only",
+            " ",
+            "openid"
+        ])));
+        assert_eq!(
+            build_auth_url(GOOGLE_AUTH_URL, "id", "http://127.0.0.1:1", &scopes),
+            format!(
+                "{}?client_id=id&redirect_uri=http%3A%2F%2F127.0.0.1%3A1&response_type=code\
+                 &scope=https%3A%2F%2Fwww.googleapis.com%2Fauth%2Fcloud-platform.read-only%20openid\
+                 &access_type=offline&prompt=consent",
+                GOOGLE_AUTH_URL
+            )
+        );
+
+        // Unset, empty or malformed settings fall back to the default scope
+        for configured in [
+            None,
+            Some(serde_json::json!([])),
+            Some(serde_json::json!("openid")),
+        ] {
+            assert_eq!(scopes_or_default(configured), DEFAULT_GOOGLE_SCOPES);
+        }
+    }
+
+    #[test]
+    fn test_is_insufficient_scope() {
+        assert!(is_insufficient_scope(
+            403,
+            Some(r#"Bearer realm="https://accounts.google.com/", error="insufficient_scope""#),
+            ""
+        ));
+        assert!(is_insufficient_scope(
+            403,
+            None,
+            r#"{"error":{"status":"PERMISSION_DENIED","details":[{"reason":"ACCESS_TOKEN_SCOPE_INSUFFICIENT"}]}}"#
+        ));
+        // Expired/invalid tokens are real errors
+        assert!(!is_insufficient_scope(
+            401,
+            Some(r#"Bearer error="invalid_token""#),
+            ""
+        ));
+        assert!(!is_insufficient_scope(200, None, "insufficient_scope"));
+    }
+
+    #[test]
+    fn test_google_identity_from_userinfo() {
+        let identity: GoogleIdentity = serde_json::from_str(
+            r#"{"sub":"1","email":"alice@example.com","email_verified":true,"name":"Alice","picture":"https://example.com/a.png"}"#,
+        )
+        .unwrap();
+        assert_eq!(identity.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(identity.name.as_deref(), Some("Alice"));
+        assert_eq!(identity_key("google"), "google_identity");
+    }
+
+    #[test]
+    fn test_provider_statuses() {
+        let auth = AuthStatusResponse {
+            google: true,
+            anthropic: false,
+        };
+        let statuses = provider_statuses(&auth, None);
+        let summary: Vec<(&str, bool, &str)> = statuses
+            .iter()
+            .map(|s| (s.provider.as_str(), s.ready, s.status.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("google", true, "ready"),
+                ("anthropic", false, "not_authenticated"),
+                ("gemini", false, "not_configured"),
+            ]
+        );
+
+        let gemini = |result| provider_statuses(&auth, Some(result)).pop().unwrap();
+        let valid = gemini(Ok(ValidateApiKeyResult {
+            valid: true,
+            error: None,
+        }));
+        assert!(valid.ready);
+        let invalid = gemini(Ok(ValidateApiKeyResult {
+            valid: false,
+            error: Some("Invalid API key (400)".to_string()),
+        }));
+        assert_eq!(
+            (invalid.status.as_str(), invalid.message.as_str()),
+            ("invalid", "Invalid API key (400)")
+        );
+        assert_eq!(gemini(Err("Network error".to_string())).status, "error");
+    }
+
+    #[test]
+    fn test_oauth_registry_replaces_running_flow() {
+        let registry = OAuthRegistry::new();
+        let (old_id, mut old_rx) = registry.register("google");
+        let (new_id, mut new_rx) = registry.register("google");
+
+        // Registering again cancels the running flow
+        assert!(old_rx.try_recv().is_ok());
+        // The old flow finishing leaves the new one registered
+        registry.remove("google", old_id);
+        assert!(registry.cancel("google"));
+        assert!(new_rx.try_recv().is_ok());
+
+        registry.remove("google", new_id);
+        assert!(!registry.cancel("google"));
+    }
+}
@@ -5,8 +5,20 @@
 
 use serde::{Deserialize, Serialize};
 use std::process::Command;
+use std::sync::Mutex;
 use crate::shell::{get_robust_path_env, resolve_binary_path};
 
+pub(crate) const CLAUDE_INSTALL_URL: &str = "https://docs.anthropic.com/en/docs/claude-code";
+
+// Oldest claude CLI with the `-p`, `--dangerously-skip-permissions` and
+// `--permission-mode plan` flags spawn_streaming_process relies on
+const MIN_CLAUDE_VERSION: (u32, u32, u32) = (1, 0, 0);
+// Newest major version verified against those flags
+const MAX_CLAUDE_MAJOR: u32 = 2;
+
+// Compatibility check result, computed once per session
+static CLAUDE_COMPATIBILITY: Mutex<Option<ClaudeCompatibility>> = Mutex::new(None);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DependencyStatus {
@@ -24,6 +36,17 @@ pub struct DependencyCheckResult {
     pub dependencies: Vec<DependencyStatus>,
 }
 
+/// Pre-flight result for the claude CLI; a warning, never a hard failure
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeCompatibility {
+    pub installed: bool,
+    pub version: Option<String>,
+    pub compatible: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
 /// Check if a command exists and get its version
 fn check_command(cmd: &str, version_args: &[&str]) -> (bool, Option<String>) {
     // Resolve absolute path to binary (critical for macOS .app bundles)
@@ -59,7 +82,7 @@ pub fn check_dependencies() -> DependencyCheckResult {
         name: "Claude Code CLI".to_string(),
         installed: claude_installed,
         version: claude_version,
-        install_url: "https://docs.anthropic.com/en/docs/claude-code".to_string(),
+        install_url: CLAUDE_INSTALL_URL.to_string(),
         description: "Required for AI code generation and tests".to_string(),
     });
 
@@ -70,3 +93,104 @@ pub fn check_dependencies() -> DependencyCheckResult {
         dependencies,
     }
 }
+
+/// Check the installed claude CLI against the known-compatible version range
+/// The result is cached for the session; pass `refresh` to re-run `claude --version`
+#[tauri::command]
+pub fn check_claude_compatibility(refresh: Option<bool>) -> ClaudeCompatibility {
+    let mut cached = CLAUDE_COMPATIBILITY.lock().unwrap();
+    if let Some(result) = cached.as_ref().filter(|_| !refresh.unwrap_or(false)) {
+        return result.clone();
+    }
+
+    let (installed, version) = check_command("claude", &["--version"]);
+    let result = claude_version_gate(installed, version);
+    if let Some(warning) = &result.warning {
+        log::warn!("[deps] {}", warning);
+    }
+    *cached = Some(result.clone());
+    result
+}
+
+/// Compare a `claude --version` line against the compatible range
+fn claude_version_gate(installed: bool, version: Option<String>) -> ClaudeCompatibility {
+    let parsed = version.as_deref().and_then(parse_version);
+    let warning = if !installed {
+        Some("Claude Code CLI was not found on PATH".to_string())
+    } else {
+        match parsed {
+            None => Some(format!(
+                "Could not determine the Claude Code CLI version from {:?}",
+                version.as_deref().unwrap_or_default()
+            )),
+            Some(v) if v < MIN_CLAUDE_VERSION => Some(format!(
+                "Claude Code CLI {}.{}.{} is older than the minimum supported {}.{}.{}; please upgrade",
+                v.0, v.1, v.2, MIN_CLAUDE_VERSION.0, MIN_CLAUDE_VERSION.1, MIN_CLAUDE_VERSION.2
+            )),
+            Some(v) if v.0 > MAX_CLAUDE_MAJOR => Some(format!(
+                "Claude Code CLI {}.{}.{} is newer than the tested {}.x releases; some flags may have changed",
+                v.0, v.1, v.2, MAX_CLAUDE_MAJOR
+            )),
+            Some(_) => None,
+        }
+    };
+
+    ClaudeCompatibility {
+        installed,
+        version,
+        compatible: warning.is_none(),
+        warning,
+    }
+}
+
+/// First `major.minor.patch` token in version output, e.g. `1.0.3 (Claude Code)`
+fn parse_version(text: &str) -> Option<(u32, u32, u32)> {
+    text.split_whitespace().find_map(|token| {
+        let mut parts = token.trim_start_matches('v').splitn(3, '.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        // Ignore pre-release/build suffixes on the patch component
+        let patch = parts.next()?;
+        let digits = patch.split(|c: char| !c.is_ascii_digit()).next()?;
+        Some((major, minor, digits.parse().ok()?))
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.0.3 (Claude Code)"), Some((1, 0, 3)));
+        assert_eq!(parse_version("claude v2.1.0-beta.1"), Some((2, 1, 0)));
+        assert_eq!(parse_version("Claude Code"), None);
+        assert_eq!(parse_version("1.2"), None);
+    }
+
+    #[test]
+    fn test_claude_version_gate() {
+        let gate = |version: &str| claude_version_gate(true, Some(version.to_string()));
+
+        assert!(gate("1.0.3 (Claude Code)").compatible);
+        assert!(gate("2.0.14 (Claude Code)").warning.is_none());
+
+        let old = gate("0.2.9 (Claude Code)");
+        assert!(!old.compatible);
+        assert!(old.warning.unwrap().contains("older than the minimum"));
+
+        let new = gate("3.0.0 (Claude Code)");
+        assert!(!new.compatible);
+        assert!(new.warning.unwrap().contains("newer than the tested"));
+
+        assert!(!gate("unknown").compatible);
+
+        let missing = claude_version_gate(false, None);
+        assert!(!missing.installed);
+        assert!(!missing.compatible);
+    }
+}
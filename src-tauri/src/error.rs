@@ -0,0 +1,167 @@
+// ============================================================================
+// Error Types
+// Structured errors returned to the frontend as { code, message }
+// ============================================================================
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    NotFound(String),
+    PermissionDenied(String),
+    NotAGitRepo,
+    NetworkError(String),
+    NotAuthenticated(String),
+    InvalidInput(String),
+    Io(String),
+    CommandFailed(String),
+    Internal(String),
+}
+
+impl AppError {
+    /// Stable, machine-readable code for the frontend to branch on
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::PermissionDenied(_) => "PERMISSION_DENIED",
+            AppError::NotAGitRepo => "NOT_A_GIT_REPO",
+            AppError::NetworkError(_) => "NETWORK_ERROR",
+            AppError::NotAuthenticated(_) => "NOT_AUTHENTICATED",
+            AppError::InvalidInput(_) => "INVALID_INPUT",
+            AppError::Io(_) => "IO_ERROR",
+            AppError::CommandFailed(_) => "COMMAND_FAILED",
+            AppError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// Human-readable message suitable for display
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::NotAGitRepo => "Not a git repository",
+            AppError::NotFound(msg)
+            | AppError::PermissionDenied(msg)
+            | AppError::NetworkError(msg)
+            | AppError::NotAuthenticated(msg)
+            | AppError::InvalidInput(msg)
+            | AppError::Io(msg)
+            | AppError::CommandFailed(msg)
+            | AppError::Internal(msg) => msg,
+        }
+    }
+
+    /// Wrap an I/O error with context, keeping not-found/permission kinds distinct
+    pub fn io(context: &str, err: std::io::Error) -> Self {
+        let message = format!("{}: {}", context, err);
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(message),
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied(message),
+            _ => AppError::Io(message),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(err.to_string()),
+            std::io::ErrorKind::PermissionDenied => AppError::PermissionDenied(err.to_string()),
+            _ => AppError::Io(err.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(err: reqwest::Error) -> Self {
+        AppError::NetworkError(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::InvalidInput(format!("Invalid JSON: {}", err))
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<AppError> for String {
+    fn from(err: AppError) -> Self {
+        err.to_string()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_to_code_and_message() {
+        let value =
+            serde_json::to_value(AppError::NotFound("Spec file not found: a.md".to_string()))
+                .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "code": "NOT_FOUND", "message": "Spec file not found: a.md" })
+        );
+
+        let value = serde_json::to_value(AppError::NotAGitRepo).unwrap();
+        assert_eq!(value["code"], "NOT_A_GIT_REPO");
+        assert_eq!(value["message"], "Not a git repository");
+    }
+
+    #[test]
+    fn test_display_uses_message() {
+        let err = AppError::InvalidInput("Path is required".to_string());
+        assert_eq!(err.to_string(), "Path is required");
+        assert_eq!(String::from(err), "Path is required");
+    }
+
+    #[test]
+    fn test_io_error_kinds_are_preserved() {
+        let not_found = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        assert_eq!(
+            AppError::io("Failed to read spec file", not_found),
+            AppError::NotFound("Failed to read spec file: missing".to_string())
+        );
+
+        let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        assert_eq!(AppError::from(denied).code(), "PERMISSION_DENIED");
+
+        let other = std::io::Error::other("boom");
+        assert_eq!(AppError::from(other).code(), "IO_ERROR");
+    }
+}
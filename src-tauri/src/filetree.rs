@@ -4,10 +4,24 @@
 // Uses the 'ignore' crate to automatically respect .gitignore rules
 // ============================================================================
 
+use crate::search::{build_walker, is_cancelled, registry_key, with_cancellation, SearchRegistry};
+use crate::workspace::SPECSTUDIO_IGNORE_FILE;
 use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+
+// Default number of files returned by recent_files
+const DEFAULT_RECENT_FILES_LIMIT: usize = 50;
+
+// Id space of directory_size ids in the SearchRegistry
+const DIRECTORY_SIZE_ID_KIND: &str = "directory-size";
 
 // ============================================================================
 // Types
@@ -23,6 +37,9 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub modified: Option<bool>,
+    /// SHA-256 of the file contents, only computed when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,17 +50,47 @@ pub struct FileTreeResult {
     pub total_dirs: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFileCount {
+    pub total_files: usize,
+    pub total_dirs: usize,
+    /// Sum of file sizes from metadata (contents are never read)
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectorySize {
+    pub path: String,
+    pub total_bytes: u64,
+    pub file_count: usize,
+    /// Totals are partial when the walk was cancelled
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    pub path: String,
+    /// Unix timestamp (milliseconds) of the last modification
+    pub modified_at: i64,
+    pub size: u64,
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
 /// Get file tree for a working directory
 /// Uses the 'ignore' crate to automatically respect .gitignore rules
+/// `include_hashes` adds per-file content hashes for change detection without git (reads every file)
 #[tauri::command]
 pub fn get_file_tree(
     working_directory: String,
     max_depth: Option<usize>,
     changed_files: Option<Vec<String>>,
+    include_hashes: Option<bool>,
 ) -> Result<FileTreeResult, String> {
     let cwd = Path::new(&working_directory);
 
@@ -70,6 +117,7 @@ pub fn get_file_tree(
         cwd,
         max_depth,
         &changed_set,
+        include_hashes.unwrap_or(false),
         &mut total_files,
         &mut total_dirs,
     )?;
@@ -80,6 +128,7 @@ pub fn get_file_tree(
         is_dir: true,
         children: Some(children),
         modified: None,
+        content_hash: None,
     };
 
     Ok(FileTreeResult {
@@ -89,11 +138,150 @@ pub fn get_file_tree(
     })
 }
 
+/// Count files, directories and bytes a search/context walk would visit
+/// Cheap pre-flight estimate using the same ignore rules as search
+#[tauri::command]
+pub fn count_workspace_files(working_directory: String) -> Result<WorkspaceFileCount, String> {
+    let cwd = Path::new(&working_directory);
+
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err("Working directory does not exist".to_string());
+    }
+
+    let mut count = WorkspaceFileCount {
+        total_files: 0,
+        total_dirs: 0,
+        total_bytes: 0,
+    };
+
+    for entry in build_walker(cwd).flatten() {
+        // Skip the root itself
+        if entry.depth() == 0 {
+            continue;
+        }
+
+        match entry.file_type() {
+            Some(file_type) if file_type.is_dir() => count.total_dirs += 1,
+            Some(file_type) if file_type.is_file() => {
+                count.total_files += 1;
+                count.total_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(count)
+}
+
+/// Total size and file count of a workspace subdirectory, respecting .gitignore
+/// Symlinks are not followed, so cycles can't occur; pass a `size_id` to allow
+/// cancelling huge trees via `cancel_directory_size`
+/// Runs off the main thread so the cancel can arrive while the walk is in flight
+#[tauri::command(async)]
+pub fn directory_size(
+    app: AppHandle,
+    working_directory: String,
+    relative_path: String,
+    size_id: Option<String>,
+) -> Result<DirectorySize, String> {
+    with_cancellation(&app, DIRECTORY_SIZE_ID_KIND, size_id, |cancel| {
+        directory_size_internal(Path::new(&working_directory), &relative_path, cancel)
+    })
+}
+
+/// Request cancellation of an in-progress directory_size
+/// Returns false if no walk with this id is running
+#[tauri::command(async)]
+pub fn cancel_directory_size(app: AppHandle, size_id: String) -> bool {
+    app.state::<SearchRegistry>()
+        .cancel(&registry_key(DIRECTORY_SIZE_ID_KIND, &size_id))
+}
+
+fn directory_size_internal(
+    cwd: &Path,
+    relative_path: &str,
+    cancel: Option<&AtomicBool>,
+) -> Result<DirectorySize, String> {
+    let dir =
+        crate::workspace::resolve_workspace_path(cwd, relative_path).map_err(|e| e.to_string())?;
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", relative_path));
+    }
+
+    let mut size = DirectorySize {
+        path: relative_path.to_string(),
+        total_bytes: 0,
+        file_count: 0,
+        cancelled: false,
+    };
+
+    for entry in build_walker(&dir).flatten() {
+        if is_cancelled(cancel) {
+            size.cancelled = true;
+            break;
+        }
+
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            size.file_count += 1;
+            size.total_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+
+    Ok(size)
+}
+
+/// Most recently modified files in the workspace, newest first
+/// Respects .gitignore, so it also surfaces uncommitted and non-git changes
+#[tauri::command]
+pub fn recent_files(
+    working_directory: String,
+    limit: Option<usize>,
+) -> Result<Vec<RecentFile>, String> {
+    let cwd = Path::new(&working_directory);
+
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err("Working directory does not exist".to_string());
+    }
+
+    let mut files: Vec<(SystemTime, RecentFile)> = build_walker(cwd)
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            let datetime: chrono::DateTime<chrono::Utc> = modified.into();
+            let path = entry
+                .path()
+                .strip_prefix(cwd)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .to_string();
+            Some((
+                modified,
+                RecentFile {
+                    path,
+                    modified_at: datetime.timestamp_millis(),
+                    size: metadata.len(),
+                },
+            ))
+        })
+        .collect();
+
+    // Ties (e.g. files written by one command) fall back to path order
+    files.sort_by(|(a_time, a), (b_time, b)| {
+        b_time.cmp(a_time).then_with(|| a.path.cmp(&b.path))
+    });
+    files.truncate(limit.unwrap_or(DEFAULT_RECENT_FILES_LIMIT));
+
+    Ok(files.into_iter().map(|(_, file)| file).collect())
+}
+
 /// Build file tree using the 'ignore' crate which respects .gitignore
 fn build_tree_with_ignore(
     base: &Path,
     max_depth: usize,
     changed_files: &std::collections::HashSet<String>,
+    include_hashes: bool,
     total_files: &mut usize,
     total_dirs: &mut usize,
 ) -> Result<Vec<FileNode>, String> {
@@ -105,6 +293,8 @@ fn build_tree_with_ignore(
         .git_global(true) // Respect global gitignore
         .git_exclude(true) // Respect .git/info/exclude
         .standard_filters(true) // Apply standard ignore filters
+        .add_custom_ignore_filename(SPECSTUDIO_IGNORE_FILE) // Overrides .gitignore
+        .follow_links(false) // Never follow symlinks (cycles, escaping the workspace)
         .build();
 
     // Collect all entries into a map organized by parent directory
@@ -145,6 +335,11 @@ fn build_tree_with_ignore(
         }
 
         let is_modified = changed_files.contains(&relative_path);
+        let content_hash = if include_hashes && !is_dir {
+            hash_file(path).ok()
+        } else {
+            None
+        };
 
         let node = FileNode {
             name: file_name,
@@ -152,6 +347,7 @@ fn build_tree_with_ignore(
             is_dir,
             children: if is_dir { Some(Vec::new()) } else { None },
             modified: if is_modified { Some(true) } else { None },
+            content_hash,
         };
 
         // Get parent directory
@@ -198,6 +394,14 @@ fn build_tree_recursive(
     nodes
 }
 
+/// Hex-encoded SHA-256 of a file's contents, streamed to avoid loading it whole
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 /// Sort nodes: directories first, then alphabetically
 fn sort_nodes(nodes: &mut [FileNode]) {
     nodes.sort_by(|a, b| {
@@ -215,3 +419,124 @@ fn sort_nodes(nodes: &mut [FileNode]) {
         }
     }
 }
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_count_workspace_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir_all(dir_path.join("src/nested")).unwrap();
+        fs::write(dir_path.join("README.md"), "12345").unwrap();
+        fs::write(dir_path.join("src/main.rs"), "123").unwrap();
+        fs::write(dir_path.join("src/nested/lib.rs"), "12").unwrap();
+        fs::write(dir_path.join(SPECSTUDIO_IGNORE_FILE), "README.md\n").unwrap();
+
+        let count = count_workspace_files(dir_path.to_string_lossy().to_string()).unwrap();
+
+        // README.md is ignored; the hidden .specstudioignore is skipped by default
+        assert_eq!(count.total_files, 2);
+        assert_eq!(count.total_dirs, 2);
+        assert_eq!(count.total_bytes, 5);
+    }
+
+    #[test]
+    fn test_content_hashes_are_stable_and_optional() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::write(dir_path.join("a.txt"), "same content").unwrap();
+        fs::write(dir_path.join("b.txt"), "same content").unwrap();
+        fs::write(dir_path.join("c.txt"), "other content").unwrap();
+
+        let working_directory = dir_path.to_string_lossy().to_string();
+        let hashes = |tree: FileTreeResult| -> Vec<Option<String>> {
+            tree.root
+                .children
+                .unwrap()
+                .into_iter()
+                .map(|node| node.content_hash)
+                .collect()
+        };
+
+        let first =
+            hashes(get_file_tree(working_directory.clone(), None, None, Some(true)).unwrap());
+        assert!(first[0].is_some());
+        assert_eq!(first[0], first[1]);
+        assert_ne!(first[0], first[2]);
+
+        // Re-fetching unchanged content yields identical hashes
+        let second =
+            hashes(get_file_tree(working_directory.clone(), None, None, Some(true)).unwrap());
+        assert_eq!(first, second);
+
+        // Hashing is opt-in
+        let unhashed = hashes(get_file_tree(working_directory, None, None, None).unwrap());
+        assert!(unhashed.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_directory_size() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        fs::create_dir_all(dir_path.join("src/nested")).unwrap();
+        fs::write(dir_path.join("README.md"), "1234567890").unwrap();
+        fs::write(dir_path.join("src/main.rs"), "1234").unwrap();
+        fs::write(dir_path.join("src/nested/lib.rs"), "123").unwrap();
+        fs::write(dir_path.join("src/debug.log"), "ignored").unwrap();
+        fs::write(dir_path.join(".gitignore"), "*.log\n").unwrap();
+        std::process::Command::new("git")
+            .arg("init")
+            .current_dir(dir_path)
+            .output()
+            .unwrap();
+
+        let size = directory_size_internal(dir_path, "src", None).unwrap();
+        assert_eq!(size.file_count, 2);
+        assert_eq!(size.total_bytes, 7);
+        assert!(!size.cancelled);
+
+        // Paths outside the workspace and plain files are rejected
+        assert!(directory_size_internal(dir_path, "../", None).is_err());
+        assert!(directory_size_internal(dir_path, "README.md", None).is_err());
+
+        let cancel = AtomicBool::new(true);
+        let size = directory_size_internal(dir_path, "", Some(&cancel)).unwrap();
+        assert!(size.cancelled);
+    }
+
+    #[test]
+    fn test_recent_files_ordered_by_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        let now = SystemTime::now();
+
+        fs::create_dir_all(dir_path.join("src")).unwrap();
+        for (path, age_secs) in [("old.txt", 300), ("src/newest.rs", 10), ("middle.md", 100)] {
+            let file = fs::File::create(dir_path.join(path)).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(age_secs))
+                .unwrap();
+        }
+
+        let working_directory = dir_path.to_string_lossy().to_string();
+        let paths = |files: Vec<RecentFile>| files.into_iter().map(|f| f.path).collect::<Vec<_>>();
+
+        assert_eq!(
+            paths(recent_files(working_directory.clone(), None).unwrap()),
+            vec!["src/newest.rs", "middle.md", "old.txt"]
+        );
+        assert_eq!(
+            paths(recent_files(working_directory, Some(2)).unwrap()),
+            vec!["src/newest.rs", "middle.md"]
+        );
+    }
+}
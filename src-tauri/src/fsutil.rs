@@ -0,0 +1,55 @@
+// ============================================================================
+// Filesystem Utilities
+// Write helpers shared by the modules that modify workspace files
+// ============================================================================
+
+use crate::error::AppError;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Write via a temp file in the same directory, then rename over the target
+/// The target's permissions are kept when it already exists
+pub(crate) fn write_atomic(path: &Path, content: &str) -> Result<(), AppError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| AppError::io("Failed to create temp file", e))?;
+
+    temp.write_all(content.as_bytes())
+        .map_err(|e| AppError::io("Failed to write temp file", e))?;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = fs::set_permissions(temp.path(), metadata.permissions());
+    }
+
+    temp.persist(path)
+        .map_err(|e| AppError::io(&format!("Failed to replace {}", path.display()), e.error))?;
+
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.md");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        // No temp files are left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        assert!(matches!(
+            write_atomic(&temp_dir.path().join("missing/notes.md"), "x"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -4,24 +4,72 @@
 // Uses API key authentication (no OAuth required)
 // ============================================================================
 
-use futures_util::StreamExt;
-use reqwest::Client;
+use crate::settings::{keys, SETTINGS_STORE};
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{AppHandle, Emitter};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::AbortHandle;
 use tauri_plugin_store::StoreExt;
 
 // Default model if none specified
 const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+// Seconds without any streamed data before the response is treated as stalled
+const DEFAULT_STALL_TIMEOUT_SECS: u64 = 60;
+// Outbound requests per minute shared across all chat sessions
+const DEFAULT_REQUESTS_PER_MINUTE: u32 = 60;
+// Harm categories and thresholds accepted in `safetySettings`
+pub(crate) const SAFETY_CATEGORIES: &[&str] = &[
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+    "HARM_CATEGORY_CIVIC_INTEGRITY",
+];
+pub(crate) const SAFETY_THRESHOLDS: &[&str] = &[
+    "BLOCK_LOW_AND_ABOVE",
+    "BLOCK_MEDIUM_AND_ABOVE",
+    "BLOCK_ONLY_HIGH",
+    "BLOCK_NONE",
+    "OFF",
+];
+// USD per million input tokens; overridden per model by the `modelPrices` setting
+const DEFAULT_MODEL_PRICES: &[(&str, f64)] = &[
+    ("gemini-2.5-pro", 1.25),
+    ("gemini-2.5-flash", 0.30),
+    ("gemini-2.5-flash-lite", 0.10),
+    ("gemini-2.0-flash", 0.10),
+    ("gemini-2.0-flash-lite", 0.075),
+];
+// Rough average for English text and code when the count endpoint is unavailable
+const CHARS_PER_TOKEN: usize = 4;
+// Instructions sent ahead of the workspace files by generate_project_summary
+const PROJECT_SUMMARY_PROMPT: &str = "Write a concise architecture summary of this project in \
+Markdown (at most ~500 words): its purpose, main components and how they interact, key \
+technologies, and conventions a contributor should follow. Do not list every file.";
+// Overall timeout for the summary request: generating over the full workspace context
+// routinely takes longer than the shared HTTP request timeout
+const SUMMARY_TIMEOUT_SECS: u64 = 300;
 
 // ============================================================================
 // Types
 // ============================================================================
 
+/// A history entry: `user`/`assistant` text, or a `tool_call`/`tool_result` turn.
+/// Tool entries name the tool in `tool_name` (or carry the `{"name", "args"}` JSON
+/// emitted with `tool_call` events as `content`); results put the output in `content`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_args: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +79,8 @@ pub struct StreamEvent {
     pub event_type: String,
     pub data: String,
     pub timestamp: u64,
+    /// Chat session that produced the event, for routing concurrent chats
+    pub session_id: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,7 +97,70 @@ pub struct ValidateApiKeyResult {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatInfo {
+    pub session_id: String,
+    pub model: String,
+    /// Unix timestamp (milliseconds) when the chat started
+    pub started_at: u64,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationTestResult {
+    pub success: bool,
+    pub model: String,
+    pub latency_ms: u64,
+    /// HTTP status, absent if the request never reached the API
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Error body (or network error) on failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextCostEstimate {
+    pub model: String,
+    pub tokens: u64,
+    /// "api" when counted by Gemini's countTokens, "heuristic" for the local estimate
+    pub method: String,
+    /// Absent when no price is known for the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_per_million_tokens: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
+    pub total_files: usize,
+    pub total_size: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSummary {
+    pub content: String,
+    /// Unix timestamp (milliseconds) when the summary was generated
+    pub generated_at: u64,
+    /// Served from `.specstudio/summary.md` instead of being regenerated
+    pub cached: bool,
+}
+
 // Gemini API types
+#[derive(Debug, Serialize)]
+struct CountTokensRequest {
+    contents: Vec<GeminiContent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CountTokensResponse {
+    total_tokens: Option<u64>,
+}
+
 #[derive(Debug, Serialize)]
 struct GeminiRequest {
     contents: Vec<GeminiContent>,
@@ -55,6 +168,15 @@ struct GeminiRequest {
     generation_config: Option<GenerationConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<Tool>>,
+    /// Per-category block thresholds; Gemini defaults apply when empty
+    #[serde(rename = "safetySettings", skip_serializing_if = "Vec::is_empty")]
+    safety_settings: Vec<SafetySetting>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct SafetySetting {
+    category: String,
+    threshold: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -77,8 +199,31 @@ struct GeminiContent {
 }
 
 #[derive(Debug, Serialize)]
-struct GeminiPart {
-    text: String,
+#[serde(untagged)]
+enum GeminiPart {
+    Text {
+        text: String,
+    },
+    FunctionCall {
+        #[serde(rename = "functionCall")]
+        function_call: FunctionCallPart,
+    },
+    FunctionResponse {
+        #[serde(rename = "functionResponse")]
+        function_response: FunctionResponsePart,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionCallPart {
+    name: String,
+    args: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct FunctionResponsePart {
+    name: String,
+    response: serde_json::Value,
 }
 
 #[derive(Debug, Serialize)]
@@ -96,11 +241,42 @@ struct GenerationConfig {
 struct GeminiStreamResponse {
     candidates: Option<Vec<GeminiCandidate>>,
     error: Option<GeminiError>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<UsageMetadata>,
+    #[serde(rename = "promptFeedback")]
+    prompt_feedback: Option<PromptFeedback>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptFeedback {
+    block_reason: Option<String>,
+    #[serde(default)]
+    safety_ratings: Vec<SafetyRating>,
 }
 
 #[derive(Debug, Deserialize)]
+struct SafetyRating {
+    category: String,
+    #[serde(default)]
+    blocked: bool,
+    probability: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UsageMetadata {
+    prompt_token_count: Option<u64>,
+    candidates_token_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiCandidate {
     content: Option<GeminiContentResponse>,
+    finish_reason: Option<String>,
+    #[serde(default)]
+    safety_ratings: Vec<SafetyRating>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -124,16 +300,194 @@ struct FunctionCall {
 #[derive(Debug, Deserialize)]
 struct GeminiError {
     message: String,
-    #[allow(dead_code)] // Part of Gemini API response, kept for completeness
     code: Option<i32>,
 }
 
+// ============================================================================
+// Chat Registry
+// ============================================================================
+
+struct ChatHandle {
+    abort: AbortHandle,
+    info: ChatInfo,
+}
+
+/// Lifecycle of running Gemini streams: abort handles, model and usage per session
+/// Registered by chat_with_gemini and removed when the stream task ends
+pub struct ChatRegistry {
+    chats: Mutex<HashMap<String, ChatHandle>>,
+}
+
+impl ChatRegistry {
+    pub fn new() -> Self {
+        Self {
+            chats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, id: String, model: String, handle: AbortHandle) {
+        let mut chats = self.chats.lock().unwrap();
+        // A stream may finish before it is registered; drop any such leftovers
+        chats.retain(|_, h| !h.abort.is_finished());
+        let info = ChatInfo {
+            session_id: id.clone(),
+            model,
+            started_at: get_timestamp(),
+            prompt_tokens: 0,
+            output_tokens: 0,
+        };
+        chats.insert(
+            id,
+            ChatHandle {
+                abort: handle,
+                info,
+            },
+        );
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.chats.lock().unwrap().remove(id);
+    }
+
+    /// Record the model actually serving the session (changes on fallback)
+    fn set_model(&self, id: &str, model: &str) {
+        if let Some(chat) = self.chats.lock().unwrap().get_mut(id) {
+            chat.info.model = model.to_string();
+        }
+    }
+
+    /// Store the latest usage reported by Gemini (counts are cumulative per response)
+    fn record_usage(&self, id: &str, usage: &UsageMetadata) {
+        if let Some(chat) = self.chats.lock().unwrap().get_mut(id) {
+            chat.info.prompt_tokens = usage.prompt_token_count.unwrap_or(0);
+            chat.info.output_tokens = usage.candidates_token_count.unwrap_or(0);
+        }
+    }
+
+    /// Sessions still streaming, oldest first
+    pub fn list(&self) -> Vec<ChatInfo> {
+        let mut chats: Vec<ChatInfo> = self
+            .chats
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|chat| !chat.abort.is_finished())
+            .map(|chat| chat.info.clone())
+            .collect();
+        chats.sort_by_key(|chat| chat.started_at);
+        chats
+    }
+
+    /// Abort every running chat, returning the session ids that were still in flight
+    pub fn abort_all(&self) -> Vec<String> {
+        let mut aborted = Vec::new();
+        for (id, chat) in self.chats.lock().unwrap().drain() {
+            if !chat.abort.is_finished() {
+                chat.abort.abort();
+                aborted.push(id);
+            }
+        }
+        aborted
+    }
+}
+
+impl Default for ChatRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Rate Limiter
+// ============================================================================
+
+/// Token bucket holding up to one minute's worth of requests
+struct TokenBucket {
+    requests_per_minute: u32,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(requests_per_minute: u32, now: Instant) -> Self {
+        Self {
+            requests_per_minute,
+            tokens: requests_per_minute as f64,
+            last_refill: now,
+        }
+    }
+
+    /// Apply a changed cap, clamping any surplus tokens
+    fn set_rate(&mut self, requests_per_minute: u32) {
+        self.requests_per_minute = requests_per_minute;
+        self.tokens = self.tokens.min(requests_per_minute as f64);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        let per_second = self.requests_per_minute as f64 / 60.0;
+        self.tokens = (self.tokens + elapsed * per_second).min(self.requests_per_minute as f64);
+        self.last_refill = now;
+    }
+
+    /// Take a token, or return how long until one is available
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        let per_second = self.requests_per_minute as f64 / 60.0;
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+    }
+}
+
+/// Shared limiter for outbound Gemini requests; callers queue instead of erroring
+pub struct RateLimiter {
+    bucket: Mutex<TokenBucket>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(
+                DEFAULT_REQUESTS_PER_MINUTE,
+                Instant::now(),
+            )),
+        }
+    }
+
+    /// Wait until a request may be sent under the given per-minute cap
+    async fn acquire(&self, requests_per_minute: u32) {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                bucket.set_rate(requests_per_minute);
+                match bucket.try_acquire(Instant::now()) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                }
+            };
+            log::debug!("Gemini rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
 /// Get the JSON schema for Development Plan output
-fn get_development_plan_schema() -> serde_json::Value {
+pub(crate) fn get_development_plan_schema() -> serde_json::Value {
     serde_json::json!({
         "type": "object",
         "properties": {
@@ -200,6 +554,31 @@ fn get_development_plan_schema() -> serde_json::Value {
     })
 }
 
+/// Primary model followed by fallbacks, without blanks or duplicates
+fn candidate_models(primary: &str, fallbacks: &[String]) -> Vec<String> {
+    let mut models: Vec<String> = Vec::new();
+    for model in std::iter::once(primary).chain(fallbacks.iter().map(|s| s.as_str())) {
+        let model = model.trim();
+        if !model.is_empty() && !models.iter().any(|m| m == model) {
+            models.push(model.to_string());
+        }
+    }
+    models
+}
+
+/// Whether an error means the model is overloaded/unavailable (worth trying a fallback)
+fn is_model_unavailable(status: i32, message: &str) -> bool {
+    matches!(status, 404 | 429 | 503)
+        || [
+            "UNAVAILABLE",
+            "RESOURCE_EXHAUSTED",
+            "overloaded",
+            "is not found",
+        ]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
 /// Get the search_files tool definition
 fn get_search_files_tool() -> Tool {
     Tool {
@@ -232,11 +611,12 @@ fn get_timestamp() -> u64 {
         .as_millis() as u64
 }
 
-fn emit_stream_event(app: &AppHandle, event_type: &str, data: &str) {
+pub(crate) fn emit_stream_event(app: &AppHandle, session_id: &str, event_type: &str, data: &str) {
     let event = StreamEvent {
         event_type: event_type.to_string(),
         data: data.to_string(),
         timestamp: get_timestamp(),
+        session_id: session_id.to_string(),
     };
     let _ = app.emit("rpc:stream:data", event);
 }
@@ -244,26 +624,65 @@ fn emit_stream_event(app: &AppHandle, event_type: &str, data: &str) {
 struct GeminiSettings {
     api_key: String,
     model: String,
+    fallback_models: Vec<String>,
+    stall_timeout: Duration,
+    requests_per_minute: u32,
+    safety_settings: Vec<SafetySetting>,
+}
+
+/// How a single model's stream ended
+enum StreamOutcome {
+    /// Carries the streamed text output
+    Completed(String),
+    /// Model overloaded/unavailable before any content was streamed
+    Unavailable(String),
 }
 
 async fn get_settings(app: &AppHandle) -> Result<GeminiSettings, String> {
     let store = app
-        .store("settings.json")
+        .store(SETTINGS_STORE)
         .map_err(|e| format!("Failed to open settings store: {}", e))?;
 
     let api_key = store
-        .get("geminiApiKey")
+        .get(keys::GEMINI_API_KEY)
         .and_then(|v| v.as_str().map(|s| s.to_string()))
         .filter(|s| !s.is_empty())
         .ok_or("Gemini API key not configured. Please set it in Settings.")?;
 
     let model = store
-        .get("geminiModel")
+        .get(keys::GEMINI_MODEL)
         .and_then(|v| v.as_str().map(|s| s.to_string()))
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| DEFAULT_MODEL.to_string());
 
-    Ok(GeminiSettings { api_key, model })
+    // Ordered models to try when the primary is overloaded or unavailable
+    let fallback_models = store
+        .get(keys::GEMINI_MODEL_FALLBACKS)
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect();
+
+    let stall_timeout_secs = store
+        .get(keys::GEMINI_STALL_TIMEOUT_SECS)
+        .and_then(|v| v.as_u64())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_STALL_TIMEOUT_SECS);
+
+    let requests_per_minute =
+        parse_requests_per_minute(store.get(keys::GEMINI_REQUESTS_PER_MINUTE));
+
+    let safety_settings = parse_safety_settings(store.get(keys::GEMINI_SAFETY_SETTINGS));
+
+    Ok(GeminiSettings {
+        api_key,
+        model,
+        fallback_models,
+        stall_timeout: Duration::from_secs(stall_timeout_secs),
+        requests_per_minute,
+        safety_settings,
+    })
 }
 
 // ============================================================================
@@ -277,133 +696,47 @@ pub async fn chat_with_gemini(
     prompt: String,
     history: Option<Vec<ChatMessage>>,
     spec_content: Option<String>,
+    summary_directory: Option<String>,
 ) -> Result<ChatResult, String> {
-    let session_id = format!("chat_{}", get_timestamp());
+    let session_id = crate::shell::unique_id("chat");
 
     // Get settings
     let settings = get_settings(&app).await?;
 
-    // Build the system context with Architect persona
-    let mut system_context = String::new();
-
-    system_context.push_str(r#"# Role: Software Architect
-
-You are an expert Software Architect specializing in creating comprehensive development plans.
-
-## Your Mission
-1. Discuss requirements and architecture in MARKDOWN
-2. Only generate a JSON Development Plan when the user sends the specific trigger phrase
-
-## Output Format
-Default to Markdown. Only output strict JSON when the prompt specifically asks to 'Create a comprehensive development plan'.
-
-When generating JSON, use this structure:
-- title: Overall plan title
-- overview: High-level description
-- phases: Array of development phases
-  - Each phase has: title, description, tickets
-  - Each ticket has: id, title, requirements[], acceptance_criteria[]
-
-## Guidelines
-- Break complex features into logical phases
-- Each ticket should be independently implementable
-- Use clear, actionable language in requirements
-- Define measurable acceptance criteria
-- Consider dependencies between tickets
-
-## Tools Available
-- search_files: Search the codebase to understand existing patterns and structure
-"#);
-
-    if let Some(spec) = &spec_content {
-        system_context.push_str("\n## Current Specification\n");
-        system_context.push_str(spec);
-        system_context.push_str("\n\n");
-    }
-
-    // Build Gemini contents from history
-    let mut contents: Vec<GeminiContent> = Vec::new();
-
-    // Add system context as first user message
-    contents.push(GeminiContent {
-        role: "user".to_string(),
-        parts: vec![GeminiPart {
-            text: system_context,
-        }],
-    });
-    contents.push(GeminiContent {
-        role: "model".to_string(),
-        parts: vec![GeminiPart {
-            text: "I understand. I'm ready to help you architect your solution. I can explore your codebase using search_files and create structured development plans when you're ready. What would you like to discuss?".to_string(),
-        }],
-    });
-
-    // Add conversation history
-    if let Some(hist) = history {
-        for msg in hist {
-            let role = if msg.role == "assistant" {
-                "model"
-            } else {
-                "user"
-            };
-            contents.push(GeminiContent {
-                role: role.to_string(),
-                parts: vec![GeminiPart {
-                    text: msg.content,
-                }],
-            });
-        }
-    }
-
-    // Add current prompt
-    contents.push(GeminiContent {
-        role: "user".to_string(),
-        parts: vec![GeminiPart { text: prompt.clone() }],
-    });
-
-    // Detect if user is requesting a plan (enable strict JSON output)
-    // CRITICAL: This must match the exact phrase from ide-layout.tsx handleCreatePlan
-    let requesting_plan = prompt.contains("Create a comprehensive development plan");
-
-    // Configure generation with optional strict JSON schema
-    let generation_config = if requesting_plan {
-        GenerationConfig {
-            temperature: 0.7,
-            max_output_tokens: 8192,
-            response_mime_type: Some("application/json".to_string()),
-            response_schema: Some(get_development_plan_schema()),
-        }
-    } else {
-        GenerationConfig {
-            temperature: 0.7,
-            max_output_tokens: 8192,
-            response_mime_type: None,
-            response_schema: None,
-        }
-    };
-
-    // Tools cannot be used with JSON response mode (Gemini API limitation)
-    let tools = if requesting_plan {
-        None
-    } else {
-        Some(vec![get_search_files_tool()])
-    };
-
-    let request = GeminiRequest {
-        contents,
-        generation_config: Some(generation_config),
-        tools,
-    };
+    // Cached summary from generate_project_summary, sent instead of full workspace context
+    let project_summary = summary_directory
+        .and_then(|dir| crate::workspace::read_project_summary(Path::new(&dir)))
+        .map(|(content, _)| content);
+
+    let request = build_chat_request(
+        &prompt,
+        history,
+        spec_content.as_deref(),
+        project_summary.as_deref(),
+        settings.safety_settings.clone(),
+    );
 
     // Spawn async task to handle streaming
     let app_clone = app.clone();
-
-    tokio::spawn(async move {
-        if let Err(e) = stream_gemini_response(&app_clone, settings, request).await {
-            emit_stream_event(&app_clone, "error", &e);
-            emit_stream_event(&app_clone, "complete", "Chat ended with error");
+    let task_session_id = session_id.clone();
+    let model = settings.model.clone();
+
+    let handle = tokio::spawn(async move {
+        if let Err(e) =
+            stream_gemini_response(&app_clone, &task_session_id, settings, request).await
+        {
+            emit_stream_event(&app_clone, &task_session_id, "error", &e);
+            emit_stream_event(
+                &app_clone,
+                &task_session_id,
+                "complete",
+                "Chat ended with error",
+            );
         }
+        app_clone.state::<ChatRegistry>().remove(&task_session_id);
     });
+    app.state::<ChatRegistry>()
+        .register(session_id.clone(), model, handle.abort_handle());
 
     Ok(ChatResult {
         started: true,
@@ -413,38 +746,95 @@ When generating JSON, use this structure:
 
 async fn stream_gemini_response(
     app: &AppHandle,
+    session_id: &str,
     settings: GeminiSettings,
     request: GeminiRequest,
-) -> Result<(), String> {
-    let client = Client::new();
-
-    // Build the API URL for Google AI Studio
-    let url = format!(
-        "https://generativelanguage.googleapis.com/v1beta/models/{}:streamGenerateContent?key={}&alt=sse",
-        settings.model, settings.api_key
-    );
-
-    emit_stream_event(app, "output", "");
+) -> Result<String, String> {
+    let models = candidate_models(&settings.model, &settings.fallback_models);
+
+    emit_stream_event(app, session_id, "output", "");
+
+    for (index, model) in models.iter().enumerate() {
+        let has_fallback = index + 1 < models.len();
+        if index > 0 {
+            emit_stream_event(
+                app,
+                session_id,
+                "info",
+                &format!(
+                    "{} is unavailable, falling back to {}",
+                    models[index - 1],
+                    model
+                ),
+            );
+            app.state::<ChatRegistry>().set_model(session_id, model);
+        }
 
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .json(&request)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Gemini: {}", e))?;
+        // Build the API URL for Google AI Studio
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:streamGenerateContent?key={}&alt=sse",
+            model, settings.api_key
+        );
+
+        app.state::<RateLimiter>()
+            .acquire(settings.requests_per_minute)
+            .await;
+
+        let response = crate::http::streaming_post(&url)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to send request to Gemini: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let error_text = response.text().await.unwrap_or_default();
+            if has_fallback && is_model_unavailable(status as i32, &error_text) {
+                log::warn!(
+                    "Gemini model {} unavailable ({}): {}",
+                    model,
+                    status,
+                    error_text
+                );
+                continue;
+            }
+            return Err(format!("Gemini API error: {}", error_text));
+        }
 
-    if !response.status().is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(format!("Gemini API error: {}", error_text));
+        match read_sse_stream(app, session_id, response, settings.stall_timeout).await? {
+            StreamOutcome::Completed(output) => {
+                emit_stream_event(app, session_id, "complete", "Chat completed");
+                return Ok(output);
+            }
+            StreamOutcome::Unavailable(message) if has_fallback => {
+                log::warn!("Gemini model {} unavailable: {}", model, message);
+            }
+            StreamOutcome::Unavailable(message) => {
+                emit_stream_event(app, session_id, "error", &message);
+                return Err(message);
+            }
+        }
     }
 
+    Err("No Gemini model available".to_string())
+}
+
+/// Forward an SSE response as stream events
+/// Fails if no data arrives within `stall_timeout` of the previous chunk
+async fn read_sse_stream(
+    app: &AppHandle,
+    session_id: &str,
+    response: reqwest::Response,
+    stall_timeout: Duration,
+) -> Result<StreamOutcome, String> {
     // Stream the response using SSE
     let mut stream = response.bytes_stream();
     let mut buffer = String::new();
     let mut received_any_content = false;
+    let mut output = String::new();
 
-    while let Some(chunk) = stream.next().await {
+    while let Some(chunk) = next_with_timeout(&mut stream, stall_timeout).await? {
         let chunk = chunk.map_err(|e| format!("Stream error: {}", e))?;
         let text = String::from_utf8_lossy(&chunk);
 
@@ -486,10 +876,30 @@ async fn stream_gemini_response(
                 match serde_json::from_str::<GeminiStreamResponse>(data_line) {
                     Ok(response) => {
                         if let Some(error) = response.error {
-                            emit_stream_event(app, "error", &error.message);
+                            // Nothing streamed yet, so the caller may retry on a fallback model
+                            if !received_any_content
+                                && is_model_unavailable(error.code.unwrap_or(0), &error.message)
+                            {
+                                return Ok(StreamOutcome::Unavailable(error.message));
+                            }
+                            emit_stream_event(app, session_id, "error", &error.message);
                             return Err(error.message);
                         }
 
+                        if let Some(usage) = &response.usage_metadata {
+                            app.state::<ChatRegistry>().record_usage(session_id, usage);
+                        }
+
+                        if let Some(feedback) = &response.prompt_feedback {
+                            if let Some(reason) = &feedback.block_reason {
+                                return Err(format!(
+                                    "Prompt blocked by Gemini ({}){}",
+                                    reason,
+                                    blocked_categories(&feedback.safety_ratings)
+                                ));
+                            }
+                        }
+
                         if let Some(candidates) = response.candidates {
                             for candidate in candidates {
                                 if let Some(content) = candidate.content {
@@ -499,7 +909,10 @@ async fn stream_gemini_response(
                                             if let Some(text) = part.text {
                                                 if !text.is_empty() {
                                                     received_any_content = true;
-                                                    emit_stream_event(app, "output", &text);
+                                                    emit_stream_event(
+                                                        app, session_id, "output", &text,
+                                                    );
+                                                    output.push_str(&text);
                                                 }
                                             }
 
@@ -512,6 +925,7 @@ async fn stream_gemini_response(
                                                 });
                                                 emit_stream_event(
                                                     app,
+                                                    session_id,
                                                     "tool_call",
                                                     &serde_json::to_string(&tool_call_json).unwrap_or_default()
                                                 );
@@ -519,13 +933,30 @@ async fn stream_gemini_response(
                                         }
                                     }
                                 }
+
+                                if candidate.finish_reason.as_deref() == Some("SAFETY") {
+                                    return Err(format!(
+                                        "Response blocked by Gemini safety filters{}. \
+                                         Adjust the safety settings if this is expected.",
+                                        blocked_categories(&candidate.safety_ratings)
+                                    ));
+                                }
                             }
                         }
                     }
                     Err(e) => {
                         log::warn!("Failed to parse Gemini response: {} - {}", e, data_line);
                         // Show parse errors to user for debugging
-                        emit_stream_event(app, "error", &format!("Parse error: {} (data: {}...)", e, &data_line[..data_line.len().min(100)]));
+                        emit_stream_event(
+                            app,
+                            session_id,
+                            "error",
+                            &format!(
+                                "Parse error: {} (data: {}...)",
+                                e,
+                                &data_line[..data_line.len().min(100)]
+                            ),
+                        );
                     }
                 }
             }
@@ -536,27 +967,52 @@ async fn stream_gemini_response(
     if !received_any_content {
         // Check if there's remaining data in buffer
         if !buffer.trim().is_empty() {
-            emit_stream_event(app, "error", &format!("Incomplete response. Remaining buffer: {}...", &buffer[..buffer.len().min(200)]));
+            emit_stream_event(
+                app,
+                session_id,
+                "error",
+                &format!(
+                    "Incomplete response. Remaining buffer: {}...",
+                    &buffer[..buffer.len().min(200)]
+                ),
+            );
         } else {
-            emit_stream_event(app, "error", "No content received from Gemini API");
+            emit_stream_event(
+                app,
+                session_id,
+                "error",
+                "No content received from Gemini API",
+            );
         }
     }
 
-    emit_stream_event(app, "complete", "Chat completed");
-    Ok(())
+    Ok(StreamOutcome::Completed(output))
 }
 
-/// Validate a Gemini API key by making a test request
-#[tauri::command]
-pub async fn validate_gemini_api_key(api_key: String) -> Result<ValidateApiKeyResult, String> {
-    let client = Client::new();
+/// Await the next stream item, failing if none arrives within `timeout`
+async fn next_with_timeout<S>(stream: &mut S, timeout: Duration) -> Result<Option<S::Item>, String>
+where
+    S: Stream + Unpin,
+{
+    tokio::time::timeout(timeout, stream.next())
+        .await
+        .map_err(|_| {
+            format!(
+                "Gemini stream stalled: no data received for {}s",
+                timeout.as_secs()
+            )
+        })
+}
+
+/// Validate a Gemini API key by making a test request
+#[tauri::command]
+pub async fn validate_gemini_api_key(api_key: String) -> Result<ValidateApiKeyResult, String> {
     let url = format!(
         "https://generativelanguage.googleapis.com/v1beta/models?key={}",
         api_key
     );
 
-    let response = client
-        .get(&url)
+    let response = crate::http::get(&url)
         .send()
         .await
         .map_err(|e| format!("Network error: {}", e))?;
@@ -576,3 +1032,1064 @@ pub async fn validate_gemini_api_key(api_key: String) -> Result<ValidateApiKeyRe
     }
 }
 
+/// Convert a saved markdown spec (`.md` or `.md.gz`) into a development plan, bypassing chat
+/// Streams progress like a chat (session id `plan_*`), saves `<name>.plan.json`
+/// next to the spec and returns the saved plan filename (relative to the specs dir)
+#[tauri::command]
+pub async fn generate_plan_from_spec(
+    app: AppHandle,
+    spec_filename: String,
+    working_directory: Option<String>,
+) -> Result<String, String> {
+    let stem = crate::workspace::spec_stem(&spec_filename);
+    if stem == spec_filename {
+        return Err(format!("Not a markdown spec: {}", spec_filename));
+    }
+    let plan_filename = format!("{}.plan.json", stem);
+    let spec = crate::workspace::read_spec(spec_filename, working_directory.clone())
+        .map_err(|e| e.to_string())?;
+
+    let settings = get_settings(&app).await?;
+    let request = build_chat_request(
+        &build_plan_prompt(&spec.content),
+        None,
+        Some(&spec.content),
+        None,
+        settings.safety_settings.clone(),
+    );
+
+    let session_id = crate::shell::unique_id("plan");
+    let model = settings.model.clone();
+    let app_clone = app.clone();
+    let task_session_id = session_id.clone();
+    let handle = tokio::spawn(async move {
+        let result = stream_gemini_response(&app_clone, &task_session_id, settings, request).await;
+        if let Err(e) = &result {
+            emit_stream_event(&app_clone, &task_session_id, "error", e);
+            emit_stream_event(
+                &app_clone,
+                &task_session_id,
+                "complete",
+                "Plan generation ended with error",
+            );
+        }
+        app_clone.state::<ChatRegistry>().remove(&task_session_id);
+        result
+    });
+    app.state::<ChatRegistry>()
+        .register(session_id.clone(), model, handle.abort_handle());
+
+    let output = match handle.await {
+        Ok(result) => result?,
+        Err(e) if e.is_cancelled() => return Err("Plan generation cancelled".to_string()),
+        Err(e) => return Err(format!("Plan generation failed: {}", e)),
+    };
+
+    let plan = parse_plan_json(&output)?;
+    let content = serde_json::to_string_pretty(&plan)
+        .map_err(|e| format!("Failed to serialize plan: {}", e))?;
+    let saved =
+        crate::workspace::save_spec(plan_filename.clone(), content, working_directory, None)
+            .map_err(|e| e.to_string())?;
+
+    Ok(saved.filename.unwrap_or(plan_filename))
+}
+
+/// List Gemini chats that are still streaming
+#[tauri::command]
+pub fn list_chats(app: AppHandle) -> Vec<ChatInfo> {
+    app.state::<ChatRegistry>().list()
+}
+
+/// End-to-end health check: run a one-token generation with the given key and model
+/// Catches keys/regions that can list models but not generate content
+#[tauri::command]
+pub async fn test_gemini_generation(
+    api_key: String,
+    model: Option<String>,
+) -> Result<GenerationTestResult, String> {
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+        model, api_key
+    );
+
+    let started = Instant::now();
+    let response = crate::http::post(&url)
+        .header("Content-Type", "application/json")
+        .json(&build_ping_request())
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            return Ok(GenerationTestResult {
+                success: false,
+                model,
+                latency_ms,
+                status: None,
+                error: Some(format!("Network error: {}", e)),
+            });
+        }
+    };
+
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let api_error = serde_json::from_str::<GeminiStreamResponse>(&body)
+        .ok()
+        .and_then(|r| r.error);
+
+    let error = if !status.is_success() {
+        Some(body)
+    } else {
+        api_error.map(|e| e.message)
+    };
+
+    Ok(GenerationTestResult {
+        success: error.is_none(),
+        model,
+        latency_ms,
+        status: Some(status.as_u16()),
+        error,
+    })
+}
+
+/// Estimate the input tokens and cost of sending the workspace context to a model
+/// Counts with Gemini's countTokens when an API key is set, otherwise (or on failure)
+/// falls back to a local characters-per-token heuristic
+#[tauri::command]
+pub async fn estimate_context_cost(
+    app: AppHandle,
+    working_directory: String,
+    model: Option<String>,
+) -> Result<ContextCostEstimate, String> {
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| format!("Failed to open settings store: {}", e))?;
+    let api_key = store
+        .get(keys::GEMINI_API_KEY)
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .filter(|s| !s.is_empty());
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .or_else(|| {
+            store
+                .get(keys::GEMINI_MODEL)
+                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                .filter(|s| !s.is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    let price = model_price(&model, store.get(keys::MODEL_PRICES));
+    let requests_per_minute =
+        parse_requests_per_minute(store.get(keys::GEMINI_REQUESTS_PER_MINUTE));
+
+    let reader = app.clone();
+    let context = tokio::task::spawn_blocking(move || {
+        crate::workspace::read_workspace_context(reader, working_directory, None, None)
+    })
+    .await
+    .map_err(|e| format!("Context read failed: {}", e))??;
+    let prompt = format_context_prompt(&context.files);
+
+    let counted = match &api_key {
+        Some(api_key) => count_tokens(&app, requests_per_minute, api_key, &model, &prompt)
+            .await
+            .map_err(|e| log::warn!("countTokens failed, using heuristic: {}", e))
+            .ok(),
+        None => None,
+    };
+    let (tokens, method) = match counted {
+        Some(tokens) => (tokens, "api"),
+        None => (estimate_tokens(&prompt), "heuristic"),
+    };
+
+    Ok(ContextCostEstimate {
+        model,
+        tokens,
+        method: method.to_string(),
+        price_per_million_tokens: price,
+        estimated_cost_usd: price.map(|price| tokens as f64 * price / 1_000_000.0),
+        total_files: context.total_files,
+        total_size: context.total_size,
+        truncated: context.truncated,
+    })
+}
+
+/// Summarize the workspace architecture with the model and cache it in `.specstudio/summary.md`
+/// The cached summary is returned as-is unless `force` is set, a context file changed since,
+/// or context files were added or removed
+#[tauri::command]
+pub async fn generate_project_summary(
+    app: AppHandle,
+    working_directory: String,
+    force: Option<bool>,
+) -> Result<ProjectSummary, String> {
+    let cwd = PathBuf::from(&working_directory);
+    let reader = app.clone();
+    let context = tokio::task::spawn_blocking(move || {
+        crate::workspace::read_workspace_context(reader, working_directory, None, None)
+    })
+    .await
+    .map_err(|e| format!("Context read failed: {}", e))??;
+
+    if !force.unwrap_or(false) {
+        if let Some((content, written)) = crate::workspace::read_project_summary(&cwd) {
+            if !crate::workspace::is_summary_stale(&cwd, written, &context.files) {
+                return Ok(ProjectSummary {
+                    content,
+                    generated_at: written
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                    cached: true,
+                });
+            }
+        }
+    }
+
+    let settings = get_settings(&app).await?;
+    let request = build_summary_request(
+        &format_context_prompt(&context.files),
+        settings.safety_settings.clone(),
+    );
+    app.state::<RateLimiter>()
+        .acquire(settings.requests_per_minute)
+        .await;
+    let content = generate_content(
+        &settings.api_key,
+        &settings.model,
+        &request,
+        Duration::from_secs(SUMMARY_TIMEOUT_SECS),
+    )
+    .await?;
+    crate::workspace::write_project_summary(&cwd, &content, &context.files)?;
+
+    Ok(ProjectSummary {
+        content,
+        generated_at: get_timestamp(),
+        cached: false,
+    })
+}
+
+/// Build the Architect chat request shared by chat and plan generation
+/// Prompts containing the plan trigger phrase switch to strict JSON plan output
+fn build_chat_request(
+    prompt: &str,
+    history: Option<Vec<ChatMessage>>,
+    spec_content: Option<&str>,
+    project_summary: Option<&str>,
+    safety_settings: Vec<SafetySetting>,
+) -> GeminiRequest {
+    // Build the system context with Architect persona
+    let mut system_context = String::new();
+
+    system_context.push_str(r#"# Role: Software Architect
+
+You are an expert Software Architect specializing in creating comprehensive development plans.
+
+## Your Mission
+1. Discuss requirements and architecture in MARKDOWN
+2. Only generate a JSON Development Plan when the user sends the specific trigger phrase
+
+## Output Format
+Default to Markdown. Only output strict JSON when the prompt specifically asks to 'Create a comprehensive development plan'.
+
+When generating JSON, use this structure:
+- title: Overall plan title
+- overview: High-level description
+- phases: Array of development phases
+  - Each phase has: title, description, tickets
+  - Each ticket has: id, title, requirements[], acceptance_criteria[]
+
+## Guidelines
+- Break complex features into logical phases
+- Each ticket should be independently implementable
+- Use clear, actionable language in requirements
+- Define measurable acceptance criteria
+- Consider dependencies between tickets
+
+## Tools Available
+- search_files: Search the codebase to understand existing patterns and structure
+"#);
+
+    if let Some(summary) = project_summary {
+        system_context.push_str("\n## Project Summary\n");
+        system_context.push_str(summary);
+        system_context.push_str("\n\n");
+    }
+
+    if let Some(spec) = spec_content {
+        system_context.push_str("\n## Current Specification\n");
+        system_context.push_str(spec);
+        system_context.push_str("\n\n");
+    }
+
+    // Build Gemini contents from history
+    let mut contents: Vec<GeminiContent> = Vec::new();
+
+    // Add system context as first user message
+    contents.push(GeminiContent {
+        role: "user".to_string(),
+        parts: vec![GeminiPart::Text {
+            text: system_context,
+        }],
+    });
+    contents.push(GeminiContent {
+        role: "model".to_string(),
+        parts: vec![GeminiPart::Text {
+            text: "I understand. I'm ready to help you architect your solution. I can explore your codebase using search_files and create structured development plans when you're ready. What would you like to discuss?".to_string(),
+        }],
+    });
+
+    // Add conversation history
+    if let Some(hist) = history {
+        contents.extend(history_to_contents(hist));
+    }
+
+    // Add current prompt
+    contents.push(GeminiContent {
+        role: "user".to_string(),
+        parts: vec![GeminiPart::Text {
+            text: prompt.to_string(),
+        }],
+    });
+
+    // Detect if user is requesting a plan (enable strict JSON output)
+    // CRITICAL: This must match the exact phrase from ide-layout.tsx handleCreatePlan
+    let requesting_plan = prompt.contains("Create a comprehensive development plan");
+
+    // Configure generation with optional strict JSON schema
+    let generation_config = if requesting_plan {
+        GenerationConfig {
+            temperature: 0.7,
+            max_output_tokens: 8192,
+            response_mime_type: Some("application/json".to_string()),
+            response_schema: Some(get_development_plan_schema()),
+        }
+    } else {
+        GenerationConfig {
+            temperature: 0.7,
+            max_output_tokens: 8192,
+            response_mime_type: None,
+            response_schema: None,
+        }
+    };
+
+    // Tools cannot be used with JSON response mode (Gemini API limitation)
+    let tools = if requesting_plan {
+        None
+    } else {
+        Some(vec![get_search_files_tool()])
+    };
+
+    GeminiRequest {
+        contents,
+        generation_config: Some(generation_config),
+        tools,
+        safety_settings,
+    }
+}
+
+/// Plan request for a spec; contains the trigger phrase that enables JSON plan mode
+fn build_plan_prompt(spec_content: &str) -> String {
+    format!(
+        r#"Create a comprehensive development plan for the feature described in the specification below.
+
+Analyze the specification and break down the work into logical phases with specific, actionable tickets.
+
+# Specification
+
+{}
+
+Create the development plan now."#,
+        spec_content
+    )
+}
+
+/// Extract and validate the plan object from a model response
+fn parse_plan_json(output: &str) -> Result<serde_json::Value, String> {
+    let start = output.find('{');
+    let end = output.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &output[start..=end],
+        _ => return Err("No JSON plan found in Gemini response".to_string()),
+    };
+
+    let plan: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid plan JSON: {}", e))?;
+    if !plan["phases"].is_array() {
+        return Err("Plan JSON is missing a phases array".to_string());
+    }
+    Ok(plan)
+}
+
+/// Translate chat history into Gemini contents
+/// Tool calls become model `functionCall` parts and tool results user `functionResponse`
+/// parts; consecutive tool entries share one content, as Gemini sends parallel calls
+fn history_to_contents(history: Vec<ChatMessage>) -> Vec<GeminiContent> {
+    let mut contents: Vec<GeminiContent> = Vec::new();
+
+    for msg in history {
+        let (role, part) = match msg.role.as_str() {
+            "tool_call" => match tool_call_part(&msg) {
+                Some(part) => ("model", part),
+                None => ("model", GeminiPart::Text { text: msg.content }),
+            },
+            "tool_result" => ("user", tool_result_part(msg)),
+            "assistant" => ("model", GeminiPart::Text { text: msg.content }),
+            _ => ("user", GeminiPart::Text { text: msg.content }),
+        };
+
+        let is_tool_part = !matches!(part, GeminiPart::Text { .. });
+        if let Some(last) = contents.last_mut() {
+            let same_kind = last
+                .parts
+                .last()
+                .is_some_and(|p| std::mem::discriminant(p) == std::mem::discriminant(&part));
+            if is_tool_part && last.role == role && same_kind {
+                last.parts.push(part);
+                continue;
+            }
+        }
+        contents.push(GeminiContent {
+            role: role.to_string(),
+            parts: vec![part],
+        });
+    }
+
+    contents
+}
+
+/// Name and args from the message fields, falling back to the JSON in `content`
+fn tool_call_part(msg: &ChatMessage) -> Option<GeminiPart> {
+    let payload: serde_json::Value = serde_json::from_str(&msg.content).unwrap_or_default();
+    let name = msg
+        .tool_name
+        .clone()
+        .or_else(|| payload["name"].as_str().map(|s| s.to_string()))?;
+    let args = msg
+        .tool_args
+        .clone()
+        .or_else(|| payload.get("args").cloned())
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    Some(GeminiPart::FunctionCall {
+        function_call: FunctionCallPart { name, args },
+    })
+}
+
+/// Gemini expects an object response; other JSON values and plain text are wrapped
+fn tool_result_part(msg: ChatMessage) -> GeminiPart {
+    let response = match serde_json::from_str::<serde_json::Value>(&msg.content) {
+        Ok(value @ serde_json::Value::Object(_)) => value,
+        Ok(value) => serde_json::json!({ "content": value }),
+        Err(_) => serde_json::json!({ "content": msg.content }),
+    };
+
+    GeminiPart::FunctionResponse {
+        function_response: FunctionResponsePart {
+            name: msg.tool_name.unwrap_or_default(),
+            response,
+        },
+    }
+}
+
+/// Workspace files in the same layout the chat sends them as context
+fn format_context_prompt(files: &[crate::workspace::FileContent]) -> String {
+    let mut prompt = String::from("## Workspace Files\n\n");
+    for file in files {
+        prompt.push_str(&format!(
+            "### {}\n```\n{}\n```\n\n",
+            file.path, file.content
+        ));
+    }
+    prompt
+}
+
+/// Local token estimate: characters (not bytes) divided by CHARS_PER_TOKEN, rounded up
+fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN) as u64
+}
+
+/// Price per million input tokens: the `modelPrices` setting first, then the built-in table
+fn model_price(model: &str, overrides: Option<serde_json::Value>) -> Option<f64> {
+    overrides
+        .as_ref()
+        .and_then(|prices| prices.get(model))
+        .and_then(|price| price.as_f64())
+        .filter(|price| price.is_finite() && *price >= 0.0)
+        .or_else(|| {
+            DEFAULT_MODEL_PRICES
+                .iter()
+                .find(|(name, _)| *name == model)
+                .map(|(_, price)| *price)
+        })
+}
+
+/// Count tokens with Gemini's countTokens endpoint
+/// Queues on the shared RateLimiter like every other Gemini request
+async fn count_tokens(
+    app: &AppHandle,
+    requests_per_minute: u32,
+    api_key: &str,
+    model: &str,
+    text: &str,
+) -> Result<u64, String> {
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:countTokens?key={}",
+        model, api_key
+    );
+    let request = CountTokensRequest {
+        contents: vec![GeminiContent {
+            role: "user".to_string(),
+            parts: vec![GeminiPart::Text {
+                text: text.to_string(),
+            }],
+        }],
+    };
+
+    app.state::<RateLimiter>()
+        .acquire(requests_per_minute)
+        .await;
+    let response = crate::http::post(&url)
+        .header("Content-Type", "application/json")
+        .json(&request)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status.as_u16(), body));
+    }
+
+    serde_json::from_str::<CountTokensResponse>(&body)
+        .ok()
+        .and_then(|r| r.total_tokens)
+        .ok_or_else(|| format!("Unexpected countTokens response: {}", body))
+}
+
+/// `geminiRequestsPerMinute` setting, falling back to the default when unset or zero
+fn parse_requests_per_minute(value: Option<serde_json::Value>) -> u32 {
+    value
+        .and_then(|v| v.as_u64())
+        .filter(|rpm| *rpm > 0)
+        .map(|rpm| rpm.min(u32::MAX as u64) as u32)
+        .unwrap_or(DEFAULT_REQUESTS_PER_MINUTE)
+}
+
+/// Safety thresholds from the `geminiSafetySettings` object (category → threshold)
+/// Unknown categories or thresholds are skipped so a typo can't break every request
+fn parse_safety_settings(value: Option<serde_json::Value>) -> Vec<SafetySetting> {
+    let Some(serde_json::Value::Object(map)) = value else {
+        return Vec::new();
+    };
+
+    let mut settings: Vec<SafetySetting> = map
+        .iter()
+        .filter_map(|(category, threshold)| {
+            let threshold = threshold.as_str()?;
+            if !SAFETY_CATEGORIES.contains(&category.as_str())
+                || !SAFETY_THRESHOLDS.contains(&threshold)
+            {
+                log::warn!("Ignoring invalid safety setting {}={}", category, threshold);
+                return None;
+            }
+            Some(SafetySetting {
+                category: category.clone(),
+                threshold: threshold.to_string(),
+            })
+        })
+        .collect();
+    settings.sort_by(|a, b| a.category.cmp(&b.category));
+    settings
+}
+
+/// ": HARM_CATEGORY_X (HIGH), ..." for the ratings that caused a block
+fn blocked_categories(ratings: &[SafetyRating]) -> String {
+    let blocked: Vec<String> = ratings
+        .iter()
+        .filter(|r| r.blocked)
+        .map(|r| match &r.probability {
+            Some(probability) => format!("{} ({})", r.category, probability),
+            None => r.category.clone(),
+        })
+        .collect();
+
+    if blocked.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", blocked.join(", "))
+    }
+}
+
+/// Single-turn request asking for an architecture summary of the given workspace files
+fn build_summary_request(context: &str, safety_settings: Vec<SafetySetting>) -> GeminiRequest {
+    GeminiRequest {
+        contents: vec![GeminiContent {
+            role: "user".to_string(),
+            parts: vec![GeminiPart::Text {
+                text: format!("{}\n\n{}", PROJECT_SUMMARY_PROMPT, context),
+            }],
+        }],
+        generation_config: Some(GenerationConfig {
+            temperature: 0.2,
+            max_output_tokens: 2048,
+            response_mime_type: None,
+            response_schema: None,
+        }),
+        tools: None,
+        safety_settings,
+    }
+}
+
+/// Run a non-streaming generation and return the text of the first candidate
+/// Sent without the shared request timeout; `timeout` bounds the whole request instead
+async fn generate_content(
+    api_key: &str,
+    model: &str,
+    request: &GeminiRequest,
+    timeout: Duration,
+) -> Result<String, String> {
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+        model, api_key
+    );
+    let response = crate::http::streaming_post(&url)
+        .timeout(timeout)
+        .header("Content-Type", "application/json")
+        .json(request)
+        .send()
+        .await
+        .map_err(|e| format!("Network error: {}", e))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    let parsed = serde_json::from_str::<GeminiStreamResponse>(&body);
+    if let Some(error) = parsed.as_ref().ok().and_then(|r| r.error.as_ref()) {
+        return Err(format!("Gemini API error: {}", error.message));
+    }
+    if !status.is_success() {
+        return Err(format!("Gemini API error ({}): {}", status.as_u16(), body));
+    }
+
+    let text: String = parsed
+        .map_err(|e| format!("Failed to parse Gemini response: {}", e))?
+        .candidates
+        .unwrap_or_default()
+        .into_iter()
+        .next()
+        .and_then(|candidate| candidate.content)
+        .and_then(|content| content.parts)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|part| part.text)
+        .collect();
+    if text.trim().is_empty() {
+        return Err("Gemini returned an empty response".to_string());
+    }
+    Ok(text)
+}
+
+/// Minimal generation request used for connectivity checks
+fn build_ping_request() -> GeminiRequest {
+    GeminiRequest {
+        contents: vec![GeminiContent {
+            role: "user".to_string(),
+            parts: vec![GeminiPart::Text {
+                text: "ping".to_string(),
+            }],
+        }],
+        generation_config: Some(GenerationConfig {
+            temperature: 0.0,
+            max_output_tokens: 1,
+            response_mime_type: None,
+            response_schema: None,
+        }),
+        tools: None,
+        safety_settings: Vec::new(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_models_order_and_dedup() {
+        let fallbacks = vec![
+            "gemini-2.5-flash-lite".to_string(),
+            " ".to_string(),
+            "gemini-2.5-flash".to_string(),
+            "gemini-2.0-flash".to_string(),
+        ];
+        assert_eq!(
+            candidate_models("gemini-2.5-flash", &fallbacks),
+            vec![
+                "gemini-2.5-flash",
+                "gemini-2.5-flash-lite",
+                "gemini-2.0-flash"
+            ]
+        );
+        assert_eq!(
+            candidate_models("gemini-2.5-pro", &[]),
+            vec!["gemini-2.5-pro"]
+        );
+    }
+
+    #[test]
+    fn test_is_model_unavailable() {
+        assert!(is_model_unavailable(503, ""));
+        assert!(is_model_unavailable(429, ""));
+        assert!(is_model_unavailable(
+            0,
+            "The model is overloaded. Please try again later."
+        ));
+        assert!(is_model_unavailable(
+            500,
+            r#"{"error": {"status": "UNAVAILABLE"}}"#
+        ));
+        assert!(!is_model_unavailable(400, "API key not valid"));
+        assert!(!is_model_unavailable(403, "PERMISSION_DENIED"));
+    }
+
+    #[test]
+    fn test_next_with_timeout_detects_stall() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let timeout = Duration::from_millis(20);
+
+            // Items arrive, then the stream goes silent without ending
+            let mut stalled =
+                futures_util::stream::iter(vec![1, 2]).chain(futures_util::stream::pending());
+            assert_eq!(next_with_timeout(&mut stalled, timeout).await, Ok(Some(1)));
+            assert_eq!(next_with_timeout(&mut stalled, timeout).await, Ok(Some(2)));
+            let err = next_with_timeout(&mut stalled, timeout).await.unwrap_err();
+            assert!(err.contains("stalled"), "{}", err);
+
+            // A stream that ends normally is not a stall
+            let mut finished = futures_util::stream::iter(vec![1]);
+            assert_eq!(next_with_timeout(&mut finished, timeout).await, Ok(Some(1)));
+            assert_eq!(next_with_timeout(&mut finished, timeout).await, Ok(None));
+        });
+    }
+
+    #[test]
+    fn test_chat_registry_abort_all_is_idempotent() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let registry = ChatRegistry::new();
+            assert!(registry.abort_all().is_empty());
+
+            let running = tokio::spawn(futures_util::future::pending::<()>());
+            registry.register(
+                "chat_1".to_string(),
+                "m".to_string(),
+                running.abort_handle(),
+            );
+
+            let finished = tokio::spawn(async {});
+            let finished_handle = finished.abort_handle();
+            finished.await.unwrap();
+            registry.register("chat_2".to_string(), "m".to_string(), finished_handle);
+
+            assert_eq!(registry.abort_all(), vec!["chat_1".to_string()]);
+            assert!(running.await.unwrap_err().is_cancelled());
+            assert!(registry.abort_all().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_ping_request_is_a_single_token_generation() {
+        let value = serde_json::to_value(build_ping_request()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "contents": [{ "role": "user", "parts": [{ "text": "ping" }] }],
+                "generationConfig": { "temperature": 0.0, "maxOutputTokens": 1 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(60, start);
+
+        // A full bucket allows a burst of one minute's worth of requests
+        for _ in 0..60 {
+            assert!(bucket.try_acquire(start).is_ok());
+        }
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        // Refills at requests_per_minute / 60 tokens per second
+        assert!(bucket
+            .try_acquire(start + Duration::from_millis(500))
+            .is_err());
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_err());
+
+        // Never refills beyond capacity
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..60 {
+            assert!(bucket.try_acquire(later).is_ok());
+        }
+        assert!(bucket.try_acquire(later).is_err());
+
+        // Lowering the cap clamps surplus tokens
+        let mut bucket = TokenBucket::new(60, start);
+        bucket.set_rate(2);
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        assert_eq!(
+            bucket.try_acquire(start).unwrap_err(),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_chat_registry_tracks_model_and_usage() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let registry = ChatRegistry::new();
+            let chat = tokio::spawn(futures_util::future::pending::<()>());
+            registry.register(
+                "chat_1".to_string(),
+                "gemini-2.5-flash".to_string(),
+                chat.abort_handle(),
+            );
+
+            registry.set_model("chat_1", "gemini-2.5-flash-lite");
+            let usage: UsageMetadata = serde_json::from_value(serde_json::json!({
+                "promptTokenCount": 120,
+                "candidatesTokenCount": 45,
+                "totalTokenCount": 165
+            }))
+            .unwrap();
+            registry.record_usage("chat_1", &usage);
+            // Unknown sessions are ignored
+            registry.record_usage("missing", &usage);
+
+            let chats = registry.list();
+            assert_eq!(chats.len(), 1);
+            assert_eq!(chats[0].session_id, "chat_1");
+            assert_eq!(chats[0].model, "gemini-2.5-flash-lite");
+            assert_eq!((chats[0].prompt_tokens, chats[0].output_tokens), (120, 45));
+
+            registry.remove("chat_1");
+            assert!(registry.list().is_empty());
+            chat.abort();
+        });
+    }
+
+    #[test]
+    fn test_history_translates_tool_turns() {
+        let message = |role: &str, content: &str| ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            tool_name: None,
+            tool_args: None,
+        };
+        let history = vec![
+            message("user", "Where is auth handled?"),
+            // As emitted in tool_call stream events
+            message(
+                "tool_call",
+                r#"{"name":"search_files","args":{"query":"oauth"}}"#,
+            ),
+            ChatMessage {
+                tool_name: Some("search_files".to_string()),
+                tool_args: Some(serde_json::json!({ "query": "token" })),
+                ..message("tool_call", "")
+            },
+            ChatMessage {
+                tool_name: Some("search_files".to_string()),
+                ..message("tool_result", r#"{"results":["src/auth.rs"]}"#)
+            },
+            ChatMessage {
+                tool_name: Some("search_files".to_string()),
+                ..message("tool_result", "no matches")
+            },
+            message("assistant", "Auth lives in src/auth.rs"),
+        ];
+
+        let contents = serde_json::to_value(history_to_contents(history)).unwrap();
+        assert_eq!(
+            contents,
+            serde_json::json!([
+                { "role": "user", "parts": [{ "text": "Where is auth handled?" }] },
+                { "role": "model", "parts": [
+                    { "functionCall": { "name": "search_files", "args": { "query": "oauth" } } },
+                    { "functionCall": { "name": "search_files", "args": { "query": "token" } } }
+                ] },
+                { "role": "user", "parts": [
+                    { "functionResponse": {
+                        "name": "search_files",
+                        "response": { "results": ["src/auth.rs"] }
+                    } },
+                    { "functionResponse": {
+                        "name": "search_files",
+                        "response": { "content": "no matches" }
+                    } }
+                ] },
+                { "role": "model", "parts": [{ "text": "Auth lives in src/auth.rs" }] }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_history_keeps_text_turns_separate() {
+        let history: Vec<ChatMessage> = serde_json::from_value(serde_json::json!([
+            { "role": "user", "content": "one" },
+            { "role": "user", "content": "two" },
+            // A tool call without a name can't be replayed as a functionCall
+            { "role": "tool_call", "content": "not json" }
+        ]))
+        .unwrap();
+
+        let contents = history_to_contents(history);
+        assert_eq!(contents.len(), 3);
+        assert_eq!(contents[2].role, "model");
+        assert!(matches!(&contents[2].parts[0], GeminiPart::Text { text } if text == "not json"));
+    }
+
+    #[test]
+    fn test_safety_settings_serialize() {
+        let safety_settings = parse_safety_settings(Some(serde_json::json!({
+            "HARM_CATEGORY_DANGEROUS_CONTENT": "BLOCK_NONE",
+            "HARM_CATEGORY_HARASSMENT": "BLOCK_ONLY_HIGH",
+            "HARM_CATEGORY_UNKNOWN": "BLOCK_NONE",
+            "HARM_CATEGORY_HATE_SPEECH": "BLOCK_SOMETIMES"
+        })));
+
+        let mut request = build_ping_request();
+        assert!(serde_json::to_value(&request)
+            .unwrap()
+            .get("safetySettings")
+            .is_none());
+
+        request.safety_settings = safety_settings;
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["safetySettings"],
+            serde_json::json!([
+                { "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "threshold": "BLOCK_NONE" },
+                { "category": "HARM_CATEGORY_HARASSMENT", "threshold": "BLOCK_ONLY_HIGH" }
+            ])
+        );
+        assert!(parse_safety_settings(Some(serde_json::json!("BLOCK_NONE"))).is_empty());
+    }
+
+    #[test]
+    fn test_safety_block_reasons_are_parsed() {
+        let response: GeminiStreamResponse = serde_json::from_value(serde_json::json!({
+            "candidates": [{
+                "finishReason": "SAFETY",
+                "safetyRatings": [
+                    { "category": "HARM_CATEGORY_DANGEROUS_CONTENT", "probability": "HIGH", "blocked": true },
+                    { "category": "HARM_CATEGORY_HARASSMENT", "probability": "NEGLIGIBLE" }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let candidate = &response.candidates.unwrap()[0];
+        assert_eq!(candidate.finish_reason.as_deref(), Some("SAFETY"));
+        assert_eq!(
+            blocked_categories(&candidate.safety_ratings),
+            ": HARM_CATEGORY_DANGEROUS_CONTENT (HIGH)"
+        );
+        assert_eq!(blocked_categories(&[]), "");
+    }
+
+    #[test]
+    fn test_plan_prompt_uses_plan_request_path() {
+        let request = build_chat_request(
+            &build_plan_prompt("# Refunds"),
+            None,
+            Some("# Refunds"),
+            None,
+            Vec::new(),
+        );
+        let value = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(
+            value["generationConfig"]["responseMimeType"],
+            "application/json"
+        );
+        assert_eq!(
+            value["generationConfig"]["responseSchema"],
+            get_development_plan_schema()
+        );
+        assert!(value.get("tools").is_none());
+    }
+
+    #[test]
+    fn test_parse_plan_json() {
+        let plan = parse_plan_json(
+            "```json\n{\"title\": \"Refunds\", \"overview\": \"\", \"phases\": []}\n```",
+        )
+        .unwrap();
+        assert_eq!(plan["title"], "Refunds");
+
+        assert!(parse_plan_json("no json here").is_err());
+        assert!(parse_plan_json("{\"title\": \"x\"}").is_err());
+        assert!(parse_plan_json("{not json}").is_err());
+    }
+
+    #[test]
+    fn test_estimate_tokens_heuristic() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Counts characters, not UTF-8 bytes
+        assert_eq!(estimate_tokens("éééé"), 1);
+
+        let files = vec![crate::workspace::FileContent {
+            path: "src/main.rs".to_string(),
+            content: "fn main() {}".to_string(),
+        }];
+        let prompt = format_context_prompt(&files);
+        assert!(prompt.contains("### src/main.rs\n```\nfn main() {}\n```"));
+        assert_eq!(estimate_tokens(&prompt), prompt.len().div_ceil(4) as u64);
+    }
+
+    #[test]
+    fn test_model_price_prefers_configured_table() {
+        assert_eq!(model_price("gemini-2.5-pro", None), Some(1.25));
+        assert_eq!(model_price("unknown-model", None), None);
+
+        let overrides = serde_json::json!({ "gemini-2.5-pro": 2.5, "custom": 0.5, "bad": -1 });
+        assert_eq!(
+            model_price("gemini-2.5-pro", Some(overrides.clone())),
+            Some(2.5)
+        );
+        assert_eq!(model_price("custom", Some(overrides.clone())), Some(0.5));
+        assert_eq!(
+            model_price("gemini-2.5-flash", Some(overrides.clone())),
+            Some(0.30)
+        );
+        assert_eq!(model_price("bad", Some(overrides)), None);
+    }
+}
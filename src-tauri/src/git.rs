@@ -3,9 +3,36 @@
 // Provides git status, revert, and file history operations
 // ============================================================================
 
+use crate::error::AppError;
+use crate::shell::emit_tagged_stream_event;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use tauri::AppHandle;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+// Approximate size of each streamed diff chunk
+const DIFF_CHUNK_SIZE: usize = 16 * 1024;
+
+// Entries git_init ensures are in .gitignore (chat transcripts and log files)
+const GITIGNORE_ENTRIES: &[&str] = &[".specstudio/chats/", "*.log"];
+
+const INITIAL_COMMIT_MESSAGE: &str = "Initial commit";
+
+// Blame lines returned for one file; longer files are truncated
+const MAX_BLAME_LINES: usize = 5000;
+
+// `git show` header format: fields split by unit separators, header ended by a record separator
+const COMMIT_FORMAT: &str = "--format=%H%x1f%an%x1f%ae%x1f%at%x1f%B%x1e";
+
+// Hash git blame reports for lines that aren't committed yet
+const UNCOMMITTED_HASH: &str = "0000000000000000000000000000000000000000";
 
 // ============================================================================
 // Types
@@ -28,6 +55,100 @@ pub struct GitRevertResult {
     pub reverted_files: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStageResult {
+    pub success: bool,
+    pub status: GitStatusResult,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GitRestoreAction {
+    /// Tracked file checked out from the index
+    Reverted,
+    /// Untracked file removed from disk
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRestoreResult {
+    pub success: bool,
+    pub file_path: String,
+    pub action: GitRestoreAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRemote {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitRemoteStatus {
+    pub remotes: Vec<GitRemote>,
+    /// Upstream of the current branch (e.g. `origin/main`), if configured
+    pub upstream: Option<String>,
+    /// Commits on HEAD not on the upstream
+    pub ahead: usize,
+    /// Commits on the upstream not on HEAD
+    pub behind: usize,
+    /// Unix timestamp (milliseconds) of the last fetch, from `.git/FETCH_HEAD`
+    pub last_fetched_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitInitResult {
+    pub status: GitStatusResult,
+    /// Whether .gitignore was created or had entries added
+    pub gitignore_updated: bool,
+    /// Hash of the initial commit, if one was made
+    pub commit: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitBlameLine {
+    /// 1-indexed line number in the current file
+    pub line_number: usize,
+    /// Full commit hash (all zeros for uncommitted lines)
+    pub commit: String,
+    pub author: String,
+    /// Unix timestamp (milliseconds) of the authored date
+    pub date: i64,
+    pub content: String,
+    pub uncommitted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitBlameResult {
+    pub file_path: String,
+    pub lines: Vec<GitBlameLine>,
+    /// More than MAX_BLAME_LINES lines; only the first ones are returned
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommitDetails {
+    pub hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    /// Unix timestamp (milliseconds) of the authored date
+    pub date: i64,
+    /// First line of the message
+    pub subject: String,
+    /// Full commit message
+    pub message: String,
+    pub diff: String,
+    pub files_changed: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitDiffResult {
@@ -41,11 +162,13 @@ pub struct GitDiffResult {
 
 /// Get git status for a working directory
 #[tauri::command]
-pub fn git_status(working_directory: String) -> Result<GitStatusResult, String> {
+pub fn git_status(working_directory: String) -> Result<GitStatusResult, AppError> {
     let cwd = Path::new(&working_directory);
 
     if !cwd.exists() || !cwd.is_dir() {
-        return Err("Working directory does not exist".to_string());
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
     }
 
     // Check if it's a git repo
@@ -64,11 +187,14 @@ pub fn git_status(working_directory: String) -> Result<GitStatusResult, String>
         .args(["status", "--porcelain"])
         .current_dir(cwd)
         .output()
-        .map_err(|e| format!("Failed to run git status: {}", e))?;
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git status: {}", e)))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("git status failed: {}", stderr));
+        return Err(AppError::CommandFailed(format!(
+            "git status failed: {}",
+            stderr
+        )));
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -100,20 +226,70 @@ pub fn git_status(working_directory: String) -> Result<GitStatusResult, String>
     })
 }
 
+/// Initialize a git repository in a directory that isn't one yet
+/// Optionally adds SpecStudio's chats and log files to .gitignore and commits everything
+#[tauri::command]
+pub fn git_init(
+    working_directory: String,
+    initial_commit: Option<bool>,
+    update_gitignore: Option<bool>,
+) -> Result<GitInitResult, AppError> {
+    let cwd = Path::new(&working_directory);
+
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+    // Also refuses subdirectories of a repository, where `git init` would nest a new one
+    if is_inside_work_tree(cwd)? || cwd.join(".git").exists() {
+        return Err(AppError::InvalidInput(
+            "Directory is already inside a git repository".to_string(),
+        ));
+    }
+
+    run_git(cwd, &["init"])?;
+
+    let gitignore_updated = if update_gitignore.unwrap_or(true) {
+        ensure_gitignore_entries(cwd)?
+    } else {
+        false
+    };
+
+    let commit = if initial_commit.unwrap_or(false) {
+        run_git(cwd, &["add", "-A"])?;
+        run_git(
+            cwd,
+            &["commit", "--allow-empty", "-m", INITIAL_COMMIT_MESSAGE],
+        )?;
+        Some(run_git(cwd, &["rev-parse", "HEAD"])?.trim().to_string())
+    } else {
+        None
+    };
+
+    Ok(GitInitResult {
+        status: git_status(working_directory)?,
+        gitignore_updated,
+        commit,
+    })
+}
+
 /// Revert all changes in the working directory
 /// This runs: git clean -fd && git checkout .
 #[tauri::command]
-pub fn git_revert_all(working_directory: String) -> Result<GitRevertResult, String> {
+pub fn git_revert_all(working_directory: String) -> Result<GitRevertResult, AppError> {
     let cwd = Path::new(&working_directory);
 
     if !cwd.exists() || !cwd.is_dir() {
-        return Err("Working directory does not exist".to_string());
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
     }
 
     // First, get the count of files that will be reverted
     let status = git_status(working_directory.clone())?;
     if !status.is_git_repo {
-        return Err("Not a git repository".to_string());
+        return Err(AppError::NotAGitRepo);
     }
 
     if !status.has_changes {
@@ -131,11 +307,14 @@ pub fn git_revert_all(working_directory: String) -> Result<GitRevertResult, Stri
         .args(["checkout", "."])
         .current_dir(cwd)
         .output()
-        .map_err(|e| format!("Failed to run git checkout: {}", e))?;
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git checkout: {}", e)))?;
 
     if !checkout_output.status.success() {
         let stderr = String::from_utf8_lossy(&checkout_output.stderr);
-        return Err(format!("git checkout failed: {}", stderr));
+        return Err(AppError::CommandFailed(format!(
+            "git checkout failed: {}",
+            stderr
+        )));
     }
 
     // Run git clean -fd to remove untracked files
@@ -143,11 +322,14 @@ pub fn git_revert_all(working_directory: String) -> Result<GitRevertResult, Stri
         .args(["clean", "-fd"])
         .current_dir(cwd)
         .output()
-        .map_err(|e| format!("Failed to run git clean: {}", e))?;
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git clean: {}", e)))?;
 
     if !clean_output.status.success() {
         let stderr = String::from_utf8_lossy(&clean_output.stderr);
-        return Err(format!("git clean failed: {}", stderr));
+        return Err(AppError::CommandFailed(format!(
+            "git clean failed: {}",
+            stderr
+        )));
     }
 
     Ok(GitRevertResult {
@@ -163,35 +345,58 @@ pub fn git_show_file(
     working_directory: String,
     file_path: String,
     git_ref: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let cwd = Path::new(&working_directory);
 
     if !cwd.exists() || !cwd.is_dir() {
-        return Err("Working directory does not exist".to_string());
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
     }
 
-    // Run git show {ref}:{path}
-    let output = Command::new("git")
-        .args(["show", &format!("{}:{}", git_ref, file_path)])
-        .current_dir(cwd)
-        .output()
-        .map_err(|e| format!("Failed to run git show: {}", e))?;
+    Ok(show_file_at_ref(cwd, &git_ref, &file_path)?.unwrap_or_default())
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        // If file doesn't exist in the ref, return empty string
-        if stderr.contains("does not exist") || stderr.contains("path") {
-            return Ok(String::new());
-        }
-        return Err(format!("git show failed: {}", stderr));
+/// Per-line blame for a file (`git blame --porcelain`), including uncommitted edits
+#[tauri::command]
+pub fn git_blame(working_directory: String, file_path: String) -> Result<GitBlameResult, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+    resolve_repo_path(cwd, &file_path)?;
+
+    let output = run_git(cwd, &["blame", "--porcelain", "--", &file_path])?;
+    let (lines, truncated) = parse_blame_porcelain(&output, MAX_BLAME_LINES);
+
+    Ok(GitBlameResult {
+        file_path,
+        lines,
+        truncated,
+    })
+}
+
+/// Metadata and diff of a single commit (`git show`), HEAD by default
+#[tauri::command]
+pub fn git_show_commit(
+    working_directory: String,
+    git_ref: Option<String>,
+) -> Result<GitCommitDetails, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+    let git_ref = git_ref.unwrap_or_else(|| "HEAD".to_string());
+    if git_ref.is_empty() || git_ref.starts_with('-') {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid git ref: {}",
+            git_ref
+        )));
     }
 
-    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    let output = run_git(cwd, &["show", "--no-color", COMMIT_FORMAT, &git_ref, "--"])?;
+    parse_commit_details(&output).ok_or_else(|| {
+        AppError::CommandFailed(format!("Unexpected git show output for {}", git_ref))
+    })
 }
 
 /// Read current file content from disk
 #[tauri::command]
-pub fn read_file(working_directory: String, file_path: String) -> Result<String, String> {
+pub fn read_file(working_directory: String, file_path: String) -> Result<String, AppError> {
     let cwd = Path::new(&working_directory);
     let full_path = cwd.join(&file_path);
 
@@ -199,8 +404,7 @@ pub fn read_file(working_directory: String, file_path: String) -> Result<String,
         return Ok(String::new());
     }
 
-    std::fs::read_to_string(&full_path)
-        .map_err(|e| format!("Failed to read file: {}", e))
+    std::fs::read_to_string(&full_path).map_err(|e| AppError::io("Failed to read file", e))
 }
 
 /// Get git diff for staged changes (or specific files if provided)
@@ -210,66 +414,33 @@ pub fn read_file(working_directory: String, file_path: String) -> Result<String,
 pub fn get_staged_diff(
     working_directory: String,
     files: Option<Vec<String>>,
-) -> Result<GitDiffResult, String> {
+) -> Result<GitDiffResult, AppError> {
     let cwd = Path::new(&working_directory);
 
     if !cwd.exists() || !cwd.is_dir() {
-        return Err("Working directory does not exist".to_string());
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
     }
 
     // Check if it's a git repo
     let git_dir = cwd.join(".git");
     if !git_dir.exists() {
-        return Err("Not a git repository".to_string());
+        return Err(AppError::NotAGitRepo);
     }
 
-    let output = if let Some(file_list) = files {
-        if file_list.is_empty() {
-            // If empty list provided, return all changes (staged + unstaged)
-            Command::new("git")
-                .args(["diff", "HEAD"])
-                .current_dir(cwd)
-                .output()
-                .map_err(|e| format!("Failed to run git diff: {}", e))?
-        } else {
-            // Get diff for specific files (includes both staged and unstaged)
-            let mut args = vec!["diff", "HEAD", "--"];
-            args.extend(file_list.iter().map(|s| s.as_str()));
-            Command::new("git")
-                .args(&args)
-                .current_dir(cwd)
-                .output()
-                .map_err(|e| format!("Failed to run git diff: {}", e))?
-        }
-    } else {
-        // No files specified - get all staged changes
-        // First check if there are any commits in the repo
-        let rev_parse = Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .current_dir(cwd)
-            .output()
-            .map_err(|e| format!("Failed to check git history: {}", e))?;
-
-        if rev_parse.status.success() {
-            // Repo has commits - use diff HEAD
-            Command::new("git")
-                .args(["diff", "HEAD"])
-                .current_dir(cwd)
-                .output()
-                .map_err(|e| format!("Failed to run git diff: {}", e))?
-        } else {
-            // Initial commit - show all files
-            Command::new("git")
-                .args(["diff", "--cached"])
-                .current_dir(cwd)
-                .output()
-                .map_err(|e| format!("Failed to run git diff: {}", e))?
-        }
-    };
+    let output = Command::new("git")
+        .args(build_diff_args(cwd, files)?)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git diff: {}", e)))?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("git diff failed: {}", stderr));
+        return Err(AppError::CommandFailed(format!(
+            "git diff failed: {}",
+            stderr
+        )));
     }
 
     let diff = String::from_utf8_lossy(&output.stdout).to_string();
@@ -285,6 +456,613 @@ pub fn get_staged_diff(
     })
 }
 
+/// Stage files (`git add --`)
+#[tauri::command]
+pub fn git_stage(
+    working_directory: String,
+    files: Vec<String>,
+) -> Result<GitStageResult, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+    if files.is_empty() {
+        return Err(AppError::InvalidInput("No files to stage".to_string()));
+    }
+
+    let mut args = vec!["add", "--"];
+    args.extend(files.iter().map(|s| s.as_str()));
+    run_git(cwd, &args)?;
+
+    Ok(GitStageResult {
+        success: true,
+        status: git_status(working_directory)?,
+    })
+}
+
+/// Unstage files (`git restore --staged --`, or `git rm --cached` before the first commit)
+#[tauri::command]
+pub fn git_unstage(
+    working_directory: String,
+    files: Vec<String>,
+) -> Result<GitStageResult, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+    if files.is_empty() {
+        return Err(AppError::InvalidInput("No files to unstage".to_string()));
+    }
+
+    let mut args = if has_commits(cwd)? {
+        vec!["restore", "--staged", "--"]
+    } else {
+        // No HEAD to restore from yet - drop the files from the index instead
+        vec!["rm", "--cached", "-r", "--quiet", "--"]
+    };
+    args.extend(files.iter().map(|s| s.as_str()));
+    run_git(cwd, &args)?;
+
+    Ok(GitStageResult {
+        success: true,
+        status: git_status(working_directory)?,
+    })
+}
+
+/// Discard changes to a single file
+/// Tracked files are checked out (`git checkout --`); untracked files are deleted.
+/// Only a regular file that `git ls-files --others --exclude-standard` lists is ever
+/// deleted, so ignored files (e.g. `.env`) and directories are refused.
+#[tauri::command]
+pub fn git_restore_file(
+    working_directory: String,
+    file_path: String,
+) -> Result<GitRestoreResult, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+    let full_path = resolve_repo_path(cwd, &file_path)?;
+
+    let tracked = Command::new("git")
+        .args(["ls-files", "--error-unmatch", "--", &file_path])
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git ls-files: {}", e)))?
+        .status
+        .success();
+
+    let action = if tracked {
+        run_git(cwd, &["checkout", "--", &file_path])?;
+        GitRestoreAction::Reverted
+    } else {
+        let metadata = std::fs::symlink_metadata(&full_path)
+            .map_err(|_| AppError::NotFound(format!("File not found: {}", file_path)))?;
+        if !metadata.is_file() {
+            return Err(AppError::InvalidInput(format!(
+                "Only untracked files can be deleted: {}",
+                file_path
+            )));
+        }
+        if !is_untracked_file(cwd, &file_path)? {
+            return Err(AppError::InvalidInput(format!(
+                "Refusing to delete an ignored file: {}",
+                file_path
+            )));
+        }
+        std::fs::remove_file(&full_path)
+            .map_err(|e| AppError::io("Failed to delete untracked file", e))?;
+        GitRestoreAction::Deleted
+    };
+
+    Ok(GitRestoreResult {
+        success: true,
+        file_path,
+        action,
+    })
+}
+
+/// Stream `git diff` output as `rpc:stream:data` chunks for large diffs
+/// Same file selection as get_staged_diff; ends with a `complete` event
+/// whose data is the number of files changed
+/// Every event carries `streamId`; pass one to filter from the first event,
+/// otherwise one is generated. Returns the stream id.
+#[tauri::command]
+pub fn stream_git_diff(
+    app: AppHandle,
+    working_directory: String,
+    files: Option<Vec<String>>,
+    stream_id: Option<String>,
+) -> Result<String, AppError> {
+    let cwd = Path::new(&working_directory);
+
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+
+    if !cwd.join(".git").exists() {
+        return Err(AppError::NotAGitRepo);
+    }
+
+    let mut child = Command::new("git")
+        .args(build_diff_args(cwd, files)?)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git diff: {}", e)))?;
+
+    let stdout = child.stdout.take();
+    let stream_id =
+        stream_id.unwrap_or_else(|| format!("diff_{}", chrono::Utc::now().timestamp_millis()));
+    let id = stream_id.clone();
+
+    thread::spawn(move || {
+        let emit =
+            |event_type: &str, data: &str| emit_tagged_stream_event(&app, &id, event_type, data);
+        let files_changed = match stdout {
+            Some(stdout) => {
+                stream_diff_chunks(BufReader::new(stdout), |chunk| emit("output", chunk))
+            }
+            None => 0,
+        };
+
+        match child.wait_with_output() {
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                emit("error", &format!("git diff failed: {}", stderr));
+            }
+            Err(e) => emit("error", &format!("Failed to wait for git diff: {}", e)),
+            _ => {}
+        }
+
+        emit("complete", &files_changed.to_string());
+    });
+
+    Ok(stream_id)
+}
+
+/// Get remotes, upstream branch and ahead/behind counts for the current branch
+/// Repos without an upstream report `upstream: None` and zero counts
+#[tauri::command]
+pub fn git_remote_status(working_directory: String) -> Result<GitRemoteStatus, AppError> {
+    let cwd = require_git_repo(&working_directory)?;
+
+    let remotes = parse_remotes(&run_git(cwd, &["remote", "-v"])?);
+
+    // Fails when no upstream is configured (or HEAD is unborn/detached)
+    let upstream = Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"])
+        .current_dir(cwd)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    let (ahead, behind) = match upstream {
+        Some(_) => {
+            let counts = run_git(cwd, &["rev-list", "--left-right", "--count", "@{u}...HEAD"])?;
+            parse_ahead_behind(&counts).ok_or_else(|| {
+                AppError::CommandFailed(format!("Unexpected rev-list output: {}", counts.trim()))
+            })?
+        }
+        None => (0, 0),
+    };
+
+    let last_fetched_at = std::fs::metadata(cwd.join(".git/FETCH_HEAD"))
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_millis());
+
+    Ok(GitRemoteStatus {
+        remotes,
+        upstream,
+        ahead,
+        behind,
+        last_fetched_at,
+    })
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Parse `git rev-list --left-right --count @{u}...HEAD` into (ahead, behind)
+/// Left side counts upstream-only commits (behind), right side HEAD-only (ahead)
+fn parse_ahead_behind(output: &str) -> Option<(usize, usize)> {
+    let mut counts = output.split_whitespace();
+    let behind = counts.next()?.parse().ok()?;
+    let ahead = counts.next()?.parse().ok()?;
+    if counts.next().is_some() {
+        return None;
+    }
+    Some((ahead, behind))
+}
+
+/// Parse `git remote -v`, keeping the fetch URL of each remote
+fn parse_remotes(output: &str) -> Vec<GitRemote> {
+    let mut remotes: Vec<GitRemote> = Vec::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(url)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if parts.next() == Some("(push)") || remotes.iter().any(|r| r.name == name) {
+            continue;
+        }
+        remotes.push(GitRemote {
+            name: name.to_string(),
+            url: url.to_string(),
+        });
+    }
+    remotes
+}
+
+/// Forward diff output in line-aligned chunks, returning the number of files seen
+fn stream_diff_chunks<R: BufRead>(mut reader: R, mut emit: impl FnMut(&str)) -> usize {
+    let mut files_changed = 0;
+    let mut chunk = String::new();
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        if text.starts_with("diff --git") {
+            files_changed += 1;
+        }
+        chunk.push_str(&text);
+
+        if chunk.len() >= DIFF_CHUNK_SIZE {
+            emit(&chunk);
+            chunk.clear();
+        }
+    }
+
+    if !chunk.is_empty() {
+        emit(&chunk);
+    }
+
+    files_changed
+}
+
+/// Append any missing GITIGNORE_ENTRIES to .gitignore, creating it if needed
+/// Returns whether the file changed
+fn ensure_gitignore_entries(cwd: &Path) -> Result<bool, AppError> {
+    let path = cwd.join(".gitignore");
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(AppError::io("Failed to read .gitignore", e)),
+    };
+
+    let missing: Vec<&str> = GITIGNORE_ENTRIES
+        .iter()
+        .copied()
+        .filter(|entry| !existing.lines().any(|line| line.trim() == *entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(false);
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for entry in missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    std::fs::write(&path, content).map_err(|e| AppError::io("Failed to write .gitignore", e))?;
+
+    Ok(true)
+}
+
+/// Split `git show` output produced with COMMIT_FORMAT into header fields and diff
+fn parse_commit_details(output: &str) -> Option<GitCommitDetails> {
+    let (header, diff) = output.split_once('\x1e')?;
+    let mut fields = header.splitn(5, '\x1f');
+    let hash = fields.next()?.trim().to_string();
+    let author_name = fields.next()?.to_string();
+    let author_email = fields.next()?.to_string();
+    let date = fields.next()?.parse::<i64>().ok()? * 1000;
+    let message = fields.next()?.trim().to_string();
+
+    let diff = diff.trim_start_matches('\n').to_string();
+    let files_changed = diff
+        .lines()
+        .filter(|line| line.starts_with("diff --git"))
+        .count();
+
+    Some(GitCommitDetails {
+        hash,
+        author_name,
+        author_email,
+        date,
+        subject: message.lines().next().unwrap_or_default().to_string(),
+        message,
+        diff,
+        files_changed,
+    })
+}
+
+/// Parse `git blame --porcelain` output, stopping after `max_lines` lines
+/// Commit details are only printed the first time a commit appears, so they're remembered
+fn parse_blame_porcelain(output: &str, max_lines: usize) -> (Vec<GitBlameLine>, bool) {
+    struct CommitInfo {
+        author: String,
+        date: i64,
+    }
+
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            let Some((commit, line_number)) = current.take() else {
+                continue;
+            };
+            if lines.len() == max_lines {
+                return (lines, true);
+            }
+            let info = commits.get(&commit);
+            lines.push(GitBlameLine {
+                line_number,
+                uncommitted: commit == UNCOMMITTED_HASH,
+                author: info.map(|i| i.author.clone()).unwrap_or_default(),
+                date: info.map(|i| i.date).unwrap_or_default(),
+                commit,
+                content: content.to_string(),
+            });
+            continue;
+        }
+
+        let mut fields = line.split(' ');
+        let first = fields.next().unwrap_or_default();
+        let is_header = first.len() == 40 && first.chars().all(|c| c.is_ascii_hexdigit());
+        if is_header {
+            // `<hash> <original line> <final line> [<group size>]`
+            let line_number = fields.nth(1).and_then(|n| n.parse().ok()).unwrap_or(0);
+            commits.entry(first.to_string()).or_insert(CommitInfo {
+                author: String::new(),
+                date: 0,
+            });
+            current = Some((first.to_string(), line_number));
+            continue;
+        }
+
+        let Some(info) = current
+            .as_ref()
+            .and_then(|(commit, _)| commits.get_mut(commit))
+        else {
+            continue;
+        };
+        if let Some(author) = line.strip_prefix("author ") {
+            info.author = author.to_string();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            info.date = time.parse::<i64>().unwrap_or(0) * 1000;
+        }
+    }
+
+    (lines, false)
+}
+
+/// Ensure the working directory exists and is a git repository
+fn require_git_repo(working_directory: &str) -> Result<&Path, AppError> {
+    let cwd = Path::new(working_directory);
+
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+
+    if !cwd.join(".git").exists() {
+        return Err(AppError::NotAGitRepo);
+    }
+
+    Ok(cwd)
+}
+
+/// Resolve a repo-relative path, rejecting anything that escapes the working directory,
+/// the repository root itself (`.`) and anything inside `.git`
+fn resolve_repo_path(cwd: &Path, file_path: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(file_path);
+    let is_relative = relative
+        .components()
+        .any(|c| matches!(c, Component::Normal(_)))
+        && relative.components().all(|c| match c {
+            Component::Normal(name) => !name.eq_ignore_ascii_case(".git"),
+            Component::CurDir => true,
+            _ => false,
+        });
+
+    if !is_relative {
+        return Err(AppError::InvalidInput(format!(
+            "Path must be inside the repository: {}",
+            file_path
+        )));
+    }
+
+    let full_path = cwd.join(relative);
+
+    // Catch symlinked parents pointing outside the repo
+    if let (Ok(root), Some(Ok(parent))) = (
+        cwd.canonicalize(),
+        full_path.parent().map(|p| p.canonicalize()),
+    ) {
+        if !parent.starts_with(&root) {
+            return Err(AppError::InvalidInput(format!(
+                "Path must be inside the repository: {}",
+                file_path
+            )));
+        }
+    }
+
+    Ok(full_path)
+}
+
+/// Whether git lists the path as untracked and not ignored
+fn is_untracked_file(cwd: &Path, file_path: &str) -> Result<bool, AppError> {
+    let listed = run_git(
+        cwd,
+        &[
+            "ls-files",
+            "--others",
+            "--exclude-standard",
+            "--",
+            file_path,
+        ],
+    )?;
+    // ls-files prints repo-relative paths with `/`; drop any `./` segments to compare
+    let normalized = Path::new(file_path)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    Ok(listed.lines().any(|line| line == normalized))
+}
+
+/// Run a git subcommand, mapping spawn failures and non-zero exits to errors
+fn run_git(cwd: &Path, args: &[&str]) -> Result<String, AppError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git {}: {}", args[0], e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::CommandFailed(format!(
+            "git {} failed: {}",
+            args[0], stderr
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Whether the repository has at least one commit (HEAD resolves)
+fn has_commits(cwd: &Path) -> Result<bool, AppError> {
+    let rev_parse = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to check git history: {}", e)))?;
+
+    Ok(rev_parse.status.success())
+}
+
+/// Whether the directory is inside a git work tree (at any depth)
+fn is_inside_work_tree(cwd: &Path) -> Result<bool, AppError> {
+    let rev_parse = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to check for a repository: {}", e)))?;
+
+    Ok(rev_parse.status.success() && String::from_utf8_lossy(&rev_parse.stdout).trim() == "true")
+}
+
+/// Build `git diff` arguments for get_staged_diff/stream_git_diff
+/// None: all staged changes (or `--cached` before the first commit);
+/// Some(files): those files against HEAD (all changes when empty)
+fn build_diff_args(cwd: &Path, files: Option<Vec<String>>) -> Result<Vec<String>, AppError> {
+    let mut args = vec!["diff".to_string()];
+
+    match files {
+        Some(file_list) => {
+            args.push("HEAD".to_string());
+            if !file_list.is_empty() {
+                // Get diff for specific files (includes both staged and unstaged)
+                args.push("--".to_string());
+                args.extend(file_list);
+            }
+        }
+        None => {
+            // No files specified - first check if there are any commits in the repo
+            if has_commits(cwd)? {
+                args.push("HEAD".to_string());
+            } else {
+                // Initial commit - show all staged files
+                args.push("--cached".to_string());
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+/// Get file content at a git ref, or None if the file didn't exist at that ref
+pub(crate) fn show_file_at_ref(
+    cwd: &Path,
+    git_ref: &str,
+    file_path: &str,
+) -> Result<Option<String>, AppError> {
+    Ok(show_file_bytes_at_ref(cwd, git_ref, file_path)?
+        .map(|bytes| String::from_utf8_lossy(&bytes).to_string()))
+}
+
+/// Raw file content at a git ref (e.g. for compressed files), or None if the file
+/// didn't exist at that ref
+pub(crate) fn show_file_bytes_at_ref(
+    cwd: &Path,
+    git_ref: &str,
+    file_path: &str,
+) -> Result<Option<Vec<u8>>, AppError> {
+    // A leading `-` would make git parse the ref as an option
+    if git_ref.is_empty() || git_ref.starts_with('-') {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid git ref: {}",
+            git_ref
+        )));
+    }
+
+    // Run git show {ref}:{path} (untranslated, so the repository check below holds)
+    let output = Command::new("git")
+        .args(["show", &format!("{}:{}", git_ref, file_path)])
+        .env("LC_ALL", "C")
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to run git show: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(AppError::NotAGitRepo);
+        }
+        if is_missing_at_ref(cwd, git_ref, file_path) {
+            return Ok(None);
+        }
+        return Err(AppError::CommandFailed(format!(
+            "git show failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(Some(output.stdout))
+}
+
+/// Whether `git_ref` resolves but has no `file_path`, i.e. `git show <ref>:<path>` failed
+/// only because of the path. Decided by exit status, whatever language git reports in
+fn is_missing_at_ref(cwd: &Path, git_ref: &str, file_path: &str) -> bool {
+    let succeeds = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .output()
+            .is_ok_and(|output| output.status.success())
+    };
+    let tree = format!("{}^{{tree}}", git_ref);
+    let object = format!("{}:{}", git_ref, file_path);
+
+    succeeds(&["rev-parse", "--verify", "--quiet", &tree])
+        && !succeeds(&["cat-file", "-e", &object])
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -415,7 +1193,9 @@ mod tests {
         let result = git_status("/nonexistent/path".to_string());
 
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Working directory does not exist");
+        let err = result.unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+        assert_eq!(err.to_string(), "Working directory does not exist");
     }
 
     #[test]
@@ -457,7 +1237,7 @@ mod tests {
         let result = get_staged_diff(dir_path, None);
 
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Not a git repository");
+        assert_eq!(result.unwrap_err(), AppError::NotAGitRepo);
     }
 
     #[test]
@@ -493,6 +1273,445 @@ mod tests {
         let result = git_revert_all(dir_path);
 
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Not a git repository");
+        assert_eq!(result.unwrap_err(), AppError::NotAGitRepo);
+    }
+
+    #[test]
+    fn test_stream_diff_chunks() {
+        let mut diff = String::new();
+        for i in 0..3 {
+            diff.push_str(&format!("diff --git a/f{0} b/f{0}\n", i));
+            diff.push_str(&"+added line\n".repeat(2000));
+        }
+
+        let mut chunks: Vec<String> = Vec::new();
+        let files_changed = stream_diff_chunks(std::io::Cursor::new(diff.clone()), |c| {
+            chunks.push(c.to_string())
+        });
+
+        assert_eq!(files_changed, 3);
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.ends_with('\n')));
+        assert_eq!(chunks.concat(), diff);
+    }
+
+    fn porcelain_status(path: &Path) -> String {
+        let output = Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(path)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    #[test]
+    fn test_git_stage_and_unstage_initial_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        init_git_repo(dir_path).unwrap();
+        create_test_file(dir_path, "new.txt", "content").unwrap();
+        let working_directory = dir_path.to_string_lossy().to_string();
+
+        let result = git_stage(working_directory.clone(), vec!["new.txt".to_string()]).unwrap();
+        assert!(result.success);
+        assert!(result.status.changed_files.contains(&"new.txt".to_string()));
+        assert!(porcelain_status(dir_path).contains("A  new.txt"));
+
+        // No commits yet, so unstaging falls back to `git rm --cached`
+        let result = git_unstage(working_directory, vec!["new.txt".to_string()]).unwrap();
+        assert!(result
+            .status
+            .untracked_files
+            .contains(&"new.txt".to_string()));
+        assert!(porcelain_status(dir_path).contains("?? new.txt"));
+    }
+
+    #[test]
+    fn test_git_unstage_after_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        init_git_repo(dir_path).unwrap();
+        create_test_file(dir_path, "file.txt", "original").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(dir_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "initial"])
+            .current_dir(dir_path)
+            .output()
+            .unwrap();
+
+        create_test_file(dir_path, "file.txt", "modified").unwrap();
+        let working_directory = dir_path.to_string_lossy().to_string();
+
+        git_stage(working_directory.clone(), vec!["file.txt".to_string()]).unwrap();
+        assert!(porcelain_status(dir_path).contains("M  file.txt"));
+
+        git_unstage(working_directory, vec!["file.txt".to_string()]).unwrap();
+        assert!(porcelain_status(dir_path).contains(" M file.txt"));
+    }
+
+    #[test]
+    fn test_git_stage_requires_files() {
+        let temp_dir = TempDir::new().unwrap();
+        init_git_repo(temp_dir.path()).unwrap();
+
+        let result = git_stage(temp_dir.path().to_string_lossy().to_string(), Vec::new());
+        assert_eq!(result.unwrap_err().code(), "INVALID_INPUT");
+    }
+
+    #[test]
+    fn test_git_restore_file_tracked_and_untracked() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        init_git_repo(dir_path).unwrap();
+        create_test_file(dir_path, "tracked.txt", "original").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(dir_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "initial"])
+            .current_dir(dir_path)
+            .output()
+            .unwrap();
+        let working_directory = dir_path.to_string_lossy().to_string();
+
+        create_test_file(dir_path, "tracked.txt", "modified by AI").unwrap();
+        let result =
+            git_restore_file(working_directory.clone(), "tracked.txt".to_string()).unwrap();
+        assert_eq!(result.action, GitRestoreAction::Reverted);
+        assert_eq!(
+            fs::read_to_string(dir_path.join("tracked.txt")).unwrap(),
+            "original"
+        );
+
+        create_test_file(dir_path, "src/new.txt", "created by AI").unwrap();
+        let result =
+            git_restore_file(working_directory.clone(), "./src/new.txt".to_string()).unwrap();
+        assert_eq!(result.action, GitRestoreAction::Deleted);
+        assert!(!dir_path.join("src/new.txt").exists());
+
+        // Ignored files and untracked directories are never deleted
+        create_test_file(dir_path, ".gitignore", ".env\n").unwrap();
+        create_test_file(dir_path, ".env", "SECRET=1").unwrap();
+        let result = git_restore_file(working_directory.clone(), ".env".to_string());
+        assert_eq!(result.unwrap_err().code(), "INVALID_INPUT");
+        assert!(dir_path.join(".env").exists());
+
+        create_test_file(dir_path, "generated/out.txt", "output").unwrap();
+        let result = git_restore_file(working_directory, "generated".to_string());
+        assert_eq!(result.unwrap_err().code(), "INVALID_INPUT");
+        assert!(dir_path.join("generated/out.txt").exists());
+    }
+
+    #[test]
+    fn test_git_restore_file_keeps_repo_without_tracked_files() {
+        let temp_dir = TempDir::new().unwrap();
+        init_git_repo(temp_dir.path()).unwrap();
+        create_test_file(temp_dir.path(), "notes.txt", "keep me").unwrap();
+        let working_directory = temp_dir.path().to_string_lossy().to_string();
+
+        for path in [".", ".git"] {
+            let result = git_restore_file(working_directory.clone(), path.to_string());
+            assert_eq!(result.unwrap_err().code(), "INVALID_INPUT");
+        }
+        assert!(temp_dir.path().join("notes.txt").exists());
+        assert!(temp_dir.path().join(".git").is_dir());
+    }
+
+    #[test]
+    fn test_git_restore_file_rejects_outside_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        init_git_repo(temp_dir.path()).unwrap();
+        let working_directory = temp_dir.path().to_string_lossy().to_string();
+
+        for path in [
+            "../outside.txt",
+            "/etc/passwd",
+            "",
+            ".",
+            "./",
+            ".git",
+            "sub/.git/config",
+        ] {
+            let result = git_restore_file(working_directory.clone(), path.to_string());
+            assert_eq!(
+                result.unwrap_err().code(),
+                "INVALID_INPUT",
+                "path: {}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_ahead_behind() {
+        assert_eq!(parse_ahead_behind("3\t5\n"), Some((5, 3)));
+        assert_eq!(parse_ahead_behind("0\t0"), Some((0, 0)));
+        assert_eq!(parse_ahead_behind(""), None);
+        assert_eq!(parse_ahead_behind("3"), None);
+        assert_eq!(parse_ahead_behind("x\t1"), None);
+        assert_eq!(parse_ahead_behind("1\t2\t3"), None);
+    }
+
+    #[test]
+    fn test_parse_remotes() {
+        let output = "origin\tgit@github.com:a/b.git (fetch)\n\
+                      origin\tgit@github.com:a/b.git (push)\n\
+                      upstream\thttps://github.com/c/b.git (fetch)\n\
+                      upstream\thttps://github.com/c/b.git (push)\n";
+        assert_eq!(
+            parse_remotes(output),
+            vec![
+                GitRemote {
+                    name: "origin".to_string(),
+                    url: "git@github.com:a/b.git".to_string()
+                },
+                GitRemote {
+                    name: "upstream".to_string(),
+                    url: "https://github.com/c/b.git".to_string()
+                },
+            ]
+        );
+        assert!(parse_remotes("").is_empty());
+    }
+
+    #[test]
+    fn test_git_remote_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let origin = temp_dir.path().join("origin");
+        fs::create_dir_all(&origin).unwrap();
+        init_git_repo(&origin).unwrap();
+        create_test_file(&origin, "a.txt", "a").unwrap();
+        run_git(&origin, &["add", "."]).unwrap();
+        run_git(&origin, &["commit", "-m", "initial"]).unwrap();
+
+        // No remotes or upstream
+        let status = git_remote_status(origin.to_string_lossy().to_string()).unwrap();
+        assert!(status.remotes.is_empty());
+        assert_eq!(status.upstream, None);
+        assert_eq!((status.ahead, status.behind), (0, 0));
+
+        let clone = temp_dir.path().join("clone");
+        run_git(
+            temp_dir.path(),
+            &["clone", "--quiet", &origin.to_string_lossy(), "clone"],
+        )
+        .unwrap();
+        init_git_repo(&clone).unwrap();
+
+        // One commit behind after fetching, one ahead after committing locally
+        create_test_file(&origin, "b.txt", "b").unwrap();
+        run_git(&origin, &["add", "."]).unwrap();
+        run_git(&origin, &["commit", "-m", "upstream change"]).unwrap();
+        run_git(&clone, &["fetch", "--quiet"]).unwrap();
+        create_test_file(&clone, "c.txt", "c").unwrap();
+        run_git(&clone, &["add", "."]).unwrap();
+        run_git(&clone, &["commit", "-m", "local change"]).unwrap();
+
+        let status = git_remote_status(clone.to_string_lossy().to_string()).unwrap();
+        assert_eq!(status.remotes.len(), 1);
+        assert_eq!(status.remotes[0].name, "origin");
+        assert!(status
+            .upstream
+            .as_deref()
+            .is_some_and(|u| u.starts_with("origin/")));
+        assert_eq!((status.ahead, status.behind), (1, 1));
+        assert!(status.last_fetched_at.is_some());
+    }
+
+    #[test]
+    fn test_git_init() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().to_string_lossy().to_string();
+        create_test_file(temp_dir.path(), ".gitignore", "node_modules/\n*.log").unwrap();
+
+        let result = git_init(dir_path.clone(), Some(false), None).unwrap();
+        assert!(result.status.is_git_repo);
+        assert!(result.gitignore_updated);
+        assert!(result.commit.is_none());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap(),
+            "node_modules/\n*.log\n.specstudio/chats/\n"
+        );
+
+        // Refuses an existing repository, and directories nested inside one
+        assert!(matches!(
+            git_init(dir_path, None, None),
+            Err(AppError::InvalidInput(_))
+        ));
+        let nested = temp_dir.path().join("packages/app");
+        fs::create_dir_all(&nested).unwrap();
+        assert!(matches!(
+            git_init(nested.to_string_lossy().to_string(), None, None),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(!nested.join(".git").exists());
+    }
+
+    #[test]
+    fn test_git_init_with_initial_commit() {
+        // The fresh repo has no local identity; supply one through the environment
+        for (key, value) in [
+            ("GIT_AUTHOR_NAME", "Test User"),
+            ("GIT_AUTHOR_EMAIL", "test@example.com"),
+            ("GIT_COMMITTER_NAME", "Test User"),
+            ("GIT_COMMITTER_EMAIL", "test@example.com"),
+        ] {
+            std::env::set_var(key, value);
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path().to_string_lossy().to_string();
+        create_test_file(temp_dir.path(), "README.md", "# Project").unwrap();
+        create_test_file(temp_dir.path(), ".specstudio/chats/chat.json", "{}").unwrap();
+
+        let result = git_init(dir_path, Some(true), Some(true)).unwrap();
+        assert_eq!(result.commit.as_deref().map(str::len), Some(40));
+        // Everything but the ignored chats was committed
+        assert!(!result.status.has_changes);
+        let tracked = run_git(temp_dir.path(), &["ls-files"]).unwrap();
+        assert_eq!(
+            tracked.lines().collect::<Vec<_>>(),
+            vec![".gitignore", "README.md"]
+        );
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let output = "\
+1111111111111111111111111111111111111111 1 1 2
+author Ada Lovelace
+author-mail <ada@example.com>
+author-time 1700000000
+author-tz +0000
+summary Initial commit
+filename notes.md
+\t# Notes
+1111111111111111111111111111111111111111 2 2
+\tfirst line
+0000000000000000000000000000000000000000 3 3 1
+author Not Committed Yet
+author-mail <not.committed.yet>
+author-time 1700000500
+author-tz +0000
+summary Version of notes.md from notes.md
+filename notes.md
+\tedited line
+";
+
+        let (lines, truncated) = parse_blame_porcelain(output, MAX_BLAME_LINES);
+        assert!(!truncated);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].author, "Ada Lovelace");
+        assert_eq!(lines[0].date, 1_700_000_000_000);
+        assert_eq!(lines[0].content, "# Notes");
+        // Repeated commits reuse the details printed the first time
+        assert_eq!(lines[1].line_number, 2);
+        assert_eq!(lines[1].author, "Ada Lovelace");
+        assert!(!lines[1].uncommitted);
+        assert_eq!(lines[2].commit, UNCOMMITTED_HASH);
+        assert!(lines[2].uncommitted);
+        assert_eq!(lines[2].content, "edited line");
+
+        let (lines, truncated) = parse_blame_porcelain(output, 2);
+        assert_eq!(lines.len(), 2);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_git_blame_marks_uncommitted_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        init_git_repo(dir_path).unwrap();
+
+        create_test_file(dir_path, "notes.md", "one\ntwo\n").unwrap();
+        run_git(dir_path, &["add", "."]).unwrap();
+        run_git(dir_path, &["commit", "-m", "Add notes"]).unwrap();
+        create_test_file(dir_path, "notes.md", "one\nTWO\n").unwrap();
+
+        let blame = git_blame(
+            dir_path.to_string_lossy().to_string(),
+            "notes.md".to_string(),
+        )
+        .unwrap();
+        assert_eq!(blame.lines.len(), 2);
+        assert_eq!(blame.lines[0].author, "Test User");
+        assert!(!blame.lines[0].uncommitted);
+        assert!(blame.lines[1].uncommitted);
+        assert_eq!(blame.lines[1].content, "TWO");
+
+        assert!(git_blame(dir_path.to_string_lossy().to_string(), "../x".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_git_show_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        let working_directory = dir_path.to_string_lossy().to_string();
+        init_git_repo(dir_path).unwrap();
+
+        create_test_file(dir_path, "a.txt", "one\n").unwrap();
+        run_git(dir_path, &["add", "."]).unwrap();
+        run_git(dir_path, &["commit", "-m", "Add a"]).unwrap();
+        create_test_file(dir_path, "a.txt", "two\n").unwrap();
+        create_test_file(dir_path, "b.txt", "new\n").unwrap();
+        run_git(dir_path, &["add", "."]).unwrap();
+        run_git(dir_path, &["commit", "-m", "Update a\n\nAlso adds b."]).unwrap();
+
+        let head = git_show_commit(working_directory.clone(), None).unwrap();
+        assert_eq!(head.hash.len(), 40);
+        assert_eq!(head.author_name, "Test User");
+        assert_eq!(head.author_email, "test@example.com");
+        assert!(head.date > 0);
+        assert_eq!(head.subject, "Update a");
+        assert_eq!(head.message, "Update a\n\nAlso adds b.");
+        assert_eq!(head.files_changed, 2);
+        assert!(head.diff.starts_with("diff --git a/a.txt b/a.txt"));
+        assert!(head.diff.contains("+two"));
+
+        let first = git_show_commit(working_directory.clone(), Some("HEAD~1".to_string())).unwrap();
+        assert_eq!(first.subject, "Add a");
+        assert_eq!(first.files_changed, 1);
+
+        assert!(matches!(
+            git_show_commit(working_directory.clone(), Some("--output=x".to_string())),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(git_show_commit(working_directory, Some("no-such-ref".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_show_file_at_ref_missing_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        init_git_repo(dir_path).unwrap();
+
+        create_test_file(dir_path, "a.txt", "one\n").unwrap();
+        run_git(dir_path, &["add", "."]).unwrap();
+        run_git(dir_path, &["commit", "-m", "Add a"]).unwrap();
+        create_test_file(dir_path, "b.txt", "new\n").unwrap();
+
+        assert_eq!(
+            show_file_at_ref(dir_path, "HEAD", "a.txt")
+                .unwrap()
+                .as_deref(),
+            Some("one\n")
+        );
+        // Missing from the ref (whether or not it exists on disk) is not an error
+        assert_eq!(show_file_at_ref(dir_path, "HEAD", "b.txt").unwrap(), None);
+        assert_eq!(
+            show_file_at_ref(dir_path, "HEAD", "gone.txt").unwrap(),
+            None
+        );
+        // An unknown ref still is
+        assert!(matches!(
+            show_file_at_ref(dir_path, "no-such-branch", "a.txt"),
+            Err(AppError::CommandFailed(_))
+        ));
     }
 }
@@ -0,0 +1,108 @@
+// ============================================================================
+// HTTP Clients
+// Shared reqwest clients with timeouts, so a dead network fails a request
+// instead of hanging the command that made it
+// ============================================================================
+
+use reqwest::{Client, RequestBuilder};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+// Time allowed to establish a connection (DNS, TCP and TLS)
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Longest gap between reads on a non-streaming response
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// Default overall timeout for non-streaming requests (`httpTimeoutSecs` overrides it)
+pub(crate) const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+// Overall timeout currently applied to non-streaming requests
+static REQUEST_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+// ============================================================================
+// Clients
+// ============================================================================
+
+/// Client for short request/response calls (token exchange, validation)
+fn api_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| build_client(Some(READ_TIMEOUT)))
+}
+
+/// Client for streamed responses: no overall timeout, since a stream can legitimately
+/// run for minutes; callers detect stalls between chunks themselves
+fn streaming_client() -> &'static Client {
+    static CLIENT: OnceLock<Client> = OnceLock::new();
+    CLIENT.get_or_init(|| build_client(None))
+}
+
+fn build_client(read_timeout: Option<Duration>) -> Client {
+    let mut builder = Client::builder().connect_timeout(CONNECT_TIMEOUT);
+    if let Some(timeout) = read_timeout {
+        builder = builder.read_timeout(timeout);
+    }
+    builder.build().unwrap_or_else(|e| {
+        log::error!("Failed to build HTTP client, using defaults: {}", e);
+        Client::new()
+    })
+}
+
+/// GET with the configured overall timeout
+pub(crate) fn get(url: &str) -> RequestBuilder {
+    api_client().get(url).timeout(request_timeout())
+}
+
+/// POST with the configured overall timeout
+pub(crate) fn post(url: &str) -> RequestBuilder {
+    api_client().post(url).timeout(request_timeout())
+}
+
+/// POST for a streamed response; only connecting is time-limited
+pub(crate) fn streaming_post(url: &str) -> RequestBuilder {
+    streaming_client().post(url)
+}
+
+/// Apply the `httpTimeoutSecs` setting (None or 0 restores the default)
+pub(crate) fn set_request_timeout(secs: Option<u64>) {
+    let secs = secs
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS);
+    REQUEST_TIMEOUT_SECS.store(secs, Ordering::Relaxed);
+}
+
+fn request_timeout() -> Duration {
+    Duration::from_secs(REQUEST_TIMEOUT_SECS.load(Ordering::Relaxed))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_timeout_is_applied() {
+        let timeout_of = |builder: RequestBuilder| builder.build().unwrap().timeout().copied();
+        let default = Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS);
+
+        assert_eq!(timeout_of(get("https://example.com")), Some(default));
+
+        set_request_timeout(Some(5));
+        assert_eq!(
+            timeout_of(post("https://example.com")),
+            Some(Duration::from_secs(5))
+        );
+
+        set_request_timeout(Some(0));
+        assert_eq!(timeout_of(get("https://example.com")), Some(default));
+
+        // Streams are bounded by stall detection, not an overall timeout
+        assert_eq!(timeout_of(streaming_post("https://example.com")), None);
+    }
+}
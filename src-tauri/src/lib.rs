@@ -5,11 +5,20 @@
 
 mod auth;
 mod deps;
+mod error;
 mod filetree;
+mod fsutil;
 mod gemini;
 mod git;
+mod http;
+mod logs;
+mod project;
 mod search;
+mod settings;
 mod shell;
+mod system;
+mod testing;
+mod watch;
 mod workspace;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -18,14 +27,18 @@ pub fn run() {
     use std::fs;
 
     // Create centralized log directory in ~/.specstudio/logs
-    let home_dir = dirs::home_dir().expect("Could not resolve home directory");
-    let log_dir = home_dir.join(".specstudio").join("logs");
+    let log_dir = logs::log_directory().expect("Could not resolve home directory");
 
     // Ensure the log directory exists
     if let Err(e) = fs::create_dir_all(&log_dir) {
         eprintln!("Warning: Failed to create log directory: {}", e);
     }
 
+    // Resolve the login-shell PATH in the background; spawns fall back to built-in paths until then
+    std::thread::spawn(shell::refresh_shell_path_cache);
+    // Prompt files of runs that crashed before cleaning up after themselves
+    std::thread::spawn(shell::cleanup_stale_temp_files);
+
     tauri::Builder::default()
         .plugin(
             tauri_plugin_log::Builder::default()
@@ -35,6 +48,11 @@ pub fn run() {
                         path: log_dir,
                         file_name: None,
                     }),
+                    // In-memory ring buffer served by get_backend_logs
+                    Target::new(TargetKind::Dispatch(
+                        tauri_plugin_log::fern::Dispatch::new()
+                            .chain(Box::new(logs::BufferLogger) as Box<dyn log::Log>),
+                    )),
                 ])
                 .max_file_size(50_000) // 50KB
                 .rotation_strategy(RotationStrategy::KeepAll)
@@ -45,46 +63,133 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_process::init())
         .manage(shell::ProcessRegistry::new())
+        .manage(search::SearchRegistry::new())
+        .manage(auth::OAuthRegistry::new())
+        .manage(gemini::ChatRegistry::new())
+        .manage(gemini::RateLimiter::new())
+        .manage(workspace::ContextCache::new())
+        .manage(watch::FileWatchRegistry::new())
+        .manage(settings::StoreRecoveryReport::new())
+        .setup(|app| {
+            // Before any command opens a store, so a bad write can't brick the app
+            settings::recover_corrupt_stores(app.handle());
+            settings::apply_http_timeout(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Dependency check commands
             deps::check_dependencies,
+            deps::check_claude_compatibility,
             // Workspace commands
             workspace::validate_workspace,
+            workspace::check_workspace,
             workspace::read_workspace,
             workspace::list_specs,
+            workspace::list_specs_with_plans,
+            workspace::validate_plan,
+            workspace::renumber_plan_tickets,
             workspace::read_spec,
+            workspace::spec_artifacts,
             workspace::save_spec,
+            workspace::append_to_spec,
+            workspace::merge_specs,
+            workspace::diff_spec,
+            workspace::diff_text,
             workspace::delete_spec,
+            workspace::delete_specs,
+            workspace::archive_spec,
+            workspace::list_archived_specs,
+            workspace::unarchive_spec,
+            workspace::export_specs,
+            workspace::import_specs,
+            workspace::normalize_spec_filename,
+            workspace::is_valid_spec_filename,
             workspace::read_workspace_context,
+            workspace::cancel_context_read,
+            workspace::invalidate_context_cache,
+            workspace::read_workspace_file,
+            workspace::read_file_range,
+            workspace::write_workspace_file,
+            workspace::resolve_path,
+            workspace::get_recent_workspaces,
+            workspace::add_recent_workspace,
             // Shell commands (fully automated)
             shell::spawn_streaming_process,
             shell::cancel_streaming_processes,
+            shell::abort_all,
+            shell::refresh_shell_path,
+            shell::get_runtime_env,
+            shell::cleanup_temp_files,
+            shell::get_process_output,
+            shell::send_process_input,
+            // Settings commands
+            settings::get_settings,
+            settings::update_settings,
+            settings::take_store_recoveries,
+            // Diagnostics commands
+            logs::get_backend_logs,
+            logs::export_diagnostics,
             // Auth commands
             auth::check_google_oauth_configured,
             auth::start_google_oauth,
+            auth::cancel_oauth,
+            auth::reset_auth_provider,
             auth::check_google_auth,
             auth::get_google_access_token,
+            auth::get_google_identity,
             auth::logout_google,
             auth::check_anthropic_auth,
             auth::start_anthropic_oauth,
             auth::logout_anthropic,
             auth::check_all_auth,
+            auth::startup_auth_check,
             // Gemini chat commands
             gemini::chat_with_gemini,
             gemini::validate_gemini_api_key,
+            gemini::test_gemini_generation,
+            gemini::estimate_context_cost,
+            gemini::generate_project_summary,
+            gemini::list_chats,
+            gemini::generate_plan_from_spec,
             // Git commands
             git::git_status,
+            git::git_init,
             git::git_revert_all,
             git::git_show_file,
+            git::git_blame,
+            git::git_show_commit,
             git::read_file,
             git::get_staged_diff,
+            git::stream_git_diff,
+            git::git_stage,
+            git::git_unstage,
+            git::git_restore_file,
+            git::git_remote_status,
             // File tree commands
             filetree::get_file_tree,
+            filetree::count_workspace_files,
+            filetree::directory_size,
+            filetree::cancel_directory_size,
+            filetree::recent_files,
+            // File watch commands
+            watch::watch_file,
+            watch::unwatch_file,
             // Search commands
             search::search_files,
             search::search_file_names,
+            search::search_all,
+            search::cancel_search,
+            search::supported_languages,
+            search::replace_in_files,
+            // Project detection commands
+            project::detect_project_type,
+            project::list_npm_scripts,
+            // System integration commands
+            system::reveal_in_file_manager,
+            system::open_url,
             // Factory reset command
             workspace::factory_reset,
+            workspace::restore_from_backup,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,534 @@
+// ============================================================================
+// Project Detection
+// Identifies the workspace's stack(s) from marker files and suggests
+// run/test commands for them
+// ============================================================================
+
+use crate::error::AppError;
+use crate::settings::{keys, SETTINGS_STORE};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectStack {
+    /// Stack identifier: "node", "rust", "python" or "go"
+    pub name: String,
+    /// Marker file that identified the stack
+    pub marker: String,
+    pub run_command: Option<String>,
+    pub test_command: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTypeResult {
+    pub stacks: Vec<ProjectStack>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpmScript {
+    pub name: String,
+    pub command: String,
+}
+
+/// JavaScript package manager used to run scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+impl PackageManager {
+    /// Parse a manager name (e.g. from the `packageManager` setting)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "npm" => Some(PackageManager::Npm),
+            "pnpm" => Some(PackageManager::Pnpm),
+            "yarn" => Some(PackageManager::Yarn),
+            "bun" => Some(PackageManager::Bun),
+            _ => None,
+        }
+    }
+
+    pub fn binary(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// Arguments to run the `test` script
+    pub fn test_args(&self) -> &'static [&'static str] {
+        match self {
+            // `bun test` runs bun's own test runner, not the package.json script
+            PackageManager::Bun => &["run", "test"],
+            _ => &["test"],
+        }
+    }
+
+    /// Arguments to run the `dev` script
+    pub fn dev_args(&self) -> &'static [&'static str] {
+        &["run", "dev"]
+    }
+
+    /// Arguments to run an arbitrary package.json script
+    pub fn run_args<'a>(&self, script: &'a str) -> [&'a str; 2] {
+        ["run", script]
+    }
+
+    /// Arguments to run the `start` script
+    pub fn start_args(&self) -> &'static [&'static str] {
+        match self {
+            PackageManager::Bun => &["run", "start"],
+            _ => &["start"],
+        }
+    }
+
+    fn command_line(&self, args: &[&str]) -> String {
+        format!("{} {}", self.binary(), args.join(" "))
+    }
+}
+
+/// Test runner whose summary output can be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestFramework {
+    Jest,
+    Vitest,
+    Cargo,
+}
+
+impl TestFramework {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestFramework::Jest => "jest",
+            TestFramework::Vitest => "vitest",
+            TestFramework::Cargo => "cargo",
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Detect project stacks from marker files in the workspace root
+/// Node commands use the `packageManager` setting when set, as spawned scripts do
+#[tauri::command]
+pub fn detect_project_type(
+    app: AppHandle,
+    working_directory: Option<String>,
+) -> Result<ProjectTypeResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    detect_project_type_in(&cwd, preferred_package_manager(&app).as_deref())
+}
+
+/// List the scripts declared in the workspace's package.json
+/// Returns an empty list when there is no package.json
+#[tauri::command]
+pub fn list_npm_scripts(working_directory: Option<String>) -> Result<Vec<NpmScript>, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    read_npm_scripts(&cwd)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Parse package.json scripts (sorted by name)
+pub fn read_npm_scripts(cwd: &Path) -> Result<Vec<NpmScript>, AppError> {
+    let package_json = cwd.join("package.json");
+    if !package_json.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&package_json)
+        .map_err(|e| AppError::io("Failed to read package.json", e))?;
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidInput(format!("Malformed package.json: {}", e)))?;
+
+    let scripts = match json.get("scripts") {
+        None | Some(serde_json::Value::Null) => return Ok(Vec::new()),
+        Some(serde_json::Value::Object(scripts)) => scripts,
+        Some(_) => {
+            return Err(AppError::InvalidInput(
+                "Malformed package.json: \"scripts\" must be an object".to_string(),
+            ))
+        }
+    };
+
+    Ok(scripts
+        .iter()
+        .filter_map(|(name, command)| {
+            command.as_str().map(|command| NpmScript {
+                name: name.clone(),
+                command: command.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Package manager override from settings, if any
+pub fn preferred_package_manager(app: &AppHandle) -> Option<String> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::PACKAGE_MANAGER))
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+}
+
+fn detect_project_type_in(
+    cwd: &Path,
+    preferred_manager: Option<&str>,
+) -> Result<ProjectTypeResult, AppError> {
+    if !cwd.is_dir() {
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+
+    Ok(ProjectTypeResult {
+        stacks: detect_stacks(cwd, preferred_manager),
+    })
+}
+
+fn detect_stacks(cwd: &Path, preferred_manager: Option<&str>) -> Vec<ProjectStack> {
+    let mut stacks = Vec::new();
+
+    if cwd.join("package.json").is_file() {
+        stacks.push(detect_node(cwd, preferred_manager));
+    }
+
+    if cwd.join("Cargo.toml").is_file() {
+        stacks.push(ProjectStack {
+            name: "rust".to_string(),
+            marker: "Cargo.toml".to_string(),
+            run_command: Some("cargo run".to_string()),
+            test_command: Some("cargo test".to_string()),
+        });
+    }
+
+    if cwd.join("pyproject.toml").is_file() {
+        stacks.push(ProjectStack {
+            name: "python".to_string(),
+            marker: "pyproject.toml".to_string(),
+            run_command: None,
+            test_command: Some("pytest".to_string()),
+        });
+    }
+
+    if cwd.join("go.mod").is_file() {
+        stacks.push(ProjectStack {
+            name: "go".to_string(),
+            marker: "go.mod".to_string(),
+            run_command: Some("go run .".to_string()),
+            test_command: Some("go test ./...".to_string()),
+        });
+    }
+
+    stacks
+}
+
+/// Detect the package manager: explicit setting first, then lockfiles, else npm
+pub fn detect_package_manager(cwd: &Path, preferred: Option<&str>) -> PackageManager {
+    if let Some(manager) = preferred.and_then(PackageManager::from_name) {
+        return manager;
+    }
+
+    let lockfiles = [
+        ("pnpm-lock.yaml", PackageManager::Pnpm),
+        ("yarn.lock", PackageManager::Yarn),
+        ("bun.lockb", PackageManager::Bun),
+        ("bun.lock", PackageManager::Bun),
+        ("package-lock.json", PackageManager::Npm),
+    ];
+
+    lockfiles
+        .iter()
+        .find(|(lockfile, _)| cwd.join(lockfile).is_file())
+        .map(|(_, manager)| *manager)
+        .unwrap_or(PackageManager::Npm)
+}
+
+/// Detect the test runner behind `run_tests` from the project type
+/// Node projects are identified by their test script, then their dependencies
+pub fn detect_test_framework(cwd: &Path) -> Option<TestFramework> {
+    let Some(package) = read_package_json(cwd) else {
+        return cwd
+            .join("Cargo.toml")
+            .is_file()
+            .then_some(TestFramework::Cargo);
+    };
+
+    let test_script = package
+        .pointer("/scripts/test")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    if test_script.contains("vitest") {
+        return Some(TestFramework::Vitest);
+    }
+    if test_script.contains("jest") {
+        return Some(TestFramework::Jest);
+    }
+    if test_script.contains("cargo test") {
+        return Some(TestFramework::Cargo);
+    }
+
+    let has_dependency = |name: &str| {
+        ["dependencies", "devDependencies"].iter().any(|section| {
+            package
+                .get(section)
+                .and_then(|deps| deps.get(name))
+                .is_some()
+        })
+    };
+    if has_dependency("vitest") {
+        Some(TestFramework::Vitest)
+    } else if has_dependency("jest") {
+        Some(TestFramework::Jest)
+    } else {
+        None
+    }
+}
+
+fn read_package_json(cwd: &Path) -> Option<serde_json::Value> {
+    fs::read_to_string(cwd.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// Suggest package manager commands based on the scripts declared in package.json
+fn detect_node(cwd: &Path, preferred_manager: Option<&str>) -> ProjectStack {
+    let scripts = read_package_json(cwd)
+        .and_then(|json| json.get("scripts").cloned())
+        .unwrap_or_default();
+    let has_script = |name: &str| scripts.get(name).is_some();
+    let manager = detect_package_manager(cwd, preferred_manager);
+
+    let run_command = if has_script("dev") {
+        Some(manager.command_line(manager.dev_args()))
+    } else if has_script("start") {
+        Some(manager.command_line(manager.start_args()))
+    } else {
+        None
+    };
+    let test_command = has_script("test").then(|| manager.command_line(manager.test_args()));
+
+    ProjectStack {
+        name: "node".to_string(),
+        marker: "package.json".to_string(),
+        run_command,
+        test_command,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn stack_names(dir: &Path) -> Vec<String> {
+        detect_stacks(dir, None)
+            .into_iter()
+            .map(|s| s.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_no_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(stack_names(temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_detect_node_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "start": "node index.js", "test": "jest" } }"#,
+        )
+        .unwrap();
+
+        let stacks = detect_stacks(temp_dir.path(), None);
+        assert_eq!(stacks.len(), 1);
+        assert_eq!(stacks[0].name, "node");
+        assert_eq!(stacks[0].run_command.as_deref(), Some("npm start"));
+        assert_eq!(stacks[0].test_command.as_deref(), Some("npm test"));
+    }
+
+    #[test]
+    fn test_detect_node_without_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{}").unwrap();
+
+        let stacks = detect_stacks(temp_dir.path(), None);
+        assert_eq!(stacks[0].run_command, None);
+        assert_eq!(stacks[0].test_command, None);
+    }
+
+    #[test]
+    fn test_detect_multiple_stacks() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        fs::write(temp_dir.path().join("pyproject.toml"), "[project]").unwrap();
+        fs::write(temp_dir.path().join("go.mod"), "module example").unwrap();
+
+        assert_eq!(stack_names(temp_dir.path()), vec!["rust", "python", "go"]);
+
+        let stacks = detect_stacks(temp_dir.path(), None);
+        assert_eq!(stacks[0].test_command.as_deref(), Some("cargo test"));
+        assert_eq!(stacks[2].run_command.as_deref(), Some("go run ."));
+    }
+
+    #[test]
+    fn test_detect_project_type_missing_directory() {
+        let result = detect_project_type_in(Path::new("/nonexistent/path"), None);
+        assert_eq!(result.unwrap_err().code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_detect_package_manager_from_lockfiles() {
+        let cases = [
+            ("pnpm-lock.yaml", PackageManager::Pnpm),
+            ("yarn.lock", PackageManager::Yarn),
+            ("bun.lockb", PackageManager::Bun),
+            ("package-lock.json", PackageManager::Npm),
+        ];
+
+        for (lockfile, expected) in cases {
+            let temp_dir = TempDir::new().unwrap();
+            fs::write(temp_dir.path().join(lockfile), "").unwrap();
+            assert_eq!(detect_package_manager(temp_dir.path(), None), expected);
+        }
+    }
+
+    #[test]
+    fn test_detect_package_manager_setting_and_fallback() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            detect_package_manager(temp_dir.path(), None),
+            PackageManager::Npm
+        );
+
+        fs::write(temp_dir.path().join("yarn.lock"), "").unwrap();
+        assert_eq!(
+            detect_package_manager(temp_dir.path(), Some("pnpm")),
+            PackageManager::Pnpm
+        );
+        // Unknown setting values fall through to lockfile detection
+        assert_eq!(
+            detect_package_manager(temp_dir.path(), Some("maven")),
+            PackageManager::Yarn
+        );
+    }
+
+    #[test]
+    fn test_node_commands_use_package_manager() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "dev": "vite", "test": "vitest" } }"#,
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join("bun.lockb"), "").unwrap();
+
+        let stacks = detect_stacks(temp_dir.path(), None);
+        assert_eq!(stacks[0].run_command.as_deref(), Some("bun run dev"));
+        assert_eq!(stacks[0].test_command.as_deref(), Some("bun run test"));
+
+        // The packageManager setting wins over the lockfile, as it does when spawning
+        let stacks = detect_stacks(temp_dir.path(), Some("pnpm"));
+        assert_eq!(stacks[0].run_command.as_deref(), Some("pnpm run dev"));
+        assert_eq!(stacks[0].test_command.as_deref(), Some("pnpm test"));
+    }
+
+    #[test]
+    fn test_read_npm_scripts() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(read_npm_scripts(temp_dir.path()).unwrap().is_empty());
+
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "dev": "vite", "lint": "eslint ." } }"#,
+        )
+        .unwrap();
+
+        let scripts = read_npm_scripts(temp_dir.path()).unwrap();
+        assert_eq!(scripts.len(), 2);
+        assert_eq!(scripts[0].name, "dev");
+        assert_eq!(scripts[1].command, "eslint .");
+    }
+
+    #[test]
+    fn test_read_npm_scripts_malformed() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("package.json"), "{ \"scripts\": ").unwrap();
+
+        let err = read_npm_scripts(temp_dir.path()).unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        assert!(err.message().starts_with("Malformed package.json"));
+
+        fs::write(temp_dir.path().join("package.json"), r#"{ "scripts": [] }"#).unwrap();
+        assert!(read_npm_scripts(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_detect_test_framework() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(detect_test_framework(temp_dir.path()), None);
+
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        assert_eq!(
+            detect_test_framework(temp_dir.path()),
+            Some(TestFramework::Cargo)
+        );
+
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "test": "vitest run" }, "devDependencies": { "jest": "29" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            detect_test_framework(temp_dir.path()),
+            Some(TestFramework::Vitest)
+        );
+
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "test": "node scripts/test.js" }, "devDependencies": { "jest": "29" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            detect_test_framework(temp_dir.path()),
+            Some(TestFramework::Jest)
+        );
+
+        fs::write(
+            temp_dir.path().join("package.json"),
+            r#"{ "scripts": { "test": "mocha" } }"#,
+        )
+        .unwrap();
+        assert_eq!(detect_test_framework(temp_dir.path()), None);
+    }
+}
@@ -1,13 +1,100 @@
 // ============================================================================
 // File Search Commands
-// Provides content search with .gitignore support
+// Provides content search and replace with .gitignore support
 // Uses the 'ignore' crate to automatically respect .gitignore rules
 // ============================================================================
 
-use ignore::WalkBuilder;
+use crate::error::AppError;
+use crate::fsutil::write_atomic;
+use crate::workspace::SPECSTUDIO_IGNORE_FILE;
+use crate::settings::{keys, SETTINGS_STORE};
+use ignore::{Walk, WalkBuilder, WalkParallel, WalkState};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::io::{BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Files larger than this are skipped by content search unless overridden
+const DEFAULT_MAX_SEARCH_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Content search uses this share of the available cores unless the `searchThreads`
+/// setting or the `threads` argument says otherwise, leaving the rest for the app
+const DEFAULT_SEARCH_THREAD_DIVISOR: usize = 2;
+
+/// Id space of search_files / search_all ids in the SearchRegistry
+const SEARCH_ID_KIND: &str = "search";
+
+/// Extension → highlighting language, kept small on purpose
+/// Single source for search result tagging and the supported_languages command
+const LANGUAGE_EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("ts", "typescript"),
+    ("tsx", "tsx"),
+    ("js", "javascript"),
+    ("jsx", "jsx"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("py", "python"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("rb", "ruby"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("swift", "swift"),
+    ("php", "php"),
+    ("sh", "bash"),
+    ("md", "markdown"),
+    ("json", "json"),
+    ("toml", "toml"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("html", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sql", "sql"),
+];
+
+/// Keywords that introduce a named function or type declaration
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "fn",
+    "def",
+    "function",
+    "func",
+    "class",
+    "struct",
+    "enum",
+    "trait",
+    "interface",
+];
+
+/// Modifiers that may precede a declaration keyword
+const DECLARATION_MODIFIERS: &[&str] = &[
+    "pub",
+    "pub(crate)",
+    "async",
+    "export",
+    "default",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "unsafe",
+    "const",
+    "abstract",
+];
 
 // ============================================================================
 // Types
@@ -19,6 +106,10 @@ pub struct SearchResult {
     pub path: String,
     pub line_number: usize,
     pub line_content: String,
+    /// Highlighting language inferred from the file extension
+    pub language: Option<String>,
+    /// Nearest preceding function/class or markdown heading (best-effort)
+    pub enclosing_symbol: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,19 +118,190 @@ pub struct SearchResponse {
     pub results: Vec<SearchResult>,
     pub total_matches: usize,
     pub files_searched: usize,
+    /// Files over the size limit that were not searched
+    pub files_skipped: usize,
+    pub cancelled: bool,
+    /// Walker threads the search ran on
+    pub threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchKind {
+    FileName,
+    Content,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAllResult {
+    pub path: String,
+    pub match_kind: MatchKind,
+    /// Only set for content matches
+    pub line_number: Option<usize>,
+    pub line_content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchAllResponse {
+    pub results: Vec<SearchAllResult>,
+    pub total_matches: usize,
+    pub files_searched: usize,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceFileResult {
+    pub path: String,
+    pub replacements: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceResponse {
+    pub files: Vec<ReplaceFileResult>,
+    pub total_replacements: usize,
+    pub files_changed: usize,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageMapping {
+    /// Lowercase, without the leading dot
+    pub extension: String,
+    pub language: String,
+}
+
+// ============================================================================
+// Search Registry (for cancellation)
+// ============================================================================
+
+/// Cancellation flags for in-progress searches and other walks, keyed by registry_key
+pub struct SearchRegistry {
+    searches: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl SearchRegistry {
+    pub fn new() -> Self {
+        Self {
+            searches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, id: String) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        self.searches.lock().unwrap().insert(id, flag.clone());
+        flag
+    }
+
+    /// Returns false if no search with this id is running
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.searches.lock().unwrap().get(id) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.searches.lock().unwrap().remove(id);
+    }
+
+    /// Signal every running search to stop, returning how many were signalled
+    pub fn cancel_all(&self) -> usize {
+        let searches = self.searches.lock().unwrap();
+        for flag in searches.values() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        searches.len()
+    }
+}
+
+impl Default for SearchRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
+// Long-running walks are `async` commands so they run off the main thread;
+// otherwise a cancel_search call would queue behind the search it should stop
+
 /// Search for files containing the query string
 /// Respects .gitignore and other ignore rules
-#[tauri::command]
+/// Pass a `search_id` to allow cancelling via `cancel_search`
+/// Files over `max_file_size` bytes (default 10MB) are skipped
+/// Runs on `threads` walker threads, falling back to the `searchThreads` setting and
+/// then to half the available cores (at least one)
+/// Results are ordered by path and line, but once `max_results` is reached the walk
+/// stops early, so which files made the cut can vary between runs
+/// Matching is a case-insensitive substring per line by default. With `regex` the query
+/// is a regular expression, and `multiline` (regex only) matches it against the whole
+/// file so patterns can span lines; matches report the line they start on.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
 pub fn search_files(
+    app: AppHandle,
     query: String,
     path: String,
     max_results: Option<usize>,
+    search_id: Option<String>,
+    max_file_size: Option<u64>,
+    threads: Option<usize>,
+    regex: Option<bool>,
+    multiline: Option<bool>,
+) -> Result<SearchResponse, String> {
+    let threads = search_thread_count(threads.or_else(|| get_search_threads(&app)));
+    let mode = SearchMode::from_flags(regex.unwrap_or(false), multiline.unwrap_or(false));
+    with_cancellation(&app, SEARCH_ID_KIND, search_id, |cancel| {
+        search_files_internal(
+            query,
+            mode,
+            path,
+            max_results,
+            max_file_size,
+            threads,
+            cancel,
+        )
+    })
+}
+
+/// Request cancellation of an in-progress search
+/// Returns false if no search with this id is running
+#[tauri::command(async)]
+pub fn cancel_search(app: AppHandle, search_id: String) -> bool {
+    app.state::<SearchRegistry>()
+        .cancel(&registry_key(SEARCH_ID_KIND, &search_id))
+}
+
+/// Extension → language table used to tag search results, so the UI can match it
+#[tauri::command]
+pub fn supported_languages() -> Vec<LanguageMapping> {
+    LANGUAGE_EXTENSIONS
+        .iter()
+        .map(|(extension, language)| LanguageMapping {
+            extension: extension.to_string(),
+            language: language.to_string(),
+        })
+        .collect()
+}
+
+fn search_files_internal(
+    query: String,
+    mode: SearchMode,
+    path: String,
+    max_results: Option<usize>,
+    max_file_size: Option<u64>,
+    threads: usize,
+    cancel: Option<&AtomicBool>,
 ) -> Result<SearchResponse, String> {
     let search_path = Path::new(&path);
 
@@ -48,77 +310,193 @@ pub fn search_files(
     }
 
     let max_results = max_results.unwrap_or(1000);
-    let query_lower = query.to_lowercase();
+    let max_file_size = max_file_size.unwrap_or(DEFAULT_MAX_SEARCH_FILE_SIZE);
+    let matcher = QueryMatcher::new(&query, mode)?;
+
+    let results = Mutex::new(Vec::new());
+    let files_searched = AtomicUsize::new(0);
+    let files_skipped = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+
+    // Files are searched on `threads` walker threads; each visitor checks the
+    // shared result count and cancel flag so all threads stop promptly
+    build_parallel_walker(search_path, threads).run(|| {
+        Box::new(|result| {
+            if is_cancelled(cancel) {
+                cancelled.store(true, Ordering::Relaxed);
+                return WalkState::Quit;
+            }
+            if results.lock().unwrap().len() >= max_results {
+                return WalkState::Quit;
+            }
 
-    // Build the walker with gitignore support
-    let walker = WalkBuilder::new(search_path)
-        .hidden(true) // Respect hidden file rules
-        .git_ignore(true) // Respect .gitignore
-        .git_global(true) // Respect global gitignore
-        .git_exclude(true) // Respect .git/info/exclude
-        .standard_filters(true) // Apply standard ignore filters
-        .build();
+            let Ok(entry) = result else {
+                return WalkState::Continue; // Skip errors
+            };
+            let entry_path = entry.path();
 
-    let mut results = Vec::new();
-    let mut files_searched = 0;
+            // Only search files (not directories)
+            if !entry_path.is_file() {
+                return WalkState::Continue;
+            }
 
-    for result in walker {
-        let entry = match result {
-            Ok(entry) => entry,
-            Err(_) => continue, // Skip errors
-        };
+            // Huge files (logs, dumps) that slipped past ignore rules would stall the search
+            if exceeds_size_limit(entry_path, max_file_size) {
+                files_skipped.fetch_add(1, Ordering::Relaxed);
+                return WalkState::Continue;
+            }
 
-        let entry_path = entry.path();
+            files_searched.fetch_add(1, Ordering::Relaxed);
 
-        // Only search files (not directories)
-        if !entry_path.is_file() {
-            continue;
+            // Get relative path for display
+            let relative_path = entry_path
+                .strip_prefix(search_path)
+                .unwrap_or(entry_path)
+                .to_string_lossy()
+                .to_string();
+
+            let matches = search_file(entry_path, &relative_path, &matcher, max_results);
+            if matches.is_empty() {
+                return WalkState::Continue;
+            }
+
+            let mut results = results.lock().unwrap();
+            results.extend(matches);
+            if results.len() >= max_results {
+                WalkState::Quit
+            } else {
+                WalkState::Continue
+            }
+        })
+    });
+
+    let files_searched = files_searched.into_inner();
+    let files_skipped = files_skipped.into_inner();
+
+    if cancelled.into_inner() {
+        return Ok(SearchResponse {
+            results: Vec::new(),
+            total_matches: 0,
+            files_searched,
+            files_skipped,
+            cancelled: true,
+            threads,
+        });
+    }
+
+    // Threads finish in any order, so sort for display; when the walk stopped at
+    // max_results, the set of hits still depends on which files the threads reached first
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|a, b| a.path.cmp(&b.path).then(a.line_number.cmp(&b.line_number)));
+    results.truncate(max_results);
+    let total_matches = results.len();
+
+    Ok(SearchResponse {
+        results,
+        total_matches,
+        files_searched,
+        files_skipped,
+        cancelled: false,
+        threads,
+    })
+}
+
+/// Matches in one file, up to `max_results`
+fn search_file(
+    path: &Path,
+    relative_path: &str,
+    matcher: &QueryMatcher,
+    max_results: usize,
+) -> Vec<SearchResult> {
+    if let QueryMatcher::Multiline(re) = matcher {
+        return search_file_content(path, relative_path, re, max_results);
+    }
+
+    let mut results = Vec::new();
+    let Some(lines) = read_lines(path) else {
+        return results; // Skip unreadable files
+    };
+
+    let language = language_for_path(path);
+    let is_markdown = language == Some("markdown");
+    let mut enclosing_symbol: Option<String> = None;
+
+    // Search for query in each line
+    for (line_number, line) in lines.enumerate() {
+        if let Some(symbol) = declared_symbol(&line, is_markdown) {
+            enclosing_symbol = Some(symbol);
         }
 
-        files_searched += 1;
+        if matcher.is_line_match(&line) {
+            results.push(SearchResult {
+                path: relative_path.to_string(),
+                line_number: line_number + 1, // 1-indexed
+                line_content: line,
+                language: language.map(|l| l.to_string()),
+                enclosing_symbol: enclosing_symbol.clone(),
+            });
 
-        // Get relative path for display
-        let relative_path = entry_path
-            .strip_prefix(search_path)
-            .unwrap_or(entry_path)
-            .to_string_lossy()
-            .to_string();
+            // Stop if we've reached max results
+            if results.len() >= max_results {
+                break;
+            }
+        }
+    }
 
-        // Read file content
-        let content = match fs::read_to_string(entry_path) {
-            Ok(content) => content,
-            Err(_) => continue, // Skip binary files or unreadable files
-        };
+    results
+}
 
-        // Search for query in each line
-        for (line_number, line) in content.lines().enumerate() {
-            if line.to_lowercase().contains(&query_lower) {
-                results.push(SearchResult {
-                    path: relative_path.clone(),
-                    line_number: line_number + 1, // 1-indexed
-                    line_content: line.to_string(),
-                });
+/// Multiline matches in one file, reported at the line each match starts on
+/// The whole file is read, so non-UTF-8 (binary) files are skipped
+fn search_file_content(
+    path: &Path,
+    relative_path: &str,
+    re: &regex::Regex,
+    max_results: usize,
+) -> Vec<SearchResult> {
+    let mut results = Vec::new();
+    let Ok(content) = fs::read_to_string(path) else {
+        return results; // Skip unreadable files
+    };
+
+    let language = language_for_path(path);
+    let is_markdown = language == Some("markdown");
+    let mut enclosing_symbol: Option<String> = None;
+    let mut starts = re.find_iter(&content).map(|m| m.start()).peekable();
+    let mut line_start = 0;
+
+    for (line_number, raw_line) in content.split('\n').enumerate() {
+        let line_end = line_start + raw_line.len();
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        if let Some(symbol) = declared_symbol(line, is_markdown) {
+            enclosing_symbol = Some(symbol);
+        }
 
-                // Stop if we've reached max results
-                if results.len() >= max_results {
-                    break;
-                }
+        // One result per line, however many matches start on it
+        let mut matched = false;
+        while starts.next_if(|start| *start <= line_end).is_some() {
+            matched = true;
+        }
+        if matched {
+            results.push(SearchResult {
+                path: relative_path.to_string(),
+                line_number: line_number + 1, // 1-indexed
+                line_content: line.to_string(),
+                language: language.map(|l| l.to_string()),
+                enclosing_symbol: enclosing_symbol.clone(),
+            });
+            if results.len() >= max_results {
+                break;
             }
         }
 
-        // Stop searching files if we've reached max results
-        if results.len() >= max_results {
+        if starts.peek().is_none() {
             break;
         }
+        line_start = line_end + 1;
     }
 
-    let total_matches = results.len();
-
-    Ok(SearchResponse {
-        results,
-        total_matches,
-        files_searched,
-    })
+    results
 }
 
 /// Get list of file paths matching a query (filename search)
@@ -139,13 +517,7 @@ pub fn search_file_names(
     let query_lower = query.to_lowercase();
 
     // Build the walker with gitignore support
-    let walker = WalkBuilder::new(search_path)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .standard_filters(true)
-        .build();
+    let walker = build_walker(search_path);
 
     let mut results = Vec::new();
 
@@ -187,6 +559,418 @@ pub fn search_file_names(
     Ok(results)
 }
 
+/// Search file names and file contents in a single traversal
+/// Filename matches for a file are listed before its content matches
+#[tauri::command(async)]
+pub fn search_all(
+    app: AppHandle,
+    query: String,
+    path: String,
+    max_results: Option<usize>,
+    search_id: Option<String>,
+) -> Result<SearchAllResponse, String> {
+    with_cancellation(&app, SEARCH_ID_KIND, search_id, |cancel| {
+        search_all_internal(query, path, max_results, cancel)
+    })
+}
+
+fn search_all_internal(
+    query: String,
+    path: String,
+    max_results: Option<usize>,
+    cancel: Option<&AtomicBool>,
+) -> Result<SearchAllResponse, String> {
+    let search_path = Path::new(&path);
+
+    if !search_path.exists() {
+        return Err("Search path does not exist".to_string());
+    }
+
+    let max_results = max_results.unwrap_or(1000);
+    let query_lower = query.to_lowercase();
+
+    let mut results = Vec::new();
+    let mut files_searched = 0;
+
+    for result in build_walker(search_path) {
+        if is_cancelled(cancel) {
+            return Ok(SearchAllResponse {
+                results: Vec::new(),
+                total_matches: 0,
+                files_searched,
+                cancelled: true,
+            });
+        }
+
+        let entry = match result {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        let entry_path = entry.path();
+
+        // Only search files (not directories)
+        if !entry_path.is_file() {
+            continue;
+        }
+
+        files_searched += 1;
+
+        let relative_path = entry_path
+            .strip_prefix(search_path)
+            .unwrap_or(entry_path)
+            .to_string_lossy()
+            .to_string();
+
+        let file_name = entry_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+
+        if file_name.to_lowercase().contains(&query_lower) {
+            results.push(SearchAllResult {
+                path: relative_path.clone(),
+                match_kind: MatchKind::FileName,
+                line_number: None,
+                line_content: None,
+            });
+
+            if results.len() >= max_results {
+                break;
+            }
+        }
+
+        if exceeds_size_limit(entry_path, DEFAULT_MAX_SEARCH_FILE_SIZE) {
+            continue; // File name matched above; content is too large to scan
+        }
+        let Some(lines) = read_lines(entry_path) else {
+            continue; // Skip unreadable files
+        };
+
+        for (line_number, line) in lines.enumerate() {
+            if line.to_lowercase().contains(&query_lower) {
+                results.push(SearchAllResult {
+                    path: relative_path.clone(),
+                    match_kind: MatchKind::Content,
+                    line_number: Some(line_number + 1), // 1-indexed
+                    line_content: Some(line),
+                });
+
+                if results.len() >= max_results {
+                    break;
+                }
+            }
+        }
+
+        if results.len() >= max_results {
+            break;
+        }
+    }
+
+    let total_matches = results.len();
+
+    Ok(SearchAllResponse {
+        results,
+        total_matches,
+        files_searched,
+        cancelled: false,
+    })
+}
+
+/// Replace `query` with `replacement` in an explicit list of files (relative to `path`)
+/// Matching is case-sensitive unless `case_sensitive` is false, for literal and regex
+/// queries alike. Literal replacements are inserted as-is; with `regex`, `$1`-style
+/// captures are expanded.
+/// All files are processed before anything is written, and each write is atomic.
+#[tauri::command]
+pub fn replace_in_files(
+    query: String,
+    replacement: String,
+    path: String,
+    files: Vec<String>,
+    regex: Option<bool>,
+    dry_run: Option<bool>,
+    case_sensitive: Option<bool>,
+) -> Result<ReplaceResponse, AppError> {
+    let base_path = Path::new(&path);
+
+    if !base_path.exists() {
+        return Err(AppError::NotFound("Search path does not exist".to_string()));
+    }
+    if files.is_empty() {
+        return Err(AppError::InvalidInput(
+            "An explicit file list is required for replace".to_string(),
+        ));
+    }
+    if query.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Search query must not be empty".to_string(),
+        ));
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    let is_regex = regex.unwrap_or(false);
+    // Literal queries are escaped so both modes share one matcher
+    let pattern = regex::RegexBuilder::new(&if is_regex {
+        query.clone()
+    } else {
+        regex::escape(&query)
+    })
+    .case_insensitive(!case_sensitive.unwrap_or(true))
+    .build()
+    .map_err(|e| AppError::InvalidInput(format!("Invalid regex: {}", e)))?;
+
+    // Compute every replacement first so a bad file aborts before any writes
+    let mut pending: Vec<(PathBuf, String)> = Vec::new();
+    let mut results = Vec::new();
+
+    for file in &files {
+        let file_path = resolve_relative_path(base_path, file)?;
+        let content = fs::read_to_string(&file_path)
+            .map_err(|e| AppError::io(&format!("Failed to read {}", file), e))?;
+
+        let replacements = pattern.find_iter(&content).count();
+        let new_content = if is_regex {
+            pattern.replace_all(&content, replacement.as_str())
+        } else {
+            pattern.replace_all(&content, regex::NoExpand(&replacement))
+        }
+        .to_string();
+
+        if replacements > 0 {
+            pending.push((file_path, new_content));
+        }
+        results.push(ReplaceFileResult {
+            path: file.clone(),
+            replacements,
+        });
+    }
+
+    if !dry_run {
+        for (file_path, content) in &pending {
+            write_atomic(file_path, content)?;
+        }
+    }
+
+    Ok(ReplaceResponse {
+        total_replacements: results.iter().map(|r| r.replacements).sum(),
+        files_changed: pending.len(),
+        files: results,
+        dry_run,
+    })
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Join a relative file path onto `base`, rejecting absolute paths, `..` and
+/// symlinks that resolve outside `base`
+fn resolve_relative_path(base: &Path, file: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(file);
+    let is_safe = relative.components().count() > 0
+        && relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+    if !is_safe {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid file path: {}",
+            file
+        )));
+    }
+
+    // Symlinks can still lead out of `base`, so compare the resolved paths
+    let root = base
+        .canonicalize()
+        .map_err(|e| AppError::io("Failed to resolve search path", e))?;
+    let path = base
+        .join(relative)
+        .canonicalize()
+        .map_err(|e| AppError::io(&format!("Failed to resolve {}", file), e))?;
+    if !path.starts_with(&root) {
+        return Err(AppError::InvalidInput(format!(
+            "Path is outside the search path: {}",
+            file
+        )));
+    }
+
+    Ok(path)
+}
+
+/// Run a walk with a registered cancellation flag (if an id was given)
+/// `kind` selects the id space, see registry_key
+pub(crate) fn with_cancellation<R>(
+    app: &AppHandle,
+    kind: &str,
+    id: Option<String>,
+    search: impl FnOnce(Option<&AtomicBool>) -> R,
+) -> R {
+    let Some(id) = id else {
+        return search(None);
+    };
+
+    let key = registry_key(kind, &id);
+    let registry = app.state::<SearchRegistry>();
+    let flag = registry.register(key.clone());
+    let result = search(Some(&flag));
+    registry.remove(&key);
+    result
+}
+
+/// Registry key for an id of the given kind (e.g. SEARCH_ID_KIND)
+/// Commands share the registry so abort_all can stop every walk, but each kind has
+/// its own id space so a search can't cancel a context read with the same id
+pub(crate) fn registry_key(kind: &str, id: &str) -> String {
+    format!("{}:{}", kind, id)
+}
+
+/// Infer a highlighting language from the file extension
+fn language_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    LANGUAGE_EXTENSIONS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, language)| *language)
+}
+
+/// Name declared on this line: a markdown heading, or a function/type for code
+/// Purely lexical, so nested or multi-line declarations may be missed
+fn declared_symbol(line: &str, is_markdown: bool) -> Option<String> {
+    let trimmed = line.trim_start();
+
+    if is_markdown {
+        let heading = trimmed.strip_prefix('#')?.trim_start_matches('#').trim();
+        return (!heading.is_empty()).then(|| heading.to_string());
+    }
+
+    let mut tokens = trimmed
+        .split_whitespace()
+        .skip_while(|token| DECLARATION_MODIFIERS.contains(token));
+    let keyword = tokens.next()?;
+    if !DECLARATION_KEYWORDS.contains(&keyword) {
+        return None;
+    }
+
+    let name: String = tokens
+        .next()?
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+fn exceeds_size_limit(path: &Path, max_size: u64) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.len() > max_size)
+}
+
+/// Stream a file's lines without loading it whole
+/// Stops at the first line that isn't valid UTF-8, which also ends scans of binary files
+fn read_lines(path: &Path) -> Option<impl Iterator<Item = String>> {
+    let file = fs::File::open(path).ok()?;
+    Some(BufReader::new(file).lines().map_while(Result::ok))
+}
+
+pub(crate) fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+    cancel.is_some_and(|flag| flag.load(Ordering::Relaxed))
+}
+
+/// How search_files interprets its query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    /// Case-insensitive substring, line by line
+    Literal,
+    /// Regular expression, line by line
+    Regex,
+    /// Regular expression over the whole file content
+    Multiline,
+}
+
+impl SearchMode {
+    /// `multiline` only applies to regex queries
+    fn from_flags(regex: bool, multiline: bool) -> Self {
+        match (regex, multiline) {
+            (true, true) => SearchMode::Multiline,
+            (true, false) => SearchMode::Regex,
+            (false, _) => SearchMode::Literal,
+        }
+    }
+}
+
+/// Query compiled once per search and shared by the walker threads
+enum QueryMatcher {
+    Literal(String),
+    Regex(regex::Regex),
+    Multiline(regex::Regex),
+}
+
+impl QueryMatcher {
+    fn new(query: &str, mode: SearchMode) -> Result<Self, String> {
+        let compile = |multi_line: bool| {
+            regex::RegexBuilder::new(query)
+                .multi_line(multi_line)
+                .build()
+                .map_err(|e| format!("Invalid regex: {}", e))
+        };
+        Ok(match mode {
+            SearchMode::Literal => QueryMatcher::Literal(query.to_lowercase()),
+            SearchMode::Regex => QueryMatcher::Regex(compile(false)?),
+            // `^` and `$` keep matching at line boundaries inside the file
+            SearchMode::Multiline => QueryMatcher::Multiline(compile(true)?),
+        })
+    }
+
+    fn is_line_match(&self, line: &str) -> bool {
+        match self {
+            QueryMatcher::Literal(query_lower) => line.to_lowercase().contains(query_lower),
+            QueryMatcher::Regex(re) | QueryMatcher::Multiline(re) => re.is_match(line),
+        }
+    }
+}
+
+/// Thread count for content search: the configured value if positive, otherwise
+/// the default share of the available cores
+fn search_thread_count(configured: Option<usize>) -> usize {
+    match configured {
+        Some(threads) if threads > 0 => threads,
+        _ => std::thread::available_parallelism()
+            .map(|cores| (cores.get() / DEFAULT_SEARCH_THREAD_DIVISOR).max(1))
+            .unwrap_or(1),
+    }
+}
+
+/// `searchThreads` override from settings, if any
+fn get_search_threads(app: &AppHandle) -> Option<usize> {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::SEARCH_THREADS))
+        .and_then(|v| v.as_u64())
+        .map(|threads| threads as usize)
+}
+
+/// Build a walker that respects .gitignore and other ignore rules
+pub(crate) fn build_walker(search_path: &Path) -> Walk {
+    walk_builder(search_path).build()
+}
+
+/// Parallel variant of build_walker with the same ignore rules
+fn build_parallel_walker(search_path: &Path, threads: usize) -> WalkParallel {
+    walk_builder(search_path).threads(threads).build_parallel()
+}
+
+fn walk_builder(search_path: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(search_path);
+    builder
+        .hidden(true) // Respect hidden file rules
+        .git_ignore(true) // Respect .gitignore
+        .git_global(true) // Respect global gitignore
+        .git_exclude(true) // Respect .git/info/exclude
+        .standard_filters(true) // Apply standard ignore filters
+        .add_custom_ignore_filename(SPECSTUDIO_IGNORE_FILE) // Overrides .gitignore
+        .follow_links(false); // Never follow symlinks (cycles, escaping the workspace)
+    builder
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -217,10 +1001,14 @@ mod tests {
         create_test_file(dir_path, "test2.txt", "Another file\nNo match here").unwrap();
         create_test_file(dir_path, "test3.txt", "Testing search\nWith test keyword").unwrap();
 
-        let result = search_files(
+        let result = search_files_internal(
             "test".to_string(),
+            SearchMode::Literal,
             dir_path.to_string_lossy().to_string(),
             Some(10),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_ok());
@@ -238,10 +1026,14 @@ mod tests {
 
         create_test_file(dir_path, "case.txt", "UPPERCASE test\nlowercase TEST\nMiXeD TeSt").unwrap();
 
-        let result = search_files(
+        let result = search_files_internal(
             "test".to_string(),
+            SearchMode::Literal,
             dir_path.to_string_lossy().to_string(),
             Some(10),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_ok());
@@ -265,10 +1057,14 @@ mod tests {
             ).unwrap();
         }
 
-        let result = search_files(
+        let result = search_files_internal(
             "match".to_string(),
+            SearchMode::Literal,
             dir_path.to_string_lossy().to_string(),
             Some(5),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_ok());
@@ -280,10 +1076,14 @@ mod tests {
 
     #[test]
     fn test_search_files_nonexistent_path() {
-        let result = search_files(
+        let result = search_files_internal(
             "test".to_string(),
+            SearchMode::Literal,
             "/nonexistent/path".to_string(),
             Some(10),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_err());
@@ -333,10 +1133,14 @@ mod tests {
         create_test_file(dir_path, "normal.txt", "test content").unwrap();
         create_test_file(dir_path, "ignored.txt", "test content").unwrap();
 
-        let result = search_files(
+        let result = search_files_internal(
             "test".to_string(),
+            SearchMode::Literal,
             dir_path.to_string_lossy().to_string(),
             Some(10),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_ok());
@@ -362,10 +1166,14 @@ mod tests {
 
         create_test_file(dir_path, "test.txt", "line one\nline two with match\nline three").unwrap();
 
-        let result = search_files(
+        let result = search_files_internal(
             "match".to_string(),
+            SearchMode::Literal,
             dir_path.to_string_lossy().to_string(),
             Some(10),
+            None,
+            1,
+            None,
         );
 
         assert!(result.is_ok());
@@ -377,5 +1185,615 @@ mod tests {
         assert!(search_result.path.contains("test.txt"));
         assert_eq!(search_result.line_number, 2); // 1-indexed
         assert_eq!(search_result.line_content, "line two with match");
+        assert_eq!(search_result.language, None);
+        assert_eq!(search_result.enclosing_symbol, None);
+    }
+
+    #[test]
+    fn test_language_for_path() {
+        let cases = [
+            ("src/main.rs", Some("rust")),
+            ("app/page.tsx", Some("tsx")),
+            ("lib/util.ts", Some("typescript")),
+            ("scripts/build.py", Some("python")),
+            ("cmd/server.go", Some("go")),
+            ("README.MD", Some("markdown")),
+            ("config.yml", Some("yaml")),
+            ("Makefile", None),
+            ("data.bin", None),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(language_for_path(Path::new(path)), expected, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_search_results_include_enclosing_symbol() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(
+            dir_path,
+            "lib.rs",
+            "pub async fn load_config() {\n    let needle = 1;\n}\n",
+        )
+        .unwrap();
+        create_test_file(dir_path, "notes.md", "# Intro\n## Setup\nfind the needle\n").unwrap();
+
+        let response = search_files_internal(
+            "needle".to_string(),
+            SearchMode::Literal,
+            dir_path.to_string_lossy().to_string(),
+            Some(10),
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        let rust = response
+            .results
+            .iter()
+            .find(|r| r.path == "lib.rs")
+            .unwrap();
+        assert_eq!(rust.language.as_deref(), Some("rust"));
+        assert_eq!(rust.enclosing_symbol.as_deref(), Some("load_config"));
+
+        let markdown = response
+            .results
+            .iter()
+            .find(|r| r.path == "notes.md")
+            .unwrap();
+        assert_eq!(markdown.language.as_deref(), Some("markdown"));
+        assert_eq!(markdown.enclosing_symbol.as_deref(), Some("Setup"));
+    }
+
+    #[test]
+    fn test_declared_symbol() {
+        assert_eq!(
+            declared_symbol("def parse(x):", false).as_deref(),
+            Some("parse")
+        );
+        assert_eq!(
+            declared_symbol("export default function App() {", false).as_deref(),
+            Some("App")
+        );
+        assert_eq!(
+            declared_symbol("pub(crate) struct Registry {", false).as_deref(),
+            Some("Registry")
+        );
+        assert_eq!(declared_symbol("let fn_name = 1;", false), None);
+        assert_eq!(declared_symbol("# comment", false), None);
+        assert_eq!(declared_symbol("#", true), None);
+    }
+
+    #[test]
+    fn test_search_all_combines_file_name_and_content_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(dir_path, "config.txt", "no hit here").unwrap();
+        create_test_file(dir_path, "notes.txt", "load the config\nother").unwrap();
+        create_test_file(dir_path, "config.md", "see config").unwrap();
+
+        let response = search_all_internal(
+            "config".to_string(),
+            dir_path.to_string_lossy().to_string(),
+            Some(10),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.files_searched, 3);
+        assert_eq!(response.total_matches, 4);
+
+        let file_name_matches: Vec<&SearchAllResult> = response
+            .results
+            .iter()
+            .filter(|r| r.match_kind == MatchKind::FileName)
+            .collect();
+        assert_eq!(file_name_matches.len(), 2);
+        assert!(file_name_matches.iter().all(|r| r.line_number.is_none()));
+
+        let notes = response
+            .results
+            .iter()
+            .find(|r| r.path == "notes.txt")
+            .unwrap();
+        assert_eq!(notes.match_kind, MatchKind::Content);
+        assert_eq!(notes.line_number, Some(1));
+        assert_eq!(notes.line_content.as_deref(), Some("load the config"));
+    }
+
+    #[test]
+    fn test_search_all_max_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        for i in 0..5 {
+            create_test_file(dir_path, &format!("match{}.txt", i), "match\nmatch").unwrap();
+        }
+
+        let response = search_all_internal(
+            "match".to_string(),
+            dir_path.to_string_lossy().to_string(),
+            Some(4),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.total_matches, 4);
+    }
+
+    #[test]
+    fn test_search_cancelled_stops_walk() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        for i in 0..50 {
+            create_test_file(dir_path, &format!("file{}.txt", i), "match").unwrap();
+        }
+
+        let cancel = AtomicBool::new(true);
+        let response = search_files_internal(
+            "match".to_string(),
+            SearchMode::Literal,
+            dir_path.to_string_lossy().to_string(),
+            Some(1000),
+            None,
+            1,
+            Some(&cancel),
+        )
+        .unwrap();
+
+        assert!(response.cancelled);
+        assert!(response.results.is_empty());
+        assert_eq!(response.files_searched, 0);
+    }
+
+    #[test]
+    fn test_search_registry_cancel() {
+        let registry = SearchRegistry::new();
+        let flag = registry.register("search-1".to_string());
+
+        assert!(!flag.load(Ordering::Relaxed));
+        assert!(registry.cancel("search-1"));
+        assert!(flag.load(Ordering::Relaxed));
+
+        registry.remove("search-1");
+        assert!(!registry.cancel("search-1"));
+    }
+
+    #[test]
+    fn test_search_cancelled_from_another_thread() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        for i in 0..50 {
+            create_test_file(dir_path, &format!("dir{}/file{}.txt", i % 5, i), "match").unwrap();
+        }
+
+        // cancel_search (here: the registry) is called on another thread; the channel
+        // holds the walk back until the cancel has landed, so the outcome doesn't race
+        let registry = Arc::new(SearchRegistry::new());
+        let flag = registry.register("search-1".to_string());
+        let path = dir_path.to_string_lossy().to_string();
+        let (cancelled_tx, cancelled_rx) = std::sync::mpsc::channel();
+        let search = std::thread::spawn(move || {
+            cancelled_rx.recv().unwrap();
+            search_files_internal(
+                "match".to_string(),
+                SearchMode::Literal,
+                path,
+                Some(10_000),
+                None,
+                1,
+                Some(&flag),
+            )
+            .unwrap()
+        });
+        assert!(registry.cancel("search-1"));
+        cancelled_tx.send(()).unwrap();
+
+        let response = search.join().unwrap();
+        assert!(response.cancelled);
+        assert!(response.results.is_empty());
+        assert_eq!(response.files_searched, 0);
+    }
+
+    #[test]
+    fn test_replace_in_files_literal() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(dir_path, "a.txt", "foo bar foo").unwrap();
+        create_test_file(dir_path, "src/b.txt", "Foo foo").unwrap();
+        create_test_file(dir_path, "c.txt", "foo untouched").unwrap();
+
+        let response = replace_in_files(
+            "foo".to_string(),
+            "baz".to_string(),
+            dir_path.to_string_lossy().to_string(),
+            vec!["a.txt".to_string(), "src/b.txt".to_string()],
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.total_replacements, 3);
+        assert_eq!(response.files_changed, 2);
+        assert_eq!(response.files[0].replacements, 2);
+        assert_eq!(response.files[1].replacements, 1);
+        assert_eq!(
+            fs::read_to_string(dir_path.join("a.txt")).unwrap(),
+            "baz bar baz"
+        );
+        assert_eq!(
+            fs::read_to_string(dir_path.join("src/b.txt")).unwrap(),
+            "Foo baz"
+        );
+        // Files outside the explicit list are never touched
+        assert_eq!(
+            fs::read_to_string(dir_path.join("c.txt")).unwrap(),
+            "foo untouched"
+        );
+    }
+
+    #[test]
+    fn test_replace_in_files_case_insensitive() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        let path = dir_path.to_string_lossy().to_string();
+
+        create_test_file(dir_path, "a.txt", "foo Foo FOO").unwrap();
+        let literal = replace_in_files(
+            "foo".to_string(),
+            "$0bar".to_string(),
+            path.clone(),
+            vec!["a.txt".to_string()],
+            None,
+            None,
+            Some(false),
+        )
+        .unwrap();
+        assert_eq!(literal.total_replacements, 3);
+        // Literal replacements are not expanded
+        assert_eq!(
+            fs::read_to_string(dir_path.join("a.txt")).unwrap(),
+            "$0bar $0bar $0bar"
+        );
+
+        create_test_file(dir_path, "b.txt", "Item1 ITEM2 item3").unwrap();
+        let regex = replace_in_files(
+            r"item(\d)".to_string(),
+            "entry$1".to_string(),
+            path,
+            vec!["b.txt".to_string()],
+            Some(true),
+            None,
+            Some(false),
+        )
+        .unwrap();
+        assert_eq!(regex.total_replacements, 3);
+        assert_eq!(
+            fs::read_to_string(dir_path.join("b.txt")).unwrap(),
+            "entry1 entry2 entry3"
+        );
+    }
+
+    #[test]
+    fn test_replace_in_files_regex() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(dir_path, "a.rs", "let v1 = 1;\nlet v22 = 2;\n").unwrap();
+
+        let response = replace_in_files(
+            r"v(\d+)".to_string(),
+            "value_$1".to_string(),
+            dir_path.to_string_lossy().to_string(),
+            vec!["a.rs".to_string()],
+            Some(true),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.total_replacements, 2);
+        assert_eq!(
+            fs::read_to_string(dir_path.join("a.rs")).unwrap(),
+            "let value_1 = 1;\nlet value_22 = 2;\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_in_files_dry_run_and_validation() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        let path = dir_path.to_string_lossy().to_string();
+
+        create_test_file(dir_path, "a.txt", "foo foo").unwrap();
+
+        let response = replace_in_files(
+            "foo".to_string(),
+            "bar".to_string(),
+            path.clone(),
+            vec!["a.txt".to_string()],
+            None,
+            Some(true),
+            None,
+        )
+        .unwrap();
+        assert!(response.dry_run);
+        assert_eq!(response.total_replacements, 2);
+        assert_eq!(
+            fs::read_to_string(dir_path.join("a.txt")).unwrap(),
+            "foo foo"
+        );
+
+        let empty = replace_in_files(
+            "foo".to_string(),
+            "bar".to_string(),
+            path.clone(),
+            Vec::new(),
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(empty, Err(AppError::InvalidInput(_))));
+
+        let traversal = replace_in_files(
+            "foo".to_string(),
+            "bar".to_string(),
+            path,
+            vec!["../a.txt".to_string()],
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(traversal, Err(AppError::InvalidInput(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_in_files_rejects_symlink_escape() {
+        let outside = TempDir::new().unwrap();
+        create_test_file(outside.path(), "secret.txt", "foo").unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp_dir.path().join("link")).unwrap();
+
+        let result = replace_in_files(
+            "foo".to_string(),
+            "bar".to_string(),
+            temp_dir.path().to_string_lossy().to_string(),
+            vec!["link/secret.txt".to_string()],
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(AppError::InvalidInput(_))));
+        assert_eq!(
+            fs::read_to_string(outside.path().join("secret.txt")).unwrap(),
+            "foo"
+        );
+    }
+
+    #[test]
+    fn test_search_respects_specstudioignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(dir_path, SPECSTUDIO_IGNORE_FILE, "generated/\n").unwrap();
+        create_test_file(dir_path, "generated/api.ts", "needle").unwrap();
+        create_test_file(dir_path, "src/app.ts", "needle").unwrap();
+
+        let response = search_files_internal(
+            "needle".to_string(),
+            SearchMode::Literal,
+            dir_path.to_string_lossy().to_string(),
+            Some(10),
+            None,
+            1,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.total_matches, 1);
+        assert!(response.results[0].path.contains("app.ts"));
+    }
+
+    #[test]
+    fn test_registry_key_separates_id_spaces() {
+        let registry = SearchRegistry::new();
+        let flag = registry.register(registry_key(SEARCH_ID_KIND, "1"));
+
+        assert!(!registry.cancel(&registry_key("context", "1")));
+        assert!(!flag.load(Ordering::Relaxed));
+        assert!(registry.cancel(&registry_key(SEARCH_ID_KIND, "1")));
+        assert!(flag.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_search_registry_cancel_all() {
+        let registry = SearchRegistry::new();
+        assert_eq!(registry.cancel_all(), 0);
+
+        let first = registry.register("a".to_string());
+        let second = registry.register("b".to_string());
+        assert_eq!(registry.cancel_all(), 2);
+        assert!(first.load(Ordering::Relaxed));
+        assert!(second.load(Ordering::Relaxed));
+
+        registry.remove("a");
+        registry.remove("b");
+        assert_eq!(registry.cancel_all(), 0);
+    }
+
+    #[test]
+    fn test_search_skips_oversized_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        create_test_file(dir_path, "small.txt", "needle\n").unwrap();
+        create_test_file(dir_path, "huge.log", &"needle in a log line\n".repeat(100)).unwrap();
+
+        let response = search_files_internal(
+            "needle".to_string(),
+            SearchMode::Literal,
+            dir_path.to_string_lossy().to_string(),
+            Some(1000),
+            Some(1024),
+            1,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(response.files_searched, 1);
+        assert_eq!(response.files_skipped, 1);
+        assert_eq!(response.total_matches, 1);
+        assert_eq!(response.results[0].path, "small.txt");
+    }
+
+    #[test]
+    fn test_supported_languages_lists_common_extensions() {
+        let languages = supported_languages();
+        for (extension, language) in [
+            ("rs", "rust"),
+            ("ts", "typescript"),
+            ("tsx", "tsx"),
+            ("js", "javascript"),
+            ("py", "python"),
+            ("go", "go"),
+            ("md", "markdown"),
+            ("json", "json"),
+        ] {
+            assert!(
+                languages
+                    .iter()
+                    .any(|m| m.extension == extension && m.language == language),
+                "{} -> {}",
+                extension,
+                language
+            );
+        }
+
+        // Extensions are unique and match what search tagging infers
+        let mut extensions: Vec<&str> = languages.iter().map(|m| m.extension.as_str()).collect();
+        extensions.sort_unstable();
+        extensions.dedup();
+        assert_eq!(extensions.len(), languages.len());
+        for mapping in &languages {
+            let path = format!("file.{}", mapping.extension);
+            assert_eq!(
+                language_for_path(Path::new(&path)),
+                Some(mapping.language.as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_thread_count() {
+        assert_eq!(search_thread_count(Some(3)), 3);
+
+        // Unset or zero falls back to half the cores, never below one
+        let default = search_thread_count(None);
+        assert!(default >= 1);
+        assert_eq!(search_thread_count(Some(0)), default);
+    }
+
+    #[test]
+    fn test_search_files_uses_configured_threads() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        for i in 0..20 {
+            create_test_file(
+                dir_path,
+                &format!("dir{}/file{}.txt", i % 4, i),
+                "match\nmatch",
+            )
+            .unwrap();
+        }
+
+        let search = |threads| {
+            search_files_internal(
+                "match".to_string(),
+                SearchMode::Literal,
+                dir_path.to_string_lossy().to_string(),
+                Some(1000),
+                None,
+                threads,
+                None,
+            )
+            .unwrap()
+        };
+        let single = search(1);
+        let parallel = search(4);
+
+        assert_eq!(single.threads, 1);
+        assert_eq!(parallel.threads, 4);
+        assert_eq!(parallel.total_matches, 40);
+        assert_eq!(parallel.files_searched, 20);
+
+        // Sorted output is the same however many threads ran
+        let locations = |response: &SearchResponse| {
+            response
+                .results
+                .iter()
+                .map(|r| (r.path.clone(), r.line_number))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(locations(&single), locations(&parallel));
+    }
+
+    #[test]
+    fn test_search_files_multiline_regex() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+        create_test_file(
+            dir_path,
+            "lib.rs",
+            "// header\npub fn load(\n    path: &str,\n) -> Config {\n}\n",
+        )
+        .unwrap();
+
+        let search = |mode| {
+            search_files_internal(
+                r"fn load\(\s*path".to_string(),
+                mode,
+                dir_path.to_string_lossy().to_string(),
+                Some(10),
+                None,
+                1,
+                None,
+            )
+        };
+
+        // Line-by-line scans can't see across the newline
+        assert_eq!(search(SearchMode::Regex).unwrap().total_matches, 0);
+
+        let response = search(SearchMode::Multiline).unwrap();
+        assert_eq!(response.total_matches, 1);
+        assert_eq!(response.results[0].line_number, 2);
+        assert_eq!(response.results[0].line_content, "pub fn load(");
+        assert_eq!(
+            response.results[0].enclosing_symbol.as_deref(),
+            Some("load")
+        );
+
+        // multiline only applies to regex queries
+        assert_eq!(SearchMode::from_flags(false, true), SearchMode::Literal);
+        assert!(search_files_internal(
+            "(".to_string(),
+            SearchMode::Multiline,
+            dir_path.to_string_lossy().to_string(),
+            None,
+            None,
+            1,
+            None,
+        )
+        .unwrap_err()
+        .starts_with("Invalid regex"));
     }
 }
@@ -4,17 +4,67 @@
 // - Added deep logging for process spawning debug
 // ============================================================================
 
+use crate::project::{
+    detect_package_manager, detect_test_framework, preferred_package_manager, read_npm_scripts,
+    PackageManager,
+};
+use crate::testing::TestSummaryParser;
+use crate::settings::{keys, SETTINGS_STORE};
 use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::io::{Read, Write};
-use std::path::PathBuf;
-use std::process::{ChildStdout, ChildStderr, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH, Duration};
+use std::time::{SystemTime, UNIX_EPOCH, Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+// Upper bound on running the login shell to discover PATH (slow rc files)
+const SHELL_PATH_TIMEOUT: Duration = Duration::from_secs(3);
+// Delimits PATH in the shell output so rc-file noise can be ignored
+const SHELL_PATH_MARKER: &str = "__SPECSTUDIO_PATH__";
+
+// Recent output kept for prompt detection
+const PROMPT_TAIL_CHARS: usize = 512;
+// Longer trailing lines are treated as output, not prompts
+const MAX_PROMPT_LEN: usize = 200;
+
+// Workspace overrides for the claude instructions: `<action>.md` with a `{{spec}}` placeholder
+const PROMPTS_DIR: &str = ".specstudio/prompts";
+const SPEC_PLACEHOLDER: &str = "{{spec}}";
+
+// Prompt files handed to claude: `<temp dir>/specstudio_prompt_<process id>.txt`
+const TEMP_PROMPT_PREFIX: &str = "specstudio_prompt_";
+const TEMP_PROMPT_SUFFIX: &str = ".txt";
+// Prompt files older than this belong to runs that crashed before cleaning up
+const STALE_TEMP_FILE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Recent output kept per process for get_process_output (bytes of event data)
+const MAX_PROCESS_OUTPUT_BYTES: usize = 256 * 1024;
+// Output of this many finished processes is kept for late readers
+const MAX_FINISHED_OUTPUTS: usize = 8;
+
+// Output streamed per process before further output is dropped (`maxOutputBytes` overrides it)
+const DEFAULT_MAX_STREAMED_BYTES: usize = 16 * 1024 * 1024;
+
+// Tools whose resolution get_runtime_env reports
+const RUNTIME_ENV_BINARIES: &[&str] = &["claude", "npm", "git"];
+
+// TERM given to claude's PTY unless the spawn overrides it
+const DEFAULT_PTY_TERM: &str = "xterm-256color";
+
+// PATH reported by the user's login shell, filled in at startup
+static SHELL_PATH: Mutex<Option<String>> = Mutex::new(None);
 
 // ============================================================================
 // Types
@@ -27,6 +77,74 @@ pub struct StreamEvent {
     pub event_type: String,
     pub data: String,
     pub timestamp: u64,
+    /// Process that produced the event, for demultiplexing concurrent runs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<String>,
+    /// "stdout" or "stderr" for line-buffered package manager output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
+    /// Actionable suggestion attached to process startup failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    /// Auxiliary stream (e.g. stream_git_diff) the event belongs to; chat and process
+    /// listeners on the shared channel ignore events carrying one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<String>,
+}
+
+/// Which pipe a line of process output came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+impl OutputStream {
+    fn name(&self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        }
+    }
+
+    fn event_type(&self) -> &'static str {
+        match self {
+            OutputStream::Stdout => "output",
+            OutputStream::Stderr => "error",
+        }
+    }
+}
+
+/// Splits raw pipe reads into complete lines, holding back a partial trailing line
+/// Works on bytes so multi-byte UTF-8 characters split across reads stay intact
+#[derive(Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Complete lines (including their `\n`) made available by this chunk
+    fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let Some(last_newline) = self.pending.iter().rposition(|b| *b == b'\n') else {
+            return Vec::new();
+        };
+
+        let complete: Vec<u8> = self.pending.drain(..=last_newline).collect();
+        complete
+            .split_inclusive(|b| *b == b'\n')
+            .map(|line| String::from_utf8_lossy(line).into_owned())
+            .collect()
+    }
+
+    /// Remaining partial line, emitted once the stream ends
+    fn flush(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.pending);
+        Some(String::from_utf8_lossy(&rest).into_owned())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,45 +152,453 @@ pub struct StreamEvent {
 pub struct SpawnResult {
     pub started: bool,
     pub process_id: String,
+    /// How claude may act on the workspace (claude actions only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    /// What the permission mode trades off, for display alongside it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_note: Option<String>,
+}
+
+/// Optional per-spawn overrides for spawn_streaming_process
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnOptions {
+    /// Ask tools for colored output (default: on for claude, off for package managers)
+    pub color: Option<bool>,
+    /// `TERM` for the child (default: `xterm-256color` for claude, inherited otherwise)
+    pub term: Option<String>,
+    /// Written to the child's stdin (or PTY) right after it starts
+    /// Piped stdin is closed afterwards; include a trailing newline to submit a PTY line
+    pub initial_stdin: Option<String>,
+    /// Bytes of output forwarded before the rest is dropped (default 16MB)
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Resolved child setup shared by the claude and package manager paths
+struct ChildSetup {
+    env: TerminalEnv,
+    initial_stdin: Option<String>,
+    output_limit: Arc<OutputLimit>,
+}
+
+/// What to do with a chunk of process output under the byte cap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputAdmission {
+    Forward,
+    /// First chunk over the cap: report truncation once, then drop
+    Truncate,
+    Drop,
+}
+
+/// Cap on the bytes streamed for one process, shared by all of its readers
+/// Once exceeded nothing more is forwarded, but the process keeps running to exit
+struct OutputLimit {
+    max_bytes: usize,
+    sent: AtomicUsize,
+    truncated: AtomicBool,
+}
+
+impl OutputLimit {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            sent: AtomicUsize::new(0),
+            truncated: AtomicBool::new(false),
+        }
+    }
+
+    fn admit(&self, len: usize) -> OutputAdmission {
+        if self.truncated.load(Ordering::SeqCst) {
+            return OutputAdmission::Drop;
+        }
+        let sent = self.sent.fetch_add(len, Ordering::SeqCst) + len;
+        if sent <= self.max_bytes {
+            OutputAdmission::Forward
+        } else if !self.truncated.swap(true, Ordering::SeqCst) {
+            OutputAdmission::Truncate
+        } else {
+            OutputAdmission::Drop
+        }
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.truncated.load(Ordering::SeqCst)
+    }
+
+    /// Data of the one-off `truncated` event
+    fn truncated_message(&self) -> String {
+        format!(
+            "Output exceeded {} bytes; further output is not shown",
+            self.max_bytes
+        )
+    }
+
+    /// `complete` message, noting truncation if it happened
+    fn complete_message(&self, message: String) -> String {
+        if self.is_truncated() {
+            format!(
+                "{} (output truncated after {} bytes)",
+                message, self.max_bytes
+            )
+        } else {
+            message
+        }
+    }
+}
+
+/// Color and terminal environment variables for a spawned process
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TerminalEnv {
+    color: bool,
+    term: Option<String>,
+}
+
+impl TerminalEnv {
+    /// Apply overrides on top of the action's defaults
+    fn for_action(action: &str, options: &SpawnOptions) -> Result<Self, String> {
+        let is_claude = matches!(action, "create_code" | "gen_tests");
+        if let Some(term) = &options.term {
+            let valid = !term.is_empty()
+                && term
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c));
+            if !valid {
+                return Err(format!("Invalid TERM value: {:?}", term));
+            }
+        }
+
+        Ok(Self {
+            color: options.color.unwrap_or(is_claude),
+            term: options
+                .term
+                .clone()
+                .or_else(|| is_claude.then(|| DEFAULT_PTY_TERM.to_string())),
+        })
+    }
+
+    fn vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = if self.color {
+            vec![
+                ("FORCE_COLOR", "1".to_string()),
+                ("COLORTERM", "truecolor".to_string()),
+            ]
+        } else {
+            vec![("FORCE_COLOR", "0".to_string())]
+        };
+        if let Some(term) = &self.term {
+            vars.push(("TERM", term.clone()));
+        }
+        vars
+    }
+}
+
+/// Permission handling for claude runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    /// Read-only planning (`plan_only`)
+    Plan,
+    /// Every tool call pre-approved (the default)
+    SkipPermissions,
+    /// Safe mode: claude asks before acting; answers go through the PTY
+    Prompt,
+}
+
+impl PermissionMode {
+    fn resolve(plan_only: bool, safe_mode: bool) -> Self {
+        if plan_only {
+            PermissionMode::Plan
+        } else if safe_mode {
+            PermissionMode::Prompt
+        } else {
+            PermissionMode::SkipPermissions
+        }
+    }
+
+    fn claude_args(self) -> &'static [&'static str] {
+        match self {
+            PermissionMode::Plan => &["--permission-mode", "plan"],
+            PermissionMode::SkipPermissions => &["--dangerously-skip-permissions"],
+            PermissionMode::Prompt => &[],
+        }
+    }
+
+    fn note(self) -> &'static str {
+        match self {
+            PermissionMode::Plan => "Read-only: claude proposes changes without editing files",
+            PermissionMode::SkipPermissions => {
+                "Unattended: claude can edit files and run commands without asking. \
+                 Enable safe mode to approve each action"
+            }
+            PermissionMode::Prompt => {
+                "Safe mode: claude asks before editing files or running commands, \
+                 so the run pauses until you answer"
+            }
+        }
+    }
+}
+
+/// Buffered events of one process, for a UI catching up after a reload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutput {
+    pub process_id: String,
+    pub events: Vec<StreamEvent>,
+    /// Sequence number of `events[0]`
+    pub first_seq: u64,
+    /// Pass as `since` on the next call to get only newer events
+    pub next_seq: u64,
+    /// Events after `since` were evicted from the buffer before they could be read
+    pub dropped: bool,
+    /// The process has sent its `complete` event
+    pub finished: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CancelResult {
+    /// No kill failed (also true when nothing was running)
     pub success: bool,
+    pub killed: usize,
+    pub outcomes: Vec<KillOutcome>,
+}
+
+impl CancelResult {
+    fn from_outcomes(outcomes: Vec<KillOutcome>) -> Self {
+        Self {
+            success: outcomes.iter().all(|o| o.status != KillStatus::Failed),
+            killed: outcomes
+                .iter()
+                .filter(|o| o.status == KillStatus::Killed)
+                .count(),
+            outcomes,
+        }
+    }
+}
+
+/// What happened to one registered process when it was killed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillOutcome {
+    pub process_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    pub status: KillStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KillStatus {
+    Killed,
+    /// The kill signal could not be delivered
+    Failed,
+    /// No pid was known, so the process was only dropped from the registry
+    NoPid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortAllResult {
+    pub processes: usize,
+    pub chats: usize,
+    pub searches: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellPathResult {
+    /// Whether the login shell reported a PATH
+    pub discovered: bool,
+    /// Effective PATH used for spawned tools
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeBinary {
+    pub name: String,
+    /// Path spawns will use; the bare name when the binary wasn't found anywhere
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Environment the backend spawns tools with, for "works in my terminal" debugging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeEnv {
+    /// Effective PATH given to spawned processes (get_robust_path_env)
+    pub path: String,
+    /// PATH inherited by the app process itself
+    pub inherited_path: String,
+    /// Whether the login shell's PATH has been discovered and merged in
+    pub shell_path_discovered: bool,
+    pub binaries: Vec<RuntimeBinary>,
 }
 
 // ============================================================================
 // Process Registry
 // ============================================================================
 
-// Simplified to PTY-only (automated execution, no manual input)
+// User input arrives through send_process_input, which only PTY processes accept;
+// piped children are registered without a writer so they can still be killed
 enum ProcessWriter {
     Pty(Arc<Mutex<Option<Box<dyn Write + Send>>>>),
+    None,
 }
 
 struct ProcessHandle {
     writer: ProcessWriter,
     child_pid: Option<u32>,
+    started_at: Instant,
+}
+
+/// Ring buffer of a process's most recent events, bounded by MAX_PROCESS_OUTPUT_BYTES
+#[derive(Default)]
+struct OutputBuffer {
+    events: VecDeque<StreamEvent>,
+    bytes: usize,
+    first_seq: u64,
+    finished: bool,
+}
+
+impl OutputBuffer {
+    fn push(&mut self, event: StreamEvent) {
+        self.bytes += event.data.len();
+        self.events.push_back(event);
+        // Always keep the newest event, even if it alone exceeds the cap
+        while self.bytes > MAX_PROCESS_OUTPUT_BYTES && self.events.len() > 1 {
+            if let Some(evicted) = self.events.pop_front() {
+                self.bytes -= evicted.data.len();
+                self.first_seq += 1;
+            }
+        }
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.first_seq + self.events.len() as u64
+    }
+
+    fn read(&self, process_id: &str, since: u64) -> ProcessOutput {
+        let first_seq = since.clamp(self.first_seq, self.next_seq());
+        ProcessOutput {
+            process_id: process_id.to_string(),
+            events: self
+                .events
+                .iter()
+                .skip((first_seq - self.first_seq) as usize)
+                .cloned()
+                .collect(),
+            first_seq,
+            next_seq: self.next_seq(),
+            dropped: since < self.first_seq,
+            finished: self.finished,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProcessOutputs {
+    buffers: HashMap<String, OutputBuffer>,
+    /// Finished processes, oldest first, so their buffers can be evicted
+    finished: VecDeque<String>,
 }
 
 pub struct ProcessRegistry {
     processes: Mutex<HashMap<String, ProcessHandle>>,
+    outputs: Mutex<ProcessOutputs>,
 }
 
 impl ProcessRegistry {
     pub fn new() -> Self {
         Self {
             processes: Mutex::new(HashMap::new()),
+            outputs: Mutex::new(ProcessOutputs::default()),
         }
     }
 
+    /// Buffer an event for get_process_output; a `complete` event marks the process finished
+    fn record_output(&self, process_id: &str, event: &StreamEvent) {
+        let mut outputs = self.outputs.lock().unwrap();
+        let buffer = outputs.buffers.entry(process_id.to_string()).or_default();
+        buffer.push(event.clone());
+
+        if event.event_type == "complete" && !buffer.finished {
+            buffer.finished = true;
+            outputs.finished.push_back(process_id.to_string());
+            while outputs.finished.len() > MAX_FINISHED_OUTPUTS {
+                if let Some(oldest) = outputs.finished.pop_front() {
+                    outputs.buffers.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    fn read_output(&self, process_id: &str, since: u64) -> Option<ProcessOutput> {
+        let outputs = self.outputs.lock().unwrap();
+        outputs
+            .buffers
+            .get(process_id)
+            .map(|buffer| buffer.read(process_id, since))
+    }
+
     pub fn register_pty(&self, id: String, pty_writer: Box<dyn Write + Send>, child_pid: Option<u32>) {
         let writer_handle = Arc::new(Mutex::new(Some(pty_writer)));
-        self.processes.lock().unwrap().insert(id, ProcessHandle {
-            writer: ProcessWriter::Pty(writer_handle),
-            child_pid,
-        });
+        self.processes.lock().unwrap().insert(
+            id,
+            ProcessHandle {
+                writer: ProcessWriter::Pty(writer_handle),
+                child_pid,
+                started_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Register a piped (non-PTY) child so kill_all can stop it
+    pub fn register_child(&self, id: String, child_pid: u32) {
+        self.processes.lock().unwrap().insert(
+            id,
+            ProcessHandle {
+                writer: ProcessWriter::None,
+                child_pid: Some(child_pid),
+                started_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Most recently started process that accepts input, used when input names no process
+    fn active_process_id(&self) -> Option<String> {
+        self.processes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, handle)| matches!(handle.writer, ProcessWriter::Pty(_)))
+            .max_by_key(|(_, handle)| handle.started_at)
+            .map(|(id, _)| id.clone())
+    }
+
+    /// Write input to a process's PTY, or the active process when `id` is None
+    /// Returns the id of the process that received the input
+    fn write_input(&self, id: Option<&str>, input: &str) -> Result<String, String> {
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => self
+                .active_process_id()
+                .ok_or("No running process to send input to")?,
+        };
+        let writer_handle = self
+            .get_pty_writer(&id)
+            .ok_or_else(|| format!("Unknown process: {}", id))?;
+
+        let mut guard = writer_handle.lock().unwrap();
+        let writer = guard
+            .as_mut()
+            .ok_or_else(|| format!("Process {} no longer accepts input", id))?;
+        writer
+            .write_all(input.as_bytes())
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("Failed to write to process {}: {}", id, e))?;
+        Ok(id)
     }
 
     pub fn get_pty_writer(&self, id: &str) -> Option<Arc<Mutex<Option<Box<dyn Write + Send>>>>> {
@@ -80,6 +606,7 @@ impl ProcessRegistry {
             .get(id)
             .and_then(|h| match &h.writer {
                 ProcessWriter::Pty(w) => Some(w.clone()),
+                ProcessWriter::None => None,
             })
     }
 
@@ -87,28 +614,60 @@ impl ProcessRegistry {
         self.processes.lock().unwrap().remove(id);
     }
 
-    pub fn kill_all(&self) -> usize {
-        let mut killed = 0;
-        let mut registry = self.processes.lock().unwrap();
-        for (_, handle) in registry.drain() {
-            if let Some(pid) = handle.child_pid {
-                #[cfg(unix)]
-                {
-                    use std::process::Command;
-                    // Kill the process group to ensure all child processes are terminated
-                    let _ = Command::new("kill")
-                        .arg("-9")
-                        .arg(format!("{}", pid))
-                        .spawn();
-                    killed += 1;
-                }
-                #[cfg(not(unix))]
-                {
-                    log::warn!("Process termination not implemented for this platform");
-                }
-            }
+    /// Kill and unregister every process, reporting the outcome for each
+    pub fn kill_all(&self) -> Vec<KillOutcome> {
+        let drained: Vec<(String, Option<u32>)> = self
+            .processes
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(id, handle)| (id, handle.child_pid))
+            .collect();
+        drained
+            .into_iter()
+            .map(|(id, pid)| kill_outcome(id, pid, kill_pid))
+            .collect()
+    }
+}
+
+/// Outcome of killing one process with `kill`, which reports whether the signal was delivered
+fn kill_outcome(
+    process_id: String,
+    pid: Option<u32>,
+    kill: impl FnOnce(u32) -> bool,
+) -> KillOutcome {
+    let status = match pid {
+        Some(pid) if kill(pid) => KillStatus::Killed,
+        Some(pid) => {
+            log::warn!("Failed to kill process {} (PID {})", process_id, pid);
+            KillStatus::Failed
         }
-        killed
+        None => KillStatus::NoPid,
+    };
+    KillOutcome {
+        process_id,
+        pid,
+        status,
+    }
+}
+
+/// Send SIGKILL to a pid; false if the signal could not be delivered
+fn kill_pid(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        Command::new("kill")
+            .arg("-9")
+            .arg(pid.to_string())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+    #[cfg(not(unix))]
+    {
+        log::warn!(
+            "Process termination not implemented for this platform (PID {})",
+            pid
+        );
+        false
     }
 }
 
@@ -178,9 +737,104 @@ pub fn get_robust_path_env() -> String {
         npm_global_path.as_str(),
         cargo_path.as_str(),
     ];
-    
-    let joined_extras = extra_paths.join(":");
-    format!("{}:{}", joined_extras, existing_path)
+
+    let shell_path = SHELL_PATH.lock().unwrap().clone();
+    merge_path_entries(&extra_paths, shell_path.as_deref(), &existing_path)
+}
+
+/// First file named `binary` in a `:`-separated PATH, like a shell lookup
+fn find_in_path(binary: &str, path_env: &str) -> Option<PathBuf> {
+    path_env
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join(binary))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Join PATH sources in priority order, dropping empty and duplicate entries
+fn merge_path_entries(extras: &[&str], shell_path: Option<&str>, existing_path: &str) -> String {
+    let mut entries: Vec<&str> = Vec::new();
+    let sources = extras
+        .iter()
+        .copied()
+        .chain(shell_path.unwrap_or_default().split(':'))
+        .chain(existing_path.split(':'));
+
+    for entry in sources {
+        if !entry.is_empty() && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries.join(":")
+}
+
+/// Re-run the login shell and cache its PATH for get_robust_path_env
+/// GUI-launched apps (notably on macOS) don't inherit the user's shell PATH
+pub fn refresh_shell_path_cache() -> bool {
+    let discovered = discover_shell_path();
+    match &discovered {
+        Some(path) => log::info!("Login shell PATH: {}", path),
+        None => log::warn!("Could not determine login shell PATH, using built-in paths"),
+    }
+
+    let found = discovered.is_some();
+    *SHELL_PATH.lock().unwrap() = discovered;
+    found
+}
+
+/// Ask `$SHELL -ilc` for PATH, killing the shell if it exceeds SHELL_PATH_TIMEOUT
+#[cfg(unix)]
+fn discover_shell_path() -> Option<String> {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    let script = format!(
+        "printf '%s%s%s' '{marker}' \"$PATH\" '{marker}'",
+        marker = SHELL_PATH_MARKER
+    );
+
+    let mut child = Command::new(&shell)
+        .args(["-ilc", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| log::warn!("Failed to spawn login shell {}: {}", shell, e))
+        .ok()?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < SHELL_PATH_TIMEOUT => {
+                thread::sleep(Duration::from_millis(25));
+            }
+            _ => {
+                log::warn!("Login shell {} timed out while reading PATH", shell);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    extract_marked_path(&output)
+}
+
+#[cfg(not(unix))]
+fn discover_shell_path() -> Option<String> {
+    None
+}
+
+/// Pull PATH out from between the markers, ignoring anything rc files printed
+fn extract_marked_path(output: &str) -> Option<String> {
+    let start = output.find(SHELL_PATH_MARKER)? + SHELL_PATH_MARKER.len();
+    let end = start + output[start..].find(SHELL_PATH_MARKER)?;
+    let path = output[start..end].trim();
+    (!path.is_empty()).then(|| path.to_string())
 }
 
 fn get_timestamp() -> u64 {
@@ -190,58 +844,188 @@ fn get_timestamp() -> u64 {
         .as_millis() as u64
 }
 
-fn emit_stream_event(app: &AppHandle, event_type: &str, data: &str) {
+/// `{prefix}_{timestamp}_{n}` id; the counter keeps ids made in the same millisecond apart
+pub(crate) fn unique_id(prefix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}_{}_{}", prefix, get_timestamp(), n)
+}
+
+/// Emit an event of an auxiliary stream, tagged with the id its caller listens for
+pub(crate) fn emit_tagged_stream_event(
+    app: &AppHandle,
+    stream_id: &str,
+    event_type: &str,
+    data: &str,
+) {
+    if event_type == "error" {
+        log::error!("Stream Error: {}", data);
+    }
+    let _ = app.emit(
+        "rpc:stream:data",
+        StreamEvent {
+            event_type: event_type.to_string(),
+            data: data.to_string(),
+            timestamp: get_timestamp(),
+            process_id: None,
+            stream: None,
+            hint: None,
+            stream_id: Some(stream_id.to_string()),
+        },
+    );
+}
+
+/// Emit a stream event tagged with the process that produced it
+fn emit_process_event(app: &AppHandle, process_id: &str, event_type: &str, data: &str) {
+    send_stream_event(app, Some(process_id), None, event_type, data);
+}
+
+fn send_stream_event(
+    app: &AppHandle,
+    process_id: Option<&str>,
+    stream: Option<OutputStream>,
+    event_type: &str,
+    data: &str,
+) {
     let event = StreamEvent {
         event_type: event_type.to_string(),
         data: data.to_string(),
         timestamp: get_timestamp(),
+        process_id: process_id.map(|id| id.to_string()),
+        stream: stream.map(|s| s.name().to_string()),
+        hint: None,
+        stream_id: None,
     };
     // Log errors to backend log as well
     if event_type == "error" {
         log::error!("Stream Error: {}", data);
     }
+    publish_event(app, event);
+}
+
+/// Emit a stream event, buffering process output for get_process_output
+fn publish_event(app: &AppHandle, event: StreamEvent) {
+    if let (Some(process_id), Some(registry)) =
+        (&event.process_id, app.try_state::<ProcessRegistry>())
+    {
+        registry.record_output(process_id, &event);
+    }
     let _ = app.emit("rpc:stream:data", event);
 }
 
-fn stream_stdout(mut stdout: ChildStdout, app: AppHandle) {
-    let mut buffer = [0u8; 1024]; // Increased buffer size
-    loop {
-        match stdout.read(&mut buffer) {
-            Ok(0) => break, 
-            Ok(n) => {
-                let text = String::from_utf8_lossy(&buffer[..n]);
-                // Log output trace for debugging (verbose)
-                log::trace!("STDOUT: {}", text);
-                emit_stream_event(&app, "output", &text);
-            }
-            Err(e) => {
-                log::error!("Error reading stdout: {}", e);
-                break;
+/// Ghost user: wait for Claude's permissions screen, then select "Yes, I accept"
+/// Blocks for about 1.7s; run it off the spawning thread
+fn ghost_accept_permissions(registry: &ProcessRegistry, process_id: &str) {
+    log::info!("[GHOST USER] Waiting 1.5s for permissions screen...");
+    thread::sleep(Duration::from_millis(1500));
+
+    if let Some(writer_handle) = registry.get_pty_writer(process_id) {
+        if let Ok(mut guard) = writer_handle.lock() {
+            if let Some(ref mut w) = *guard {
+                log::info!("[GHOST USER] Sending DOWN arrow to select 'Yes, I accept'");
+                let _ = w.write_all(b"\x1B[B");
+                let _ = w.flush();
+
+                thread::sleep(Duration::from_millis(200));
+
+                log::info!("[GHOST USER] Sending ENTER to confirm");
+                let _ = w.write_all(b"\n");
+                let _ = w.flush();
+
+                log::info!("[GHOST USER] ✓ Permissions bypassed automatically");
             }
         }
     }
 }
 
-fn stream_stderr(mut stderr: ChildStderr, app: AppHandle) {
+/// Write the spawn-time input; dropping a piped stdin afterwards sends EOF
+fn write_initial_stdin(mut writer: impl Write, input: &str) -> std::io::Result<()> {
+    writer.write_all(input.as_bytes())?;
+    writer.flush()
+}
+
+/// Forward a pipe to the frontend one complete line per event
+/// Lines are also fed to the test summary parser, if one is attached, even once
+/// the output limit has cut off forwarding
+fn stream_lines(
+    reader: impl Read,
+    app: AppHandle,
+    process_id: String,
+    stream: OutputStream,
+    summary: Option<Arc<Mutex<TestSummaryParser>>>,
+    limit: Arc<OutputLimit>,
+) {
+    read_lines(reader, stream, |line| {
+        if let Some(parser) = &summary {
+            parser.lock().unwrap().feed(&strip_ansi(line));
+        }
+        match limit.admit(line.len()) {
+            OutputAdmission::Forward => {}
+            OutputAdmission::Truncate => {
+                log::warn!("Process {} exceeded its output limit", process_id);
+                emit_process_event(&app, &process_id, "truncated", &limit.truncated_message());
+                return;
+            }
+            OutputAdmission::Drop => return,
+        }
+        if stream == OutputStream::Stderr {
+            log::info!("STDERR: {}", line); // Log stderr as info to catch prompt questions
+        } else {
+            log::trace!("STDOUT: {}", line);
+        }
+        send_stream_event(
+            &app,
+            Some(&process_id),
+            Some(stream),
+            stream.event_type(),
+            line,
+        );
+    });
+}
+
+/// Read a pipe to EOF, calling `emit` once per complete line
+/// A trailing partial line is flushed when the pipe closes
+fn read_lines(mut reader: impl Read, stream: OutputStream, mut emit: impl FnMut(&str)) {
     let mut buffer = [0u8; 1024];
+    let mut lines = LineBuffer::default();
+
     loop {
-        match stderr.read(&mut buffer) {
+        match reader.read(&mut buffer) {
             Ok(0) => break,
-            Ok(n) => {
-                let text = String::from_utf8_lossy(&buffer[..n]);
-                log::info!("STDERR: {}", text); // Log stderr as info to catch prompt questions
-                emit_stream_event(&app, "error", &text);
-            }
+            Ok(n) => lines.push(&buffer[..n]).iter().for_each(|line| emit(line)),
             Err(e) => {
-                log::error!("Error reading stderr: {}", e);
+                log::error!("Error reading {}: {}", stream.name(), e);
                 break;
             }
         }
     }
+
+    if let Some(rest) = lines.flush() {
+        emit(&rest);
+    }
 }
 
-fn build_prompt(action: &str, spec_content: &str) -> String {
-    if action == "create_code" {
+/// Build the claude prompt for an action.
+///
+/// With `plan_only`, `create_code` asks for a proposed change list instead of edits:
+/// the prompt forbids writing files and claude runs in plan permission mode, so the
+/// user can review the output before starting a real run.
+fn build_prompt(action: &str, spec_content: &str, plan_only: bool) -> String {
+    if action == "create_code" && plan_only {
+        format!(
+            r#"You are planning an implementation based on the following specification.
+
+## Specification
+{}
+
+## Instructions
+1. Do NOT create, modify or delete any files - this is a preview only
+2. Inspect the project as needed to understand its structure
+3. Output the proposed changes as a list: for each file, whether it would be created, modified or deleted, followed by a short summary of the change
+4. Finish with any open questions or risks the user should review before implementation"#,
+            spec_content
+        )
+    } else if action == "create_code" {
         format!(
             r#"You are implementing code based on the following specification.
 
@@ -272,22 +1056,71 @@ fn build_prompt(action: &str, spec_content: &str) -> String {
     }
 }
 
+/// Prompt for an action, preferring the workspace's override file over the built-in text
+/// Plan-only previews always use the built-in prompt so they stay read-only
+fn resolve_prompt(root: &Path, action: &str, spec_content: &str, plan_only: bool) -> String {
+    if plan_only {
+        return build_prompt(action, spec_content, plan_only);
+    }
+
+    let override_path = root.join(PROMPTS_DIR).join(format!("{}.md", action));
+    match fs::read_to_string(&override_path) {
+        Ok(template) => {
+            log::info!("Using prompt override: {}", override_path.display());
+            render_prompt_template(&template, spec_content)
+        }
+        Err(_) => build_prompt(action, spec_content, plan_only),
+    }
+}
+
+/// Substitute the spec into a prompt template
+/// Templates without the placeholder get the spec appended so it is never dropped
+fn render_prompt_template(template: &str, spec_content: &str) -> String {
+    if template.contains(SPEC_PLACEHOLDER) {
+        template.replace(SPEC_PLACEHOLDER, spec_content)
+    } else {
+        format!(
+            "{}\n\n## Specification\n{}",
+            template.trim_end(),
+            spec_content
+        )
+    }
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
 
+/// `options` overrides color and TERM defaults for the spawned process
 #[tauri::command]
 pub fn spawn_streaming_process(
     app: AppHandle,
     action: String,
     working_directory: Option<String>,
     spec_content: Option<String>,
+    script_name: Option<String>,
+    plan_only: Option<bool>,
+    options: Option<SpawnOptions>,
 ) -> Result<SpawnResult, String> {
+    let plan_only = plan_only.unwrap_or(false) && action == "create_code";
+    let options = options.unwrap_or_default();
+    if options.max_output_bytes == Some(0) {
+        return Err("maxOutputBytes must be greater than 0".to_string());
+    }
+    let setup = ChildSetup {
+        env: TerminalEnv::for_action(&action, &options)?,
+        initial_stdin: options.initial_stdin,
+        output_limit: Arc::new(OutputLimit::new(
+            options
+                .max_output_bytes
+                .unwrap_or(DEFAULT_MAX_STREAMED_BYTES),
+        )),
+    };
     let cwd = working_directory
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    let process_id = format!("proc_{}", get_timestamp());
+    let process_id = unique_id("proc");
     let registry = app.state::<ProcessRegistry>();
 
     log::info!("--- SPATTERING PROCESS START ---");
@@ -297,10 +1130,17 @@ pub fn spawn_streaming_process(
     match action.as_str() {
         "create_code" | "gen_tests" => {
             let spec = spec_content.ok_or("specContent is required for this action")?;
-            let prompt = build_prompt(&action, &spec);
-
-            let temp_dir = std::env::temp_dir();
-            let temp_path = temp_dir.join(format!("specstudio_prompt_{}.txt", process_id));
+            let prompt = resolve_prompt(&cwd, &action, &spec, plan_only);
+            // Monorepo specs may scope the run to a subdirectory via `sub_path`
+            let cwd = crate::workspace::resolve_spec_working_directory(&cwd, &spec)
+                .map_err(|e| e.to_string())?;
+            let permission_mode = PermissionMode::resolve(plan_only, get_safe_mode(&app));
+            let permission_args = permission_mode.claude_args();
+
+            let temp_path = std::env::temp_dir().join(format!(
+                "{}{}{}",
+                TEMP_PROMPT_PREFIX, process_id, TEMP_PROMPT_SUFFIX
+            ));
 
             log::info!("Writing prompt to: {}", temp_path.display());
             fs::write(&temp_path, &prompt)
@@ -310,6 +1150,24 @@ pub fn spawn_streaming_process(
             let claude_path = resolve_binary_path("claude");
             let robust_path = get_robust_path_env();
 
+            // The script wrapper below would mask a missing or non-executable claude,
+            // so check up front
+            let target = SpawnTarget {
+                binary: "claude",
+                binary_path: &claude_path,
+                cwd: &cwd,
+            };
+            if let Some(kind) = target.preflight() {
+                let _ = fs::remove_file(&temp_path);
+                return Err(report_spawn_failure(
+                    &app,
+                    &process_id,
+                    &target,
+                    kind,
+                    "preflight check failed",
+                ));
+            }
+
             log::info!("Using Claude Binary: {}", claude_path);
             log::info!("Using PATH Env: {}", robust_path);
 
@@ -338,37 +1196,49 @@ pub fn spawn_streaming_process(
                 c.arg(&claude_path);
                 c.arg("-p");
                 c.arg(temp_path.to_str().unwrap());
-                c.arg("--dangerously-skip-permissions");
+                c.args(permission_args);
                 c
             } else {
                 log::warn!("script command not available, using direct execution");
                 let mut c = CommandBuilder::new(&claude_path);
                 c.arg("-p");
                 c.arg(temp_path.to_str().unwrap());
-                c.arg("--dangerously-skip-permissions");
+                c.args(permission_args);
                 c
             };
 
             cmd.cwd(&cwd);
             cmd.env("PATH", robust_path);
-            cmd.env("FORCE_COLOR", "1");
-            cmd.env("TERM", "xterm-256color");
-            cmd.env("COLORTERM", "truecolor");
+            for (key, value) in setup.env.vars() {
+                cmd.env(key, value);
+            }
 
-            log::info!("Spawning claude in AUTOMATED mode...");
+            match permission_mode {
+                PermissionMode::Plan => log::info!("Spawning claude in PLAN-ONLY mode..."),
+                PermissionMode::SkipPermissions => {
+                    log::info!("Spawning claude in AUTOMATED mode...")
+                }
+                PermissionMode::Prompt => log::info!("Spawning claude in SAFE mode..."),
+            }
 
             // Spawn the child process attached to the slave PTY
-            let mut child = pty_pair
-                .slave
-                .spawn_command(cmd)
-                .map_err(|e| {
-                    log::error!("Failed to spawn claude via PTY: {}", e);
-                    format!("Failed to spawn claude: {}", e)
-                })?;
+            let mut child = pty_pair.slave.spawn_command(cmd).map_err(|e| {
+                log::error!("Failed to spawn claude via PTY: {}", e);
+                let kind = target.classify(e.downcast_ref::<std::io::Error>().map(|e| e.kind()));
+                report_spawn_failure(&app, &process_id, &target, kind, &e.to_string())
+            })?;
 
             let child_pid = child.process_id();
             log::info!("Process spawned successfully. PID: {:?}", child_pid);
-            emit_stream_event(&app, "output", &format!("⚙️  Automated execution started (PID: {:?})\n", child_pid.unwrap_or(0)));
+            emit_process_event(
+                &app,
+                &process_id,
+                "output",
+                &format!(
+                    "⚙️  Automated execution started (PID: {:?})\n",
+                    child_pid.unwrap_or(0)
+                ),
+            );
 
             // Get the master PTY reader and writer
             let mut reader = pty_pair.master.try_clone_reader()
@@ -379,30 +1249,34 @@ pub fn spawn_streaming_process(
             let proc_id = process_id.clone();
             registry.register_pty(proc_id.clone(), writer, child_pid);
 
+            // Tell the user up front which permission mode this run uses
+            emit_process_event(
+                &app,
+                &process_id,
+                "output",
+                &format!("🔒 {}\n", permission_mode.note()),
+            );
+
             // GHOST USER AUTOMATION - Bypass permissions screen automatically
+            // Safe mode leaves every permission prompt to the user
+            // Initial stdin is written once the ghost keystrokes are done, so it can't
+            // land on the permissions screen; the PTY buffers it until the reader starts
             let proc_id_ghost = process_id.clone();
             let app_ghost = app.clone();
+            let initial_stdin = setup.initial_stdin.clone();
 
             thread::spawn(move || {
                 let registry = app_ghost.state::<ProcessRegistry>();
+                if permission_mode != PermissionMode::Prompt {
+                    ghost_accept_permissions(&registry, &proc_id_ghost);
+                }
 
-                log::info!("[GHOST USER] Waiting 1.5s for permissions screen...");
-                thread::sleep(Duration::from_millis(1500));
-
-                if let Some(writer_handle) = registry.get_pty_writer(&proc_id_ghost) {
-                    if let Ok(mut guard) = writer_handle.lock() {
-                        if let Some(ref mut w) = *guard {
-                            log::info!("[GHOST USER] Sending DOWN arrow to select 'Yes, I accept'");
-                            let _ = w.write_all(b"\x1B[B");
-                            let _ = w.flush();
-
-                            thread::sleep(Duration::from_millis(200));
-
-                            log::info!("[GHOST USER] Sending ENTER to confirm");
-                            let _ = w.write_all(b"\n");
-                            let _ = w.flush();
-
-                            log::info!("[GHOST USER] ✓ Permissions bypassed automatically");
+                if let Some(input) = initial_stdin {
+                    if let Some(writer_handle) = registry.get_pty_writer(&proc_id_ghost) {
+                        if let Some(writer) = writer_handle.lock().unwrap().as_mut() {
+                            if let Err(e) = write_initial_stdin(writer, &input) {
+                                log::warn!("Failed to write initial stdin: {}", e);
+                            }
                         }
                     }
                 }
@@ -410,8 +1284,11 @@ pub fn spawn_streaming_process(
 
             // PTY READER - Small buffer for immediate streaming
             let app_reader = app.clone();
+            let proc_id_reader = process_id.clone();
+            let limit_reader = setup.output_limit.clone();
             let reader_thread = thread::spawn(move || {
                 let mut buffer = [0u8; 1024]; // Small buffer for low-latency streaming
+                let mut prompt_detector = PromptDetector::default();
                 loop {
                     match reader.read(&mut buffer) {
                         Ok(0) => {
@@ -421,7 +1298,35 @@ pub fn spawn_streaming_process(
                         Ok(n) => {
                             let text = String::from_utf8_lossy(&buffer[..n]);
                             log::trace!("PTY READ ({} bytes)", n);
-                            emit_stream_event(&app_reader, "output", &text);
+                            match limit_reader.admit(n) {
+                                OutputAdmission::Forward => emit_process_event(
+                                    &app_reader,
+                                    &proc_id_reader,
+                                    "output",
+                                    &text,
+                                ),
+                                OutputAdmission::Truncate => {
+                                    log::warn!(
+                                        "Process {} exceeded its output limit",
+                                        proc_id_reader
+                                    );
+                                    emit_process_event(
+                                        &app_reader,
+                                        &proc_id_reader,
+                                        "truncated",
+                                        &limit_reader.truncated_message(),
+                                    );
+                                }
+                                OutputAdmission::Drop => {}
+                            }
+                            if let Some(prompt) = prompt_detector.push(&text) {
+                                log::info!(
+                                    "Process {} is waiting for input: {}",
+                                    proc_id_reader,
+                                    prompt
+                                );
+                                emit_process_event(&app_reader, &proc_id_reader, "prompt", &prompt);
+                            }
                         }
                         Err(e) => {
                             log::error!("Error reading from PTY: {}", e);
@@ -434,6 +1339,7 @@ pub fn spawn_streaming_process(
             // WAITER THREAD - Cleanup on completion
             let app_complete = app.clone();
             let temp_path_clone = temp_path.clone();
+            let limit_complete = setup.output_limit.clone();
 
             thread::spawn(move || {
                 // Wait for reader thread to finish (indicates process has closed PTY)
@@ -457,63 +1363,164 @@ pub fn spawn_streaming_process(
                     registry.remove(&proc_id);
                 }
                 let _ = fs::remove_file(&temp_path_clone);
-                emit_stream_event(&app_complete, "complete", &format!("✓ Execution completed (exit code: {})", exit_code));
+                emit_process_event(
+                    &app_complete,
+                    &proc_id,
+                    "complete",
+                    &limit_complete.complete_message(format!(
+                        "✓ Execution completed (exit code: {})",
+                        exit_code
+                    )),
+                );
             });
 
-            Ok(SpawnResult { started: true, process_id })
+            Ok(SpawnResult {
+                started: true,
+                process_id,
+                permission_mode: Some(permission_mode),
+                permission_note: Some(permission_mode.note().to_string()),
+            })
         }
         
         "run_tests" | "run_app" => {
-            // Similar logging added to npm commands if needed, 
-            // but sticking to claude focus for now.
-             spawn_npm_command(&app, &registry, &process_id, &cwd, 
-                if action == "run_tests" { &["test"] } else { &["run", "dev"] }
-             )
+            let manager = detect_package_manager(&cwd, preferred_package_manager(&app).as_deref());
+            let args = if action == "run_tests" {
+                manager.test_args()
+            } else {
+                manager.dev_args()
+            };
+
+            // Only test runs whose runner we recognize get a parsed summary
+            let summary = (action == "run_tests")
+                .then(|| detect_test_framework(&cwd))
+                .flatten()
+                .map(|framework| Arc::new(Mutex::new(TestSummaryParser::new(framework))));
+
+            spawn_package_manager_command(&app, &process_id, &cwd, manager, args, &setup, summary)
+        }
+
+        "run_script" => {
+            let script = script_name.ok_or("scriptName is required for this action")?;
+            let scripts = read_npm_scripts(&cwd).map_err(String::from)?;
+            if !scripts.iter().any(|s| s.name == script) {
+                return Err(format!("Script not found in package.json: {}", script));
+            }
+
+            let manager = detect_package_manager(&cwd, preferred_package_manager(&app).as_deref());
+            spawn_package_manager_command(
+                &app,
+                &process_id,
+                &cwd,
+                manager,
+                &manager.run_args(&script),
+                &setup,
+                None,
+            )
         }
 
         _ => Err(format!("Unknown streaming action: {}", action))
     }
 }
 
-fn spawn_npm_command(
+/// Whether the `safeMode` setting is on (off by default)
+fn get_safe_mode(app: &AppHandle) -> bool {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::SAFE_MODE))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn spawn_package_manager_command(
     app: &AppHandle,
-    _registry: &ProcessRegistry,
     process_id: &str,
     cwd: &PathBuf,
+    manager: PackageManager,
     args: &[&str],
+    setup: &ChildSetup,
+    summary: Option<Arc<Mutex<TestSummaryParser>>>,
 ) -> Result<SpawnResult, String> {
-    let npm_path = resolve_binary_path("npm");
+    let binary_path = resolve_binary_path(manager.binary());
     let robust_path = get_robust_path_env();
 
-    log::info!("Spawning NPM: {} {:?}", npm_path, args);
+    log::info!("Spawning {}: {} {:?}", manager.binary(), binary_path, args);
 
-    let mut cmd = Command::new(&npm_path);
+    let mut cmd = Command::new(&binary_path);
     cmd.args(args)
         .current_dir(cwd)
         .env("PATH", robust_path)
-        .env("FORCE_COLOR", "0")
-        .stdin(Stdio::null())
+        .envs(setup.env.vars())
+        .stdin(if setup.initial_stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    let mut child = cmd.spawn()
-        .map_err(|e| format!("Failed to spawn npm: {}", e))?;
+    let mut child = cmd.spawn().map_err(|e| {
+        let target = SpawnTarget {
+            binary: manager.binary(),
+            binary_path: &binary_path,
+            cwd,
+        };
+        let kind = target.classify(Some(e.kind()));
+        report_spawn_failure(app, process_id, &target, kind, &e.to_string())
+    })?;
 
     let stdout = child.stdout.take();
     let stderr = child.stderr.take();
     let proc_id = process_id.to_string();
+    app.state::<ProcessRegistry>()
+        .register_child(proc_id.clone(), child.id());
 
     let app_stdout = app.clone();
-    let stdout_thread = if let Some(stdout) = stdout {
-        Some(thread::spawn(move || stream_stdout(stdout, app_stdout)))
-    } else { None };
+    let proc_id_stdout = proc_id.clone();
+    let summary_stdout = summary.clone();
+    let limit_stdout = setup.output_limit.clone();
+    let stdout_thread = stdout.map(|stdout| {
+        thread::spawn(move || {
+            stream_lines(
+                stdout,
+                app_stdout,
+                proc_id_stdout,
+                OutputStream::Stdout,
+                summary_stdout,
+                limit_stdout,
+            )
+        })
+    });
 
     let app_stderr = app.clone();
-    let stderr_thread = if let Some(stderr) = stderr {
-        Some(thread::spawn(move || stream_stderr(stderr, app_stderr)))
-    } else { None };
+    let proc_id_stderr = proc_id.clone();
+    let summary_stderr = summary.clone();
+    let limit_stderr = setup.output_limit.clone();
+    let stderr_thread = stderr.map(|stderr| {
+        thread::spawn(move || {
+            stream_lines(
+                stderr,
+                app_stderr,
+                proc_id_stderr,
+                OutputStream::Stderr,
+                summary_stderr,
+                limit_stderr,
+            )
+        })
+    });
+
+    // Written from its own thread after the readers start, so a child that
+    // answers before reading all its input can't fill a pipe and deadlock
+    if let (Some(stdin), Some(input)) = (child.stdin.take(), setup.initial_stdin.clone()) {
+        thread::spawn(move || {
+            if let Err(e) = write_initial_stdin(stdin, &input) {
+                log::warn!("Failed to write initial stdin: {}", e);
+            }
+        });
+    }
 
     let app_complete = app.clone();
+    let proc_id_complete = proc_id.clone();
+    let limit_complete = setup.output_limit.clone();
 
     thread::spawn(move || {
         if let Some(t) = stdout_thread { let _ = t.join(); }
@@ -522,26 +1529,998 @@ fn spawn_npm_command(
         let exit_code = match child.wait() {
             Ok(status) => status.code().unwrap_or(-1),
             Err(e) => {
-                log::error!("Error waiting for npm process: {}", e);
+                log::error!("Error waiting for package manager process: {}", e);
                 -1
             }
         };
+        app_complete
+            .state::<ProcessRegistry>()
+            .remove(&proc_id_complete);
+
+        // Both readers have finished, so the parser has seen all output
+        let parsed = summary.and_then(|parser| parser.lock().unwrap().summary());
+        if let Some(parsed) = parsed {
+            if let Ok(data) = serde_json::to_string(&parsed) {
+                emit_process_event(&app_complete, &proc_id_complete, "test_summary", &data);
+            }
+        }
 
-        emit_stream_event(&app_complete, "complete", &format!("Process exited with code {}", exit_code));
+        emit_process_event(
+            &app_complete,
+            &proc_id_complete,
+            "complete",
+            &limit_complete.complete_message(format!("Process exited with code {}", exit_code)),
+        );
     });
 
-    Ok(SpawnResult { started: true, process_id: proc_id })
+    Ok(SpawnResult {
+        started: true,
+        process_id: proc_id,
+        permission_mode: None,
+        permission_note: None,
+    })
+}
+
+// ============================================================================
+// Spawn Failures
+// ============================================================================
+
+/// Common reasons a process fails to start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpawnFailureKind {
+    BinaryNotFound,
+    PermissionDenied,
+    MissingWorkingDirectory,
+    Other,
+}
+
+/// Binary and directory a process is being started with
+struct SpawnTarget<'a> {
+    binary: &'a str,
+    /// Path from resolve_binary_path (the bare name when no search path matched)
+    binary_path: &'a str,
+    cwd: &'a Path,
+}
+
+impl SpawnTarget<'_> {
+    /// Detect failures that can be spotted before spawning
+    fn preflight(&self) -> Option<SpawnFailureKind> {
+        if !self.cwd.is_dir() {
+            return Some(SpawnFailureKind::MissingWorkingDirectory);
+        }
+        match self.locate_binary() {
+            None => Some(SpawnFailureKind::BinaryNotFound),
+            Some(path) if !is_executable(&path) => Some(SpawnFailureKind::PermissionDenied),
+            Some(_) => None,
+        }
+    }
+
+    /// Map a spawn error to its likely cause
+    fn classify(&self, error: Option<std::io::ErrorKind>) -> SpawnFailureKind {
+        if !self.cwd.is_dir() {
+            return SpawnFailureKind::MissingWorkingDirectory;
+        }
+        match error {
+            Some(std::io::ErrorKind::PermissionDenied) => SpawnFailureKind::PermissionDenied,
+            Some(std::io::ErrorKind::NotFound) => SpawnFailureKind::BinaryNotFound,
+            _ => self.preflight().unwrap_or(SpawnFailureKind::Other),
+        }
+    }
+
+    fn hint(&self, kind: SpawnFailureKind) -> Option<String> {
+        match kind {
+            SpawnFailureKind::BinaryNotFound if self.binary == "claude" => Some(format!(
+                "Claude Code CLI was not found. Install it from {} and make sure it is on your PATH.",
+                crate::deps::CLAUDE_INSTALL_URL
+            )),
+            SpawnFailureKind::BinaryNotFound => Some(format!(
+                "`{}` was not found. Install it, or choose another package manager in Settings.",
+                self.binary
+            )),
+            SpawnFailureKind::PermissionDenied => Some(format!(
+                "`{}` is not executable. Run `chmod +x {}` and try again.",
+                self.binary_path, self.binary_path
+            )),
+            SpawnFailureKind::MissingWorkingDirectory => Some(format!(
+                "The workspace directory {} no longer exists. Re-open the workspace to revalidate it.",
+                self.cwd.display()
+            )),
+            SpawnFailureKind::Other => None,
+        }
+    }
+
+    /// Absolute path of the binary, searching the robust PATH for bare names
+    fn locate_binary(&self) -> Option<PathBuf> {
+        let path = Path::new(self.binary_path);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+        std::env::split_paths(&get_robust_path_env())
+            .map(|dir| dir.join(self.binary_path))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Emit a structured `error` event for a failed start and return the command error
+fn report_spawn_failure(
+    app: &AppHandle,
+    process_id: &str,
+    target: &SpawnTarget,
+    kind: SpawnFailureKind,
+    error: &str,
+) -> String {
+    let message = format!("Failed to spawn {}: {}", target.binary, error);
+    let hint = target.hint(kind);
+    log::error!("Stream Error: {} (hint: {:?})", message, hint);
+
+    let event = StreamEvent {
+        event_type: "error".to_string(),
+        data: message.clone(),
+        timestamp: get_timestamp(),
+        process_id: Some(process_id.to_string()),
+        stream: None,
+        hint: hint.clone(),
+        stream_id: None,
+    };
+    publish_event(app, event);
+
+    match hint {
+        Some(hint) => format!("{}. {}", message, hint),
+        None => message,
+    }
+}
+
+// ============================================================================
+// Prompt Detection
+// ============================================================================
+
+/// Tracks recent PTY output and reports when a process appears to wait for input
+/// Emitted once per prompt as a `prompt` event tagged with the process id
+#[derive(Default)]
+struct PromptDetector {
+    tail: String,
+    reported: bool,
+}
+
+impl PromptDetector {
+    /// Feed a chunk of output; returns the prompt text the first time one is seen
+    fn push(&mut self, text: &str) -> Option<String> {
+        self.tail.push_str(&strip_ansi(text));
+        if self.tail.len() > PROMPT_TAIL_CHARS {
+            let mut cut = self.tail.len() - PROMPT_TAIL_CHARS;
+            while !self.tail.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.tail.drain(..cut);
+        }
+
+        match detect_prompt(&self.tail) {
+            Some(prompt) if !self.reported => {
+                self.reported = true;
+                Some(prompt.to_string())
+            }
+            Some(_) => None,
+            None => {
+                self.reported = false;
+                None
+            }
+        }
+    }
+}
+
+/// Heuristic: output ends mid-line with a short question/label such as
+/// `Continue? `, `Password:` or `Overwrite (y/n) `
+fn detect_prompt(output: &str) -> Option<&str> {
+    if output.ends_with('\n') {
+        return None;
+    }
+
+    let last_line = output.rsplit(['\n', '\r']).next()?.trim();
+    if last_line.is_empty() || last_line.len() > MAX_PROMPT_LEN {
+        return None;
+    }
+
+    let lower = last_line.to_lowercase();
+    let is_prompt = last_line.ends_with('?')
+        || last_line.ends_with(':')
+        || ["(y/n)", "[y/n]", "(yes/no)"]
+            .iter()
+            .any(|choice| lower.ends_with(choice));
+
+    is_prompt.then_some(last_line)
+}
+
+/// Delete `specstudio_prompt_*.txt` files in `dir` last modified more than `max_age` ago
+fn cleanup_temp_files_in(dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let now = SystemTime::now();
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(TEMP_PROMPT_PREFIX) && name.ends_with(TEMP_PROMPT_SUFFIX)
+        })
+        .filter(|entry| {
+            entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age)
+        })
+        .filter(|entry| fs::remove_file(entry.path()).is_ok())
+        .count()
+}
+
+/// Remove ANSI escape sequences (colors, cursor movement, OSC titles)
+fn strip_ansi(text: &str) -> std::borrow::Cow<'_, str> {
+    static ANSI: OnceLock<Regex> = OnceLock::new();
+    ANSI.get_or_init(|| {
+        Regex::new(r"\x1B(?:\[[0-9;?]*[ -/]*[@-~]|\][^\x07\x1B]*(?:\x07|\x1B\\)|[@-Z\\-_])")
+            .unwrap()
+    })
+    .replace_all(text, "")
 }
 
 // ============================================================================
-// Manual Input Removed - Fully Automated Execution
-// The Ghost User automation thread handles all TUI interactions automatically
+// Process Input
+// The Ghost User thread answers the startup permissions screen; anything else
+// (e.g. safe mode permission prompts) is answered through send_process_input
 // ============================================================================
 
+/// Send input to a running process's PTY
+/// Targets `process_id` when given, otherwise the most recently started process
+/// Returns the id of the process that received the input
 #[tauri::command]
+pub fn send_process_input(
+    app: AppHandle,
+    input: String,
+    process_id: Option<String>,
+) -> Result<String, String> {
+    app.state::<ProcessRegistry>()
+        .write_input(process_id.as_deref(), &input)
+}
+
+/// Kill every registered process (PTY sessions and package-manager children alike)
+#[tauri::command(async)]
 pub fn cancel_streaming_processes(app: AppHandle) -> CancelResult {
     let registry = app.state::<ProcessRegistry>();
-    let killed = registry.kill_all();
-    log::info!("Cancelled {} streaming processes", killed);
-    CancelResult { success: true }
-}
\ No newline at end of file
+    let result = CancelResult::from_outcomes(registry.kill_all());
+    log::info!(
+        "Cancelled {} of {} streaming processes",
+        result.killed,
+        result.outcomes.len()
+    );
+    result
+}
+
+/// Buffered output of a running or recently finished process
+/// Returns events numbered `since` onwards (default 0, i.e. everything still buffered)
+#[tauri::command]
+pub fn get_process_output(
+    app: AppHandle,
+    process_id: String,
+    since: Option<u64>,
+) -> Result<ProcessOutput, String> {
+    app.state::<ProcessRegistry>()
+        .read_output(&process_id, since.unwrap_or(0))
+        .ok_or_else(|| format!("No output recorded for process {}", process_id))
+}
+
+/// Remove SpecStudio prompt files left in the temp dir by crashed runs
+/// Only files older than `max_age_secs` (default 24h) are removed, so running processes keep theirs
+/// Returns how many files were removed
+#[tauri::command]
+pub fn cleanup_temp_files(max_age_secs: Option<u64>) -> usize {
+    let max_age = max_age_secs
+        .map(Duration::from_secs)
+        .unwrap_or(STALE_TEMP_FILE_AGE);
+    cleanup_temp_files_in(&std::env::temp_dir(), max_age)
+}
+
+/// Startup sweep of stale prompt files, with the default age threshold
+pub fn cleanup_stale_temp_files() -> usize {
+    let removed = cleanup_temp_files_in(&std::env::temp_dir(), STALE_TEMP_FILE_AGE);
+    if removed > 0 {
+        log::info!("Removed {} stale prompt files", removed);
+    }
+    removed
+}
+
+/// Recompute the cached login-shell PATH (e.g. after the user edits their rc files)
+#[tauri::command]
+pub async fn refresh_shell_path() -> Result<ShellPathResult, String> {
+    let discovered = tokio::task::spawn_blocking(refresh_shell_path_cache)
+        .await
+        .map_err(|e| format!("Failed to refresh shell PATH: {}", e))?;
+
+    Ok(ShellPathResult {
+        discovered,
+        path: get_robust_path_env(),
+    })
+}
+
+/// Effective spawn PATH plus where claude, npm and git resolve to
+/// Only PATH is reported; the rest of the process environment is left out
+#[tauri::command]
+pub fn get_runtime_env() -> RuntimeEnv {
+    let path = get_robust_path_env();
+    let binaries = RUNTIME_ENV_BINARIES
+        .iter()
+        .map(|name| {
+            let resolved = resolve_binary_path(name);
+            let found = if Path::new(&resolved).is_absolute() {
+                Some(PathBuf::from(&resolved)).filter(|p| p.is_file())
+            } else {
+                find_in_path(name, &path)
+            };
+            RuntimeBinary {
+                name: name.to_string(),
+                exists: found.is_some(),
+                path: found.map_or(resolved, |p| p.to_string_lossy().to_string()),
+            }
+        })
+        .collect();
+
+    RuntimeEnv {
+        path,
+        inherited_path: std::env::var("PATH").unwrap_or_default(),
+        shell_path_discovered: SHELL_PATH.lock().unwrap().is_some(),
+        binaries,
+    }
+}
+
+/// Stop everything in flight: shell processes, Gemini chats and searches
+/// Safe to call repeatedly, including when nothing is running
+/// Runs off the main thread so it isn't queued behind a running command
+/// `processes` counts only processes that were actually killed
+#[tauri::command(async)]
+pub fn abort_all(app: AppHandle) -> AbortAllResult {
+    let processes = CancelResult::from_outcomes(app.state::<ProcessRegistry>().kill_all()).killed;
+    let aborted_chats = app.state::<crate::gemini::ChatRegistry>().abort_all();
+    let searches = app.state::<crate::search::SearchRegistry>().cancel_all();
+
+    // Aborted chat tasks never reach their own completion event
+    for session_id in &aborted_chats {
+        crate::gemini::emit_stream_event(&app, session_id, "complete", "Chat cancelled");
+    }
+    let chats = aborted_chats.len();
+
+    log::info!(
+        "Aborted {} processes, {} chats, {} searches",
+        processes,
+        chats,
+        searches
+    );
+    AbortAllResult {
+        processes,
+        chats,
+        searches,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_all_reports_piped_children() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let registry = ProcessRegistry::new();
+        registry.register_child("proc_pm".to_string(), child.id());
+
+        // Piped children can't take input, so they never become the active process
+        assert_eq!(registry.active_process_id(), None);
+
+        let outcomes = registry.kill_all();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].process_id, "proc_pm");
+        assert_eq!(outcomes[0].pid, Some(child.id()));
+        assert_eq!(outcomes[0].status, KillStatus::Killed);
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_cancel_result_from_kill_outcomes() {
+        let outcomes = vec![
+            kill_outcome("proc_1".to_string(), Some(101), |_| true),
+            kill_outcome("proc_2".to_string(), None, |_| unreachable!()),
+        ];
+        assert_eq!(outcomes[0].status, KillStatus::Killed);
+        assert_eq!(outcomes[1].status, KillStatus::NoPid);
+        let result = CancelResult::from_outcomes(outcomes.clone());
+        assert!(result.success);
+        assert_eq!(result.killed, 1);
+        assert_eq!(result.outcomes, outcomes);
+
+        let failed = kill_outcome("proc_3".to_string(), Some(103), |pid| {
+            assert_eq!(pid, 103);
+            false
+        });
+        assert_eq!(failed.status, KillStatus::Failed);
+        let result = CancelResult::from_outcomes(vec![failed]);
+        assert!(!result.success);
+        assert_eq!(result.killed, 0);
+
+        // Nothing running is still a success
+        let result = CancelResult::from_outcomes(Vec::new());
+        assert!(result.success);
+        assert_eq!(result.killed, 0);
+
+        let value = serde_json::to_value(&outcomes[0]).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "processId": "proc_1", "pid": 101, "status": "killed" })
+        );
+    }
+
+    #[test]
+    fn test_find_in_path() {
+        let first = tempfile::TempDir::new().unwrap();
+        let second = tempfile::TempDir::new().unwrap();
+        fs::write(second.path().join("tool"), "").unwrap();
+        fs::create_dir(first.path().join("tool-dir")).unwrap();
+        let path_env = format!("{}::{}", first.path().display(), second.path().display());
+
+        assert_eq!(
+            find_in_path("tool", &path_env),
+            Some(second.path().join("tool"))
+        );
+        // Directories don't count as binaries
+        assert_eq!(find_in_path("tool-dir", &path_env), None);
+        assert_eq!(find_in_path("missing", &path_env), None);
+
+        fs::write(first.path().join("tool"), "").unwrap();
+        assert_eq!(
+            find_in_path("tool", &path_env),
+            Some(first.path().join("tool"))
+        );
+    }
+
+    #[test]
+    fn test_get_runtime_env_reports_tool_binaries() {
+        let env = get_runtime_env();
+        let names: Vec<&str> = env.binaries.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, RUNTIME_ENV_BINARIES);
+        assert_eq!(env.path, get_robust_path_env());
+        for binary in &env.binaries {
+            assert_eq!(
+                binary.exists,
+                Path::new(&binary.path).is_file(),
+                "{}",
+                binary.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_path_entries() {
+        let extras = ["/home/me/.local/bin", "/opt/homebrew/bin", "/usr/local/bin"];
+
+        // Discovered entries come after the extras and before the inherited PATH
+        assert_eq!(
+            merge_path_entries(
+                &extras,
+                Some("/home/me/.nvm/versions/node/v20/bin:/usr/local/bin:/usr/bin"),
+                "/usr/bin:/bin"
+            ),
+            "/home/me/.local/bin:/opt/homebrew/bin:/usr/local/bin:\
+             /home/me/.nvm/versions/node/v20/bin:/usr/bin:/bin"
+        );
+
+        // Without a discovered PATH the extras still lead
+        assert_eq!(
+            merge_path_entries(&extras, None, "/usr/bin::/bin"),
+            "/home/me/.local/bin:/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin"
+        );
+    }
+
+    #[test]
+    fn test_extract_marked_path() {
+        let output = format!(
+            "Welcome back!\n{m}/usr/local/bin:/usr/bin{m}",
+            m = SHELL_PATH_MARKER
+        );
+        assert_eq!(
+            extract_marked_path(&output),
+            Some("/usr/local/bin:/usr/bin".to_string())
+        );
+        assert_eq!(extract_marked_path("no markers here"), None);
+        assert_eq!(
+            extract_marked_path(&format!("{m}{m}", m = SHELL_PATH_MARKER)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unique_id_within_same_millisecond() {
+        let ids: std::collections::HashSet<String> = (0..1000).map(|_| unique_id("proc")).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| id.starts_with("proc_")));
+    }
+
+    #[test]
+    fn test_detect_prompt() {
+        assert_eq!(
+            detect_prompt("Do you want to continue? "),
+            Some("Do you want to continue?")
+        );
+        assert_eq!(detect_prompt("building...\nPassword:"), Some("Password:"));
+        assert_eq!(
+            detect_prompt("Overwrite file (y/N) "),
+            Some("Overwrite file (y/N)")
+        );
+        assert_eq!(detect_prompt("progress 10%\rContinue?"), Some("Continue?"));
+
+        // Complete lines and ordinary output are not prompts
+        assert_eq!(detect_prompt("What changed?\n"), None);
+        assert_eq!(detect_prompt("Compiling specstudio"), None);
+        assert_eq!(detect_prompt(""), None);
+        assert_eq!(
+            detect_prompt(&format!("{}?", "x".repeat(MAX_PROMPT_LEN + 1))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prompt_detector_reports_once_per_prompt() {
+        let mut detector = PromptDetector::default();
+        assert_eq!(detector.push("Analyzing files...\n"), None);
+        assert_eq!(
+            detector.push("\x1B[1;32mProceed with changes?\x1B[0m "),
+            Some("Proceed with changes?".to_string())
+        );
+        // Redraws of the same prompt don't re-emit
+        assert_eq!(detector.push(" "), None);
+
+        // After more output, the next prompt is reported again
+        assert_eq!(detector.push("y\nApplying...\n"), None);
+        assert_eq!(detector.push("Run tests?"), Some("Run tests?".to_string()));
+    }
+
+    #[test]
+    fn test_plan_only_prompt_forbids_writes() {
+        let prompt = build_prompt("create_code", "Add a login page", true);
+        assert!(prompt.contains("Add a login page"));
+        assert!(prompt.contains("Do NOT create, modify or delete any files"));
+        assert!(!build_prompt("create_code", "spec", false).contains("preview only"));
+        // plan_only only affects create_code
+        assert_eq!(
+            build_prompt("gen_tests", "spec", true),
+            build_prompt("gen_tests", "spec", false)
+        );
+
+    }
+
+    #[test]
+    fn test_permission_mode_flags() {
+        // Plan mode wins over safe mode; it is read-only either way
+        for safe_mode in [false, true] {
+            let mode = PermissionMode::resolve(true, safe_mode);
+            assert_eq!(mode, PermissionMode::Plan);
+            assert_eq!(mode.claude_args(), ["--permission-mode", "plan"]);
+        }
+
+        let default = PermissionMode::resolve(false, false);
+        assert_eq!(default, PermissionMode::SkipPermissions);
+        assert_eq!(default.claude_args(), ["--dangerously-skip-permissions"]);
+
+        let safe = PermissionMode::resolve(false, true);
+        assert_eq!(safe, PermissionMode::Prompt);
+        assert!(safe.claude_args().is_empty());
+        assert!(safe.note().starts_with("Safe mode"));
+    }
+
+    #[test]
+    fn test_line_buffer_holds_partial_lines() {
+        let mut lines = LineBuffer::default();
+
+        assert!(lines.push(b"PASS src/a.te").is_empty());
+        assert_eq!(
+            lines.push(b"st.ts\nFAIL src/b"),
+            vec!["PASS src/a.test.ts\n"]
+        );
+        assert_eq!(
+            lines.push(b".test.ts\n\nTests: 1 failed"),
+            vec!["FAIL src/b.test.ts\n", "\n"]
+        );
+        assert_eq!(lines.flush().as_deref(), Some("Tests: 1 failed"));
+        assert_eq!(lines.flush(), None);
+    }
+
+    #[test]
+    fn test_line_buffer_keeps_split_utf8_intact() {
+        let mut lines = LineBuffer::default();
+        let text = "✓ ok\n".as_bytes();
+
+        assert!(lines.push(&text[..1]).is_empty());
+        assert_eq!(lines.push(&text[1..]), vec!["✓ ok\n"]);
+    }
+
+    #[test]
+    fn test_output_limit_stops_forwarding_past_cap() {
+        let output = "line 1\nline 2\nline 3\nline 4\n".repeat(100);
+        let limit = OutputLimit::new(20);
+        let mut forwarded = Vec::new();
+        let mut truncations = 0;
+        let mut seen = 0;
+
+        // Same admission flow stream_lines applies to each line
+        read_lines(output.as_bytes(), OutputStream::Stdout, |line| {
+            seen += 1;
+            match limit.admit(line.len()) {
+                OutputAdmission::Forward => forwarded.push(line.to_string()),
+                OutputAdmission::Truncate => truncations += 1,
+                OutputAdmission::Drop => {}
+            }
+        });
+
+        // Every line is still read (the pipe is drained), but only the first 20 bytes go out
+        assert_eq!(seen, 400);
+        assert_eq!(forwarded, vec!["line 1\n", "line 2\n"]);
+        assert_eq!(truncations, 1);
+        assert!(limit.is_truncated());
+        assert_eq!(
+            limit.complete_message("Process exited with code 0".to_string()),
+            "Process exited with code 0 (output truncated after 20 bytes)"
+        );
+
+        // Under the cap nothing changes
+        let limit = OutputLimit::new(DEFAULT_MAX_STREAMED_BYTES);
+        assert_eq!(limit.admit(output.len()), OutputAdmission::Forward);
+        assert!(!limit.is_truncated());
+        assert_eq!(limit.complete_message("done".to_string()), "done");
+    }
+
+    #[test]
+    fn test_render_prompt_template() {
+        assert_eq!(
+            render_prompt_template(
+                "Use pytest.\n\n{{spec}}\n\nNo new dependencies.",
+                "Add login"
+            ),
+            "Use pytest.\n\nAdd login\n\nNo new dependencies."
+        );
+        assert_eq!(
+            render_prompt_template("Use pytest.\n", "Add login"),
+            "Use pytest.\n\n## Specification\nAdd login"
+        );
+    }
+
+    #[test]
+    fn test_resolve_prompt_override_and_fallback() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+
+        // No override files: built-in prompts
+        assert_eq!(
+            resolve_prompt(root, "gen_tests", "spec", false),
+            build_prompt("gen_tests", "spec", false)
+        );
+
+        let prompts_dir = root.join(PROMPTS_DIR);
+        fs::create_dir_all(&prompts_dir).unwrap();
+        fs::write(prompts_dir.join("create_code.md"), "House rules.\n{{spec}}").unwrap();
+
+        assert_eq!(
+            resolve_prompt(root, "create_code", "Add login", false),
+            "House rules.\nAdd login"
+        );
+        // Other actions and plan-only previews keep the built-in text
+        assert_eq!(
+            resolve_prompt(root, "gen_tests", "spec", false),
+            build_prompt("gen_tests", "spec", false)
+        );
+        assert_eq!(
+            resolve_prompt(root, "create_code", "spec", true),
+            build_prompt("create_code", "spec", true)
+        );
+    }
+
+    #[test]
+    fn test_spawn_failure_kinds_and_hints() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let bin = temp_dir.path().join("tool");
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        let bin_path = bin.to_string_lossy().to_string();
+        let target = SpawnTarget {
+            binary: "tool",
+            binary_path: &bin_path,
+            cwd: temp_dir.path(),
+        };
+
+        let missing_dir = temp_dir.path().join("gone");
+        let moved = SpawnTarget {
+            cwd: &missing_dir,
+            ..target
+        };
+        assert_eq!(
+            moved.classify(Some(std::io::ErrorKind::NotFound)),
+            SpawnFailureKind::MissingWorkingDirectory
+        );
+        assert!(moved
+            .hint(SpawnFailureKind::MissingWorkingDirectory)
+            .unwrap()
+            .contains("Re-open the workspace"));
+
+        assert_eq!(
+            target.classify(Some(std::io::ErrorKind::PermissionDenied)),
+            SpawnFailureKind::PermissionDenied
+        );
+        assert!(target
+            .hint(SpawnFailureKind::PermissionDenied)
+            .unwrap()
+            .contains("chmod +x"));
+
+        let absent = SpawnTarget {
+            binary: "claude",
+            binary_path: "specstudio-no-such-binary",
+            cwd: temp_dir.path(),
+        };
+        assert_eq!(absent.preflight(), Some(SpawnFailureKind::BinaryNotFound));
+        assert_eq!(absent.classify(None), SpawnFailureKind::BinaryNotFound);
+        assert!(absent
+            .hint(SpawnFailureKind::BinaryNotFound)
+            .unwrap()
+            .contains(crate::deps::CLAUDE_INSTALL_URL));
+
+        assert_eq!(target.hint(SpawnFailureKind::Other), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawn_preflight_detects_non_executable_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let bin = temp_dir.path().join("tool");
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        let bin_path = bin.to_string_lossy().to_string();
+        let target = SpawnTarget {
+            binary: "tool",
+            binary_path: &bin_path,
+            cwd: temp_dir.path(),
+        };
+
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(target.preflight(), Some(SpawnFailureKind::PermissionDenied));
+
+        fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(target.preflight(), None);
+        assert_eq!(
+            target.classify(Some(std::io::ErrorKind::Other)),
+            SpawnFailureKind::Other
+        );
+    }
+
+    #[test]
+    fn test_cleanup_temp_files_removes_only_stale_prompts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let stale_time = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+        let write = |name: &str, stale: bool| {
+            let file = fs::File::create(temp_dir.path().join(name)).unwrap();
+            if stale {
+                file.set_modified(stale_time).unwrap();
+            }
+        };
+
+        write("specstudio_prompt_proc_1.txt", true);
+        write("specstudio_prompt_proc_2.txt", false);
+        write("other_tool_proc_3.txt", true);
+        write("specstudio_prompt_proc_4.log", true);
+
+        assert_eq!(
+            cleanup_temp_files_in(temp_dir.path(), STALE_TEMP_FILE_AGE),
+            1
+        );
+        let mut remaining: Vec<String> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            vec![
+                "other_tool_proc_3.txt",
+                "specstudio_prompt_proc_2.txt",
+                "specstudio_prompt_proc_4.log",
+            ]
+        );
+
+        // A zero threshold sweeps fresh prompt files too
+        assert_eq!(cleanup_temp_files_in(temp_dir.path(), Duration::ZERO), 1);
+        assert_eq!(
+            cleanup_temp_files_in(&temp_dir.path().join("missing"), Duration::ZERO),
+            0
+        );
+    }
+
+    #[test]
+    fn test_terminal_env_defaults_and_overrides() {
+        let vars = |action: &str, options: SpawnOptions| {
+            TerminalEnv::for_action(action, &options).unwrap().vars()
+        };
+        let pairs = |list: &[(&'static str, &str)]| -> Vec<(&'static str, String)> {
+            list.iter().map(|(k, v)| (*k, v.to_string())).collect()
+        };
+
+        // Defaults match the historical per-action behavior
+        assert_eq!(
+            vars("create_code", SpawnOptions::default()),
+            pairs(&[
+                ("FORCE_COLOR", "1"),
+                ("COLORTERM", "truecolor"),
+                ("TERM", "xterm-256color")
+            ])
+        );
+        assert_eq!(
+            vars("run_tests", SpawnOptions::default()),
+            pairs(&[("FORCE_COLOR", "0")])
+        );
+
+        let overridden = SpawnOptions {
+            color: Some(false),
+            term: Some("dumb".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            vars("gen_tests", overridden),
+            pairs(&[("FORCE_COLOR", "0"), ("TERM", "dumb")])
+        );
+        let colored = SpawnOptions {
+            color: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            vars("run_app", colored),
+            pairs(&[("FORCE_COLOR", "1"), ("COLORTERM", "truecolor")])
+        );
+
+        let invalid = SpawnOptions {
+            term: Some("xterm; rm -rf /".to_string()),
+            ..Default::default()
+        };
+        assert!(TerminalEnv::for_action("run_app", &invalid).is_err());
+    }
+
+    fn output_event(event_type: &str, data: &str) -> StreamEvent {
+        StreamEvent {
+            event_type: event_type.to_string(),
+            data: data.to_string(),
+            timestamp: 0,
+            process_id: Some("proc_1".to_string()),
+            stream: None,
+            hint: None,
+            stream_id: None,
+        }
+    }
+
+    #[test]
+    fn test_process_output_is_retrievable() {
+        let registry = ProcessRegistry::new();
+        assert!(registry.read_output("proc_1", 0).is_none());
+
+        for data in ["one\n", "two\n", "three\n"] {
+            registry.record_output("proc_1", &output_event("output", data));
+        }
+        let output = registry.read_output("proc_1", 0).unwrap();
+        let data: Vec<&str> = output.events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, vec!["one\n", "two\n", "three\n"]);
+        assert_eq!((output.first_seq, output.next_seq), (0, 3));
+        assert!(!output.dropped && !output.finished);
+
+        // Tail from a previous read
+        registry.record_output("proc_1", &output_event("complete", "done"));
+        let output = registry.read_output("proc_1", output.next_seq).unwrap();
+        assert_eq!(output.events.len(), 1);
+        assert_eq!(output.first_seq, 3);
+        assert!(output.finished);
+        assert!(registry
+            .read_output("proc_1", 10)
+            .unwrap()
+            .events
+            .is_empty());
+    }
+
+    #[test]
+    fn test_process_output_is_bounded() {
+        let registry = ProcessRegistry::new();
+        let chunk = "x".repeat(MAX_PROCESS_OUTPUT_BYTES / 4);
+        for _ in 0..10 {
+            registry.record_output("proc_1", &output_event("output", &chunk));
+        }
+
+        let output = registry.read_output("proc_1", 0).unwrap();
+        assert_eq!(output.events.len(), 4);
+        assert_eq!((output.first_seq, output.next_seq), (6, 10));
+        assert!(output.dropped);
+
+        // Only the most recent finished processes keep their output
+        for n in 0..=MAX_FINISHED_OUTPUTS {
+            let id = format!("done_{}", n);
+            registry.record_output(&id, &output_event("complete", "done"));
+        }
+        assert!(registry.read_output("done_0", 0).is_none());
+        assert!(registry.read_output("done_1", 0).is_some());
+        // Running processes are never evicted
+        assert!(registry.read_output("proc_1", 0).is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_initial_stdin_reaches_child() {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let stdin = child.stdin.take().unwrap();
+        thread::spawn(move || write_initial_stdin(stdin, "# Spec\nline two\n").unwrap());
+
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "# Spec\nline two\n"
+        );
+    }
+
+    /// PTY writer stand-in that records what was written
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_send_input_targets_process_id() {
+        let registry = ProcessRegistry::new();
+        assert!(registry.write_input(None, "y\n").is_err());
+
+        let (first, second) = (SharedWriter::default(), SharedWriter::default());
+        registry.register_pty("proc_1".to_string(), Box::new(first.clone()), None);
+        thread::sleep(Duration::from_millis(5));
+        registry.register_pty("proc_2".to_string(), Box::new(second.clone()), None);
+
+        assert_eq!(
+            registry.write_input(Some("proc_1"), "yes\n").unwrap(),
+            "proc_1"
+        );
+        // Without an id, input goes to the most recently started process
+        assert_eq!(registry.write_input(None, "no\n").unwrap(), "proc_2");
+        assert!(registry.write_input(Some("proc_9"), "x").is_err());
+
+        assert_eq!(first.0.lock().unwrap().as_slice(), b"yes\n");
+        assert_eq!(second.0.lock().unwrap().as_slice(), b"no\n");
+    }
+}
@@ -0,0 +1,128 @@
+// ============================================================================
+// System Integration Commands
+// Bridges workspace paths to native OS tools (file manager, browser, etc.)
+// ============================================================================
+
+use crate::error::AppError;
+use crate::workspace::resolve_workspace_path;
+use std::path::Path;
+
+// Only web links may be handed to the OS opener; file:, javascript: and custom
+// app schemes could run or expose local content
+const ALLOWED_URL_SCHEMES: &[&str] = &["http", "https"];
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Reveal a workspace file or folder in Finder/Explorer/the default file manager
+#[tauri::command]
+pub fn reveal_in_file_manager(
+    working_directory: String,
+    relative_path: String,
+) -> Result<(), AppError> {
+    let path = resolve_workspace_path(Path::new(&working_directory), &relative_path)?;
+    reveal(&path)
+}
+
+/// Open an http(s) URL (docs, install links) in the default browser
+#[tauri::command]
+pub fn open_url(url: String) -> Result<(), AppError> {
+    let url = validate_external_url(&url)?;
+    open::that_detached(url.as_str())
+        .map_err(|e| AppError::CommandFailed(format!("Failed to open browser: {}", e)))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Parse a URL and require an allowed scheme and a host
+fn validate_external_url(url: &str) -> Result<reqwest::Url, AppError> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| AppError::InvalidInput(format!("Invalid URL {:?}: {}", url, e)))?;
+
+    if !ALLOWED_URL_SCHEMES.contains(&parsed.scheme()) {
+        return Err(AppError::InvalidInput(format!(
+            "Refusing to open {}: URLs must be http or https",
+            parsed.scheme()
+        )));
+    }
+    if parsed.host_str().unwrap_or_default().is_empty() {
+        return Err(AppError::InvalidInput(format!("URL has no host: {}", url)));
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(target_os = "macos")]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    std::process::Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .spawn()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to open Finder: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    let mut select_arg = std::ffi::OsString::from("/select,");
+    select_arg.push(path);
+    std::process::Command::new("explorer")
+        .arg(select_arg)
+        .spawn()
+        .map_err(|e| AppError::CommandFailed(format!("Failed to open Explorer: {}", e)))?;
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    // xdg-open can't select a file, so open its containing folder instead
+    let target = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+    open::that_detached(target)
+        .map_err(|e| AppError::CommandFailed(format!("Failed to open file manager: {}", e)))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_external_url_allows_web_links() {
+        for url in [
+            "https://docs.anthropic.com/en/docs/claude-code",
+            "http://localhost:3000/",
+            "  HTTPS://Example.com/path?q=1  ",
+        ] {
+            assert!(validate_external_url(url).is_ok(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_validate_external_url_rejects_other_schemes() {
+        for url in [
+            "file:///etc/passwd",
+            "javascript:alert(1)",
+            "vscode://file/tmp/x",
+            "data:text/html,<script>alert(1)</script>",
+            "mailto:someone@example.com",
+            "not a url",
+            "",
+        ] {
+            assert!(
+                matches!(validate_external_url(url), Err(AppError::InvalidInput(_))),
+                "{}",
+                url
+            );
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// ============================================================================
+// Test Results
+// Best-effort parsing of test runner summaries (Jest, Vitest, cargo test)
+// out of streamed process output
+// ============================================================================
+
+use crate::project::TestFramework;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Payload of the `test_summary` stream event
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestSummary {
+    pub framework: String,
+    pub passed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+    /// Wall-clock duration in seconds, when the runner reports it
+    pub duration: Option<f64>,
+}
+
+/// Accumulates summary lines as they stream past
+#[derive(Debug)]
+pub struct TestSummaryParser {
+    framework: TestFramework,
+    summary: Option<TestSummary>,
+}
+
+impl TestSummaryParser {
+    pub fn new(framework: TestFramework) -> Self {
+        Self {
+            framework,
+            summary: None,
+        }
+    }
+
+    /// Feed one line of (ANSI-free) output
+    pub fn feed(&mut self, line: &str) {
+        match self.framework {
+            TestFramework::Jest => self.feed_jest(line.trim()),
+            TestFramework::Vitest => self.feed_vitest(line.trim()),
+            TestFramework::Cargo => self.feed_cargo(line.trim()),
+        }
+    }
+
+    /// Summary parsed so far, if any summary line was recognized
+    pub fn summary(&self) -> Option<TestSummary> {
+        self.summary.clone()
+    }
+
+    /// `Tests:       1 failed, 2 skipped, 10 passed, 13 total` then `Time:        2.3 s`
+    fn feed_jest(&mut self, line: &str) {
+        if let Some(counts) = line.strip_prefix("Tests:") {
+            // Watch mode prints a fresh summary per run; keep the latest
+            self.summary = Some(self.counts_summary(counts));
+        } else if let Some(time) = line.strip_prefix("Time:") {
+            self.set_duration(time);
+        }
+    }
+
+    /// `Tests  2 failed | 20 passed | 1 skipped (23)` then `Duration  1.23s (...)`
+    fn feed_vitest(&mut self, line: &str) {
+        if let Some(counts) = line.strip_prefix("Tests ") {
+            self.summary = Some(self.counts_summary(counts));
+        } else if let Some(time) = line.strip_prefix("Duration ") {
+            self.set_duration(time);
+        }
+    }
+
+    /// `test result: ok. 12 passed; 0 failed; 1 ignored; ... finished in 0.36s`
+    /// One line per test binary, so counts and durations accumulate
+    fn feed_cargo(&mut self, line: &str) {
+        let Some(rest) = line.strip_prefix("test result:") else {
+            return;
+        };
+        let parsed = self.counts_summary(rest);
+        let summary = self.summary.get_or_insert_with(|| TestSummary {
+            framework: parsed.framework.clone(),
+            ..Default::default()
+        });
+        summary.passed += parsed.passed;
+        summary.failed += parsed.failed;
+        summary.skipped += parsed.skipped;
+        if let Some(seconds) = rest.split("finished in").nth(1).and_then(parse_duration) {
+            summary.duration = Some(summary.duration.unwrap_or(0.0) + seconds);
+        }
+    }
+
+    fn counts_summary(&self, text: &str) -> TestSummary {
+        let (passed, failed, skipped) = parse_counts(text);
+        TestSummary {
+            framework: self.framework.name().to_string(),
+            passed,
+            failed,
+            skipped,
+            duration: None,
+        }
+    }
+
+    fn set_duration(&mut self, text: &str) {
+        if let Some(summary) = self.summary.as_mut() {
+            summary.duration = parse_duration(text);
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Extract `(passed, failed, skipped)` from `N passed`-style fragments
+/// Skipped also covers `ignored`, `todo` and `pending`
+fn parse_counts(text: &str) -> (u32, u32, u32) {
+    static COUNT: OnceLock<Regex> = OnceLock::new();
+    let pattern = COUNT.get_or_init(|| {
+        Regex::new(r"(\d+)\s+(passed|failed|skipped|ignored|todo|pending)\b").unwrap()
+    });
+
+    let mut counts = (0, 0, 0);
+    for caps in pattern.captures_iter(text) {
+        let n: u32 = caps[1].parse().unwrap_or(0);
+        match &caps[2] {
+            "passed" => counts.0 += n,
+            "failed" => counts.1 += n,
+            _ => counts.2 += n,
+        }
+    }
+    counts
+}
+
+/// First `1.23s` / `850 ms` / `2 m` duration in the text, in seconds
+fn parse_duration(text: &str) -> Option<f64> {
+    static DURATION: OnceLock<Regex> = OnceLock::new();
+    let pattern = DURATION.get_or_init(|| Regex::new(r"(\d+(?:\.\d+)?)\s*(ms|s|m)\b").unwrap());
+
+    let caps = pattern.captures(text)?;
+    let value: f64 = caps[1].parse().ok()?;
+    Some(match &caps[2] {
+        "ms" => value / 1000.0,
+        "m" => value * 60.0,
+        _ => value,
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(framework: TestFramework, output: &str) -> Option<TestSummary> {
+        let mut parser = TestSummaryParser::new(framework);
+        output.lines().for_each(|line| parser.feed(line));
+        parser.summary()
+    }
+
+    #[test]
+    fn test_parse_jest_summary() {
+        let output = "PASS src/a.test.ts\n\
+                      Test Suites: 1 failed, 1 passed, 2 total\n\
+                      Tests:       1 failed, 2 skipped, 10 passed, 13 total\n\
+                      Snapshots:   0 total\n\
+                      Time:        2.345 s, estimated 3 s\n";
+
+        let summary = parse(TestFramework::Jest, output).unwrap();
+        assert_eq!(summary.framework, "jest");
+        assert_eq!(
+            (summary.passed, summary.failed, summary.skipped),
+            (10, 1, 2)
+        );
+        assert_eq!(summary.duration, Some(2.345));
+    }
+
+    #[test]
+    fn test_parse_vitest_summary() {
+        let output = " Test Files  1 failed | 3 passed (4)\n\
+                      \x20     Tests  2 failed | 20 passed | 1 skipped (23)\n\
+                      \x20  Start at  10:00:00\n\
+                      \x20  Duration  850ms (transform 20ms, setup 0ms)\n";
+
+        let summary = parse(TestFramework::Vitest, output).unwrap();
+        assert_eq!(
+            (summary.passed, summary.failed, summary.skipped),
+            (20, 2, 1)
+        );
+        assert_eq!(summary.duration, Some(0.85));
+    }
+
+    #[test]
+    fn test_parse_cargo_summary_accumulates_binaries() {
+        let output = "running 3 tests\n\
+                      test result: ok. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.25s\n\
+                      running 2 tests\n\
+                      test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s\n";
+
+        let summary = parse(TestFramework::Cargo, output).unwrap();
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (4, 1, 1));
+        assert_eq!(summary.duration, Some(0.75));
+    }
+
+    #[test]
+    fn test_parse_without_summary() {
+        assert_eq!(
+            parse(TestFramework::Jest, "npm ERR! missing script: test\n"),
+            None
+        );
+        // Another runner's summary is not picked up
+        assert_eq!(
+            parse(TestFramework::Cargo, "Tests:  3 passed, 3 total\n"),
+            None
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// ============================================================================
+// File Watching
+// Watches individual workspace files so an open editor can notice external
+// changes (e.g. made by the claude process) and offer to reload
+// ============================================================================
+
+use crate::error::AppError;
+use crate::workspace::resolve_workspace_path;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Payload of `file:changed` events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChangedEvent {
+    pub working_directory: String,
+    /// Path relative to the workspace, as passed to watch_file
+    pub path: String,
+    /// "created", "modified" or "removed"
+    pub kind: String,
+}
+
+// ============================================================================
+// Watch Registry
+// ============================================================================
+
+/// Active file watchers, keyed by the watched file's canonical path
+/// Dropping a watcher stops it, so removing the entry is all unwatching takes
+pub struct FileWatchRegistry {
+    watchers: Mutex<HashMap<PathBuf, RecommendedWatcher>>,
+}
+
+impl FileWatchRegistry {
+    pub fn new() -> Self {
+        Self {
+            watchers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Watch `path`, replacing any existing watcher for it
+    /// The parent directory is watched so atomic saves (write + rename) are still seen
+    fn watch<F>(&self, path: PathBuf, on_change: F) -> Result<(), AppError>
+    where
+        F: Fn(&'static str) + Send + 'static,
+    {
+        let parent = path
+            .parent()
+            .ok_or_else(|| AppError::InvalidInput("Cannot watch the filesystem root".to_string()))?
+            .to_path_buf();
+        let target = path.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !event.paths.iter().any(|p| p == &target) {
+                return;
+            }
+            if let Some(kind) = change_kind(&event.kind) {
+                on_change(kind);
+            }
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to create file watcher: {}", e)))?;
+        watcher
+            .watch(&parent, RecursiveMode::NonRecursive)
+            .map_err(|e| AppError::Internal(format!("Failed to watch file: {}", e)))?;
+
+        self.watchers.lock().unwrap().insert(path, watcher);
+        Ok(())
+    }
+
+    /// Stop watching `path`; returns false if it wasn't watched
+    fn unwatch(&self, path: &Path) -> bool {
+        self.watchers.lock().unwrap().remove(path).is_some()
+    }
+}
+
+impl Default for FileWatchRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Watch a single workspace file and emit `file:changed` when it is modified,
+/// created or removed. Watching an already-watched file is a no-op replacement.
+#[tauri::command]
+pub fn watch_file(
+    app: AppHandle,
+    working_directory: String,
+    relative_path: String,
+) -> Result<(), AppError> {
+    let path = resolve_workspace_path(Path::new(&working_directory), &relative_path)?;
+    if path.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Path is a directory: {}",
+            relative_path
+        )));
+    }
+
+    let emitter = app.clone();
+    app.state::<FileWatchRegistry>().watch(path, move |kind| {
+        let event = FileChangedEvent {
+            working_directory: working_directory.clone(),
+            path: relative_path.clone(),
+            kind: kind.to_string(),
+        };
+        let _ = emitter.emit("file:changed", event);
+    })
+}
+
+/// Stop watching a file previously passed to watch_file
+/// Returns false if the file wasn't being watched
+#[tauri::command]
+pub fn unwatch_file(
+    app: AppHandle,
+    working_directory: String,
+    relative_path: String,
+) -> Result<bool, AppError> {
+    let path = watched_path(Path::new(&working_directory), &relative_path)?;
+    Ok(app.state::<FileWatchRegistry>().unwatch(&path))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Canonical path a file was registered under; works after the file has been deleted
+fn watched_path(cwd: &Path, relative_path: &str) -> Result<PathBuf, AppError> {
+    if let Ok(path) = resolve_workspace_path(cwd, relative_path) {
+        return Ok(path);
+    }
+    let root = cwd
+        .canonicalize()
+        .map_err(|e| AppError::io("Failed to resolve working directory", e))?;
+    Ok(root.join(relative_path))
+}
+
+/// Map a notify event to the reported change kind; access events are ignored
+fn change_kind(kind: &EventKind) -> Option<&'static str> {
+    match kind {
+        EventKind::Create(_) => Some("created"),
+        EventKind::Modify(_) => Some("modified"),
+        EventKind::Remove(_) => Some("removed"),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watch_reports_changes_until_unwatched() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("spec.md"), "# Spec").unwrap();
+        let path = resolve_workspace_path(temp_dir.path(), "spec.md").unwrap();
+
+        let registry = FileWatchRegistry::new();
+        let (tx, rx) = mpsc::channel();
+        registry
+            .watch(path.clone(), move |kind| {
+                let _ = tx.send(kind);
+            })
+            .unwrap();
+        assert!(registry.watchers.lock().unwrap().contains_key(&path));
+
+        fs::write(&path, "# Spec\n\nEdited externally").unwrap();
+        let kind = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(kind == "modified" || kind == "created", "{}", kind);
+
+        // Dropping the watcher disconnects the callback (and its sender); events
+        // already queued by the watcher thread may still arrive first
+        assert!(registry.unwatch(&path));
+        assert!(registry.watchers.lock().unwrap().is_empty());
+        assert!(!registry.unwatch(&path));
+        let disconnected = loop {
+            match rx.recv_timeout(Duration::from_secs(5)) {
+                Ok(_) => continue,
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(disconnected, mpsc::RecvTimeoutError::Disconnected);
+    }
+
+    #[test]
+    fn test_watched_path_stays_inside_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("spec.md"), "# Spec").unwrap();
+
+        assert!(resolve_workspace_path(temp_dir.path(), "../outside.md").is_err());
+        assert!(resolve_workspace_path(temp_dir.path(), "/etc/passwd").is_err());
+
+        // Deleted files can still be unwatched by their original path
+        let path = watched_path(temp_dir.path(), "spec.md").unwrap();
+        fs::remove_file(temp_dir.path().join("spec.md")).unwrap();
+        assert_eq!(watched_path(temp_dir.path(), "spec.md").unwrap(), path);
+    }
+
+    #[test]
+    fn test_change_kind_ignores_access() {
+        use notify::event::{AccessKind, CreateKind, ModifyKind, RemoveKind};
+        assert_eq!(
+            change_kind(&EventKind::Create(CreateKind::File)),
+            Some("created")
+        );
+        assert_eq!(
+            change_kind(&EventKind::Modify(ModifyKind::Any)),
+            Some("modified")
+        );
+        assert_eq!(
+            change_kind(&EventKind::Remove(RemoveKind::File)),
+            Some("removed")
+        );
+        assert_eq!(change_kind(&EventKind::Access(AccessKind::Any)), None);
+    }
+}
@@ -3,16 +3,50 @@
 // Handles file I/O for specs and workspace context
 // ============================================================================
 
+use crate::error::AppError;
+use crate::git;
+use crate::search::{registry_key, with_cancellation, SearchRegistry};
+use crate::settings::{keys, RESET_STORES, SETTINGS_STORE};
+use flate2::read::GzDecoder;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
-use tauri::AppHandle;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
 
 // ============================================================================
 // Constants
 // ============================================================================
 
 const SPECS_DIR: &str = ".specstudio/specs";
+// Archived-size specs may be stored gzipped; they are decompressed transparently on read
+const GZIPPED_SPEC_SUFFIX: &str = ".md.gz";
+// Archived specs live outside SPECS_DIR so list_specs never sees them
+const ARCHIVE_DIR: &str = ".specstudio/archive";
+// Persisted chat transcripts, optionally removed by factory_reset
+const CHATS_DIR: &str = ".specstudio/chats";
+// Bundles written by export_specs
+const EXPORTS_DIR: &str = ".specstudio/exports";
+// Cached architecture summary written by generate_project_summary
+const PROJECT_SUMMARY_FILE: &str = ".specstudio/summary.md";
+// Context file paths the cached summary was generated from, one per line
+const PROJECT_SUMMARY_FILES: &str = ".specstudio/summary.files";
+// Id space of read_workspace_context read ids in the SearchRegistry
+const CONTEXT_READ_ID_KIND: &str = "context";
+// Token factory_reset must receive to run
+const FACTORY_RESET_CONFIRMATION: &str = "RESET";
+// Per-project context exclusions (one entry per line, `#` for comments)
+const CONTEXT_IGNORE_FILE: &str = ".specstudio/context-ignore";
+// Gitignore-style patterns applied to AI context, search and the file tree, read from
+// every directory like .gitignore. Search and the file tree give it precedence over
+// .gitignore, so `!pattern` can re-include a gitignored path; context never reads .gitignore.
+pub const SPECSTUDIO_IGNORE_FILE: &str = ".specstudioignore";
 
 // Directories/files to exclude when reading workspace for AI context
 const EXCLUDED_DIRS: &[&str] = &[
@@ -84,6 +118,25 @@ const EXCLUDED_EXTENSIONS: &[&str] = &[
 const MAX_FILE_SIZE: u64 = 1024 * 1024;
 // Max total context size (5MB)
 const MAX_TOTAL_SIZE: usize = 5 * 1024 * 1024;
+// Minimum interval between context:progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+// Upper bound on file content kept by the context cache (across workspaces)
+const MAX_CONTEXT_CACHE_BYTES: usize = 32 * 1024 * 1024;
+// Settings key and cap for the recent workspaces (MRU) list
+const RECENT_WORKSPACES_KEY: &str = "recentWorkspaces";
+const MAX_RECENT_WORKSPACES: usize = 10;
+// Max file size for the general-purpose file viewer (10MB)
+const MAX_VIEW_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Unchanged lines shown around each hunk by diff_text
+const DEFAULT_DIFF_CONTEXT_LINES: usize = 3;
+/// Inserted between existing spec content and appended content
+const SPEC_APPEND_SEPARATOR: &str = "\n\n---\n\n";
+
+/// Serializes appends so concurrent streamed chunks never drop each other's writes
+static SPEC_APPEND_LOCK: Mutex<()> = Mutex::new(());
+// Directories the general file writer must never touch
+// (.specstudio is only written through the spec commands)
+const WRITE_PROTECTED_DIRS: &[&str] = &[".git", ".specstudio", "node_modules"];
 
 const FORBIDDEN_PATHS: &[&str] = &[
     "/etc", "/usr", "/bin", "/sbin", "/lib", "/lib64",
@@ -91,6 +144,26 @@ const FORBIDDEN_PATHS: &[&str] = &[
     "/root", "/snap",
 ];
 
+// Matched case-insensitively, with either path separator
+const WINDOWS_FORBIDDEN_PATHS: &[&str] = &[
+    "C:\\Windows",
+    "C:\\Program Files",
+    "C:\\Program Files (x86)",
+    "C:\\ProgramData",
+    "C:\\$Recycle.Bin",
+    "C:\\System Volume Information",
+];
+
+// Cloud-sync roots under the home directory; allowed, but selecting one triggers a warning
+const SYNC_ROOT_DIRS: &[&str] = &[
+    "Dropbox",
+    "OneDrive",
+    "Google Drive",
+    "iCloud Drive",
+    "Library/Mobile Documents",
+    "Library/CloudStorage",
+];
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -101,6 +174,104 @@ pub struct Spec {
     pub filename: String,
     pub title: String,
     pub created_at: String,
+    pub word_count: usize,
+    pub line_count: usize,
+    pub char_count: usize,
+    /// Monorepo subdirectory the spec targets (`sub_path` in frontmatter)
+    pub sub_path: Option<String>,
+}
+
+/// Spec metadata plus a summary of its companion `.plan.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecWithPlan {
+    pub spec: Spec,
+    pub has_plan: bool,
+    /// None when there is no plan or it could not be parsed
+    pub plan: Option<PlanSummary>,
+    /// Set when the plan file exists but is malformed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanValidationError {
+    /// Location in the plan, e.g. `phases[0].tickets[2].id` (empty for the whole document)
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanValidationResult {
+    pub filename: String,
+    pub valid: bool,
+    pub errors: Vec<PlanValidationError>,
+}
+
+/// One ticket's id before and after renumbering, in plan order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketIdChange {
+    pub phase_index: usize,
+    pub ticket_index: usize,
+    /// None when the ticket had no string id
+    pub old_id: Option<String>,
+    pub new_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenumberResult {
+    pub filename: String,
+    pub changes: Vec<TicketIdChange>,
+}
+
+/// A companion file of a spec that exists on disk
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecArtifact {
+    /// "plan", "chat" or "summary"
+    pub kind: String,
+    /// Path relative to the workspace
+    pub path: String,
+    pub size: u64,
+    /// Unix timestamp (milliseconds) of the last modification, if available
+    pub modified_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecArtifacts {
+    pub filename: String,
+    /// Only artifacts that exist; missing ones are omitted
+    pub artifacts: Vec<SpecArtifact>,
+}
+
+/// Fields of a plan needed for checks the JSON schema can't express
+#[derive(Deserialize)]
+struct PlanDocument {
+    phases: Vec<PlanPhase>,
+}
+
+#[derive(Deserialize)]
+struct PlanPhase {
+    /// Missing tickets are already reported by the schema check
+    tickets: Option<Vec<PlanTicket>>,
+}
+
+#[derive(Deserialize)]
+struct PlanTicket {
+    id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanSummary {
+    pub title: Option<String>,
+    pub phase_count: usize,
+    pub ticket_count: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -120,11 +291,93 @@ pub struct ValidateResult {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created: Option<bool>,
+    /// Non-blocking notice, e.g. when the home directory root was selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Result of check_workspace: validation without side effects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceCheck {
+    /// Usable as a workspace (an existing directory, or a path validate_workspace would create)
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub exists: bool,
+    pub is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveResult {
     pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+}
+
+/// Outcome of deleting one spec in delete_specs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecDeleteResult {
+    pub filename: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteSpecsResult {
+    pub deleted: usize,
+    pub failed: usize,
+    /// One entry per requested filename, in request order
+    pub results: Vec<SpecDeleteResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecImportResult {
+    /// Path of the markdown file within the source directory or archive
+    pub source: String,
+    /// Spec filename it was imported as
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Why the file was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSpecsResult {
+    pub imported: usize,
+    pub skipped: usize,
+    /// One entry per markdown file found in the source
+    pub results: Vec<SpecImportResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecDiff {
+    pub filename: String,
+    pub git_ref: String,
+    pub diff: String,
+    pub existed_at_ref: bool,
+    pub has_changes: bool,
+}
+
+/// In-memory unified diff of a proposed edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDiffResult {
+    pub diff: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub hunks: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,6 +394,10 @@ pub struct WorkspaceContext {
     pub total_files: usize,
     pub total_size: usize,
     pub truncated: bool,
+    /// Files whose content was reused from the context cache
+    pub cached_files: usize,
+    /// The read was cancelled via cancel_context_read; `files` holds what was read so far
+    pub cancelled: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,55 +407,452 @@ pub struct FileContent {
     pub content: String,
 }
 
-// ============================================================================
-// Tauri Commands
-// ============================================================================
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextProgress {
+    pub files_scanned: usize,
+    pub bytes_collected: usize,
+    pub done: bool,
+}
 
-#[tauri::command]
-pub fn validate_workspace(input_path: String) -> ValidateResult {
-    if input_path.is_empty() {
-        return ValidateResult {
-            valid: false,
-            error: Some("Path is required".to_string()),
-            path: None,
-            created: None,
-        };
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRange {
+    pub path: String,
+    /// 1-indexed, inclusive bounds after clamping; the range is empty when
+    /// `end_line` < `start_line`
+    pub start_line: usize,
+    pub end_line: usize,
+    pub total_lines: usize,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceFile {
+    pub path: String,
+    /// File contents; invalid UTF-8 sequences are replaced when `is_utf8` is false
+    pub content: String,
+    pub is_utf8: bool,
+    pub size: u64,
+    /// Unix timestamp (milliseconds) of the last modification, if available
+    pub modified_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FactoryResetSummary {
+    pub stores_cleared: Vec<String>,
+    /// Pass to restore_from_backup to undo the reset
+    pub backup_timestamp: String,
+    /// App data directory holding the `<store>.<timestamp>.bak` copies
+    pub backup_dir: String,
+    pub stores_backed_up: Vec<String>,
+    pub chat_files_removed: usize,
+    pub log_files_removed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecExport {
+    /// Absolute path of the written bundle
+    pub path: String,
+    /// "markdown" or "zip"
+    pub format: String,
+    pub spec_count: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteFileResult {
+    pub path: String,
+    pub bytes_written: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentWorkspace {
+    pub path: String,
+    pub name: String,
+    /// Unix timestamp (milliseconds) of when the workspace was last opened
+    pub last_opened: i64,
+}
+
+struct CachedFile {
+    modified: SystemTime,
+    size: u64,
+    content: String,
+    /// Value of the cache clock when the entry was last read or written
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct ContextCacheState {
+    files: HashMap<PathBuf, CachedFile>,
+    total_bytes: usize,
+    clock: u64,
+}
+
+impl ContextCacheState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
     }
 
-    let path = Path::new(&input_path);
+    fn remove(&mut self, path: &Path) {
+        if let Some(old) = self.files.remove(path) {
+            self.total_bytes -= old.content.len();
+        }
+    }
+}
 
-    if !path.is_absolute() {
-        return ValidateResult {
-            valid: false,
-            error: Some("Path must be absolute (e.g., /home/user/projects/my-app)".to_string()),
-            path: None,
-            created: None,
+/// File contents from previous context reads, keyed by path and validated by mtime + size
+/// The total is kept under MAX_CONTEXT_CACHE_BYTES by evicting least-recently-used files
+pub struct ContextCache {
+    state: Mutex<ContextCacheState>,
+    max_bytes: usize,
+}
+
+impl ContextCache {
+    pub fn new() -> Self {
+        Self::with_capacity(MAX_CONTEXT_CACHE_BYTES)
+    }
+
+    fn with_capacity(max_bytes: usize) -> Self {
+        Self {
+            state: Mutex::new(ContextCacheState::default()),
+            max_bytes,
+        }
+    }
+
+    fn get(&self, path: &Path, modified: SystemTime, size: u64) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let now = state.tick();
+        state
+            .files
+            .get_mut(path)
+            .filter(|cached| cached.modified == modified && cached.size == size)
+            .map(|cached| {
+                cached.last_used = now;
+                cached.content.clone()
+            })
+    }
+
+    fn insert(&self, path: PathBuf, modified: SystemTime, size: u64, content: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.remove(&path);
+        if content.len() > self.max_bytes {
+            return;
+        }
+        while state.total_bytes + content.len() > self.max_bytes {
+            let Some(oldest) = state
+                .files
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            state.remove(&oldest);
+        }
+        let last_used = state.tick();
+        state.total_bytes += content.len();
+        state.files.insert(
+            path,
+            CachedFile {
+                modified,
+                size,
+                content: content.to_string(),
+                last_used,
+            },
+        );
+    }
+
+    /// Drop entries under `root` that a complete walk of it didn't visit
+    /// (deleted, renamed or newly excluded files)
+    fn retain_seen(&self, root: &Path, seen: &HashSet<PathBuf>) {
+        let mut state = self.state.lock().unwrap();
+        let stale: Vec<PathBuf> = state
+            .files
+            .keys()
+            .filter(|path| path.starts_with(root) && !seen.contains(*path))
+            .cloned()
+            .collect();
+        for path in stale {
+            state.remove(&path);
+        }
+    }
+
+    /// Drop every cached file, returning how many were dropped
+    pub fn clear(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let count = state.files.len();
+        *state = ContextCacheState::default();
+        count
+    }
+}
+
+impl Default for ContextCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Symlink policy for context collection: links are skipped unless `follow_symlinks`
+/// is set, followed links must resolve inside the workspace root, and each real
+/// directory is walked once so link cycles terminate
+/// Also carries the context cache and counts the files served from it
+struct ContextWalk<'a> {
+    base: PathBuf,
+    root: PathBuf,
+    follow_symlinks: bool,
+    visited_dirs: HashSet<PathBuf>,
+    cache: Option<&'a ContextCache>,
+    cached_files: usize,
+    /// Files offered to the cache during this walk
+    seen_files: HashSet<PathBuf>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> ContextWalk<'a> {
+    fn new(
+        base: &Path,
+        follow_symlinks: bool,
+        cache: Option<&'a ContextCache>,
+        cancel: Option<&'a AtomicBool>,
+    ) -> Result<Self, AppError> {
+        let root = base
+            .canonicalize()
+            .map_err(|e| AppError::io("Failed to resolve working directory", e))?;
+        Ok(Self {
+            base: base.to_path_buf(),
+            root,
+            follow_symlinks,
+            visited_dirs: HashSet::new(),
+            cache,
+            cached_files: 0,
+            seen_files: HashSet::new(),
+            cancel,
+        })
+    }
+
+    fn is_cancelled(&self) -> bool {
+        crate::search::is_cancelled(self.cancel)
+    }
+
+    /// Read a file as UTF-8, reusing cached content when mtime and size are unchanged
+    fn read_file(&mut self, path: &Path, metadata: &fs::Metadata) -> Option<String> {
+        let Some(cache) = self.cache else {
+            return fs::read_to_string(path).ok();
         };
+        let modified = metadata.modified().ok()?;
+        self.seen_files.insert(path.to_path_buf());
+
+        if let Some(content) = cache.get(path, modified, metadata.len()) {
+            self.cached_files += 1;
+            return Some(content);
+        }
+
+        let content = fs::read_to_string(path).ok()?;
+        cache.insert(path.to_path_buf(), modified, metadata.len(), &content);
+        Some(content)
     }
 
-    let resolved_path = match path.canonicalize() {
-        Ok(p) => p,
-        Err(_) => PathBuf::from(&input_path),
-    };
+    /// Evict cache entries for files this walk no longer reaches
+    /// Skipped for cancelled walks, which only saw part of the tree
+    fn prune_cache(&self) {
+        if let Some(cache) = self.cache {
+            if !self.is_cancelled() {
+                cache.retain_seen(&self.base, &self.seen_files);
+            }
+        }
+    }
 
-    let resolved_str = resolved_path.to_string_lossy().to_string();
+    fn admits_symlink(&self, path: &Path) -> bool {
+        self.follow_symlinks
+            && path
+                .canonicalize()
+                .is_ok_and(|target| target.starts_with(&self.root))
+    }
+
+    /// Returns false if the directory was already walked (only tracked when following links)
+    fn enter_dir(&mut self, dir: &Path) -> bool {
+        if !self.follow_symlinks {
+            return true;
+        }
+        dir.canonicalize()
+            .is_ok_and(|real| self.visited_dirs.insert(real))
+    }
+}
+
+/// Directory and extension exclusions for context collection
+/// (built-ins merged with settings, `.specstudio/context-ignore` and `.specstudioignore`)
+struct ContextExclusions {
+    dirs: Vec<String>,
+    extensions: Vec<String>,
+    /// `.specstudioignore` matchers for the directories being walked, root first
+    ignores: Vec<Gitignore>,
+}
+
+impl ContextExclusions {
+    fn new(extra_dirs: &[String], extra_extensions: &[String]) -> Self {
+        let mut dirs: Vec<String> = EXCLUDED_DIRS.iter().map(|d| d.to_string()).collect();
+        let mut extensions: Vec<String> =
+            EXCLUDED_EXTENSIONS.iter().map(|e| e.to_string()).collect();
+
+        for dir in extra_dirs {
+            let dir = dir.trim().trim_matches('/');
+            if !dir.is_empty() && !dirs.iter().any(|d| d == dir) {
+                dirs.push(dir.to_string());
+            }
+        }
+        for ext in extra_extensions {
+            let ext = ext.trim().trim_start_matches('*').trim_start_matches('.');
+            let ext = ext.to_lowercase();
+            if !ext.is_empty() && !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+
+        Self {
+            dirs,
+            extensions,
+            ignores: Vec::new(),
+        }
+    }
+
+    /// Layer in gitignore-style patterns from the `.specstudioignore` in `dir`, if any
+    /// Returns whether a file was loaded, so the caller can pop it when leaving `dir`
+    fn push_ignore_file(&mut self, dir: &Path) -> bool {
+        let path = dir.join(SPECSTUDIO_IGNORE_FILE);
+        if !path.is_file() {
+            return false;
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        if builder.add(path).is_some() {
+            return false;
+        }
+        match builder.build() {
+            Ok(ignore) => {
+                self.ignores.push(ignore);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn pop_ignore_file(&mut self) {
+        self.ignores.pop();
+    }
+
+    /// Parse a context-ignore file: `*.ext` / `.ext` lines are extensions, anything else a directory
+    fn parse_ignore_file(content: &str) -> (Vec<String>, Vec<String>) {
+        let mut dirs = Vec::new();
+        let mut extensions = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("*.") {
+                extensions.push(line.to_string());
+            } else {
+                dirs.push(line.to_string());
+            }
+        }
+
+        (dirs, extensions)
+    }
+
+    fn is_excluded_dir(&self, name: &str) -> bool {
+        // .specstudio is always excluded, regardless of configuration
+        name == ".specstudio" || self.dirs.iter().any(|d| d == name)
+    }
+
+    fn is_excluded_file(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.extensions
+            .iter()
+            .any(|ext| name.ends_with(&format!(".{}", ext)))
+    }
+
+    /// The deepest `.specstudioignore` with a matching pattern decides, as with .gitignore
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.ignores
+            .iter()
+            .rev()
+            .map(|ignore| ignore.matched(path, is_dir))
+            .find(|m| !m.is_none())
+            .is_some_and(|m| m.is_ignore())
+    }
+}
+
+/// Throttles progress reports emitted while collecting context files
+struct ProgressReporter<'a> {
+    files_scanned: usize,
+    last_emit: Instant,
+    on_progress: &'a mut dyn FnMut(&ContextProgress),
+}
+
+impl<'a> ProgressReporter<'a> {
+    fn new(on_progress: &'a mut dyn FnMut(&ContextProgress)) -> Self {
+        Self {
+            files_scanned: 0,
+            last_emit: Instant::now(),
+            on_progress,
+        }
+    }
+
+    fn file_scanned(&mut self, bytes_collected: usize) {
+        self.files_scanned += 1;
+        if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            self.last_emit = Instant::now();
+            self.report(bytes_collected, false);
+        }
+    }
+
+    fn finish(&mut self, bytes_collected: usize) {
+        self.report(bytes_collected, true);
+    }
+
+    fn report(&mut self, bytes_collected: usize, done: bool) {
+        (self.on_progress)(&ContextProgress {
+            files_scanned: self.files_scanned,
+            bytes_collected,
+            done,
+        });
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
 
-    for forbidden in FORBIDDEN_PATHS {
-        if resolved_str == *forbidden || resolved_str.starts_with(&format!("{}/", forbidden)) {
+/// Validate a workspace path, creating the directory if it doesn't exist yet
+/// Use check_workspace to validate without creating anything
+#[tauri::command]
+pub fn validate_workspace(input_path: String) -> ValidateResult {
+    let (resolved_path, resolved_str) = match resolve_workspace_candidate(&input_path) {
+        Ok(resolved) => resolved,
+        Err(error) => {
             return ValidateResult {
                 valid: false,
-                error: Some("Cannot use system directories as workspace".to_string()),
+                error: Some(error),
                 path: None,
                 created: None,
-            };
+                warning: None,
+            }
         }
-    }
+    };
 
     if resolved_path.exists() {
         if resolved_path.is_dir() {
             return ValidateResult {
                 valid: true,
                 error: None,
+                warning: workspace_warning(&resolved_path, dirs::home_dir().as_deref()),
                 path: Some(resolved_str),
                 created: Some(false),
             };
@@ -208,6 +862,7 @@ pub fn validate_workspace(input_path: String) -> ValidateResult {
                 error: Some("Path exists but is not a directory".to_string()),
                 path: None,
                 created: None,
+                warning: None,
             };
         }
     }
@@ -218,24 +873,62 @@ pub fn validate_workspace(input_path: String) -> ValidateResult {
             error: None,
             path: Some(resolved_str),
             created: Some(true),
+            warning: None,
         },
         Err(e) => ValidateResult {
             valid: false,
             error: Some(format!("Failed to create directory: {}", e)),
             path: None,
             created: None,
+            warning: None,
         },
     }
 }
 
-/// Read workspace data (list of specs)
+/// Same validation as validate_workspace, but never creates the directory
+/// For previewing a path before the user confirms it
 #[tauri::command]
-pub fn read_workspace(working_directory: Option<String>) -> Result<WorkspaceData, String> {
-    let cwd = working_directory
-        .map(PathBuf::from)
-        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+pub fn check_workspace(input_path: String) -> WorkspaceCheck {
+    let (resolved_path, resolved_str) = match resolve_workspace_candidate(&input_path) {
+        Ok(resolved) => resolved,
+        Err(error) => {
+            return WorkspaceCheck {
+                valid: false,
+                error: Some(error),
+                path: None,
+                exists: false,
+                is_dir: false,
+                warning: None,
+            }
+        }
+    };
 
-    let specs = list_specs_internal(&cwd)?;
+    let exists = resolved_path.exists();
+    let is_dir = resolved_path.is_dir();
+    let valid = !exists || is_dir;
+    WorkspaceCheck {
+        valid,
+        error: (!valid).then(|| "Path exists but is not a directory".to_string()),
+        warning: valid
+            .then(|| workspace_warning(&resolved_path, dirs::home_dir().as_deref()))
+            .flatten(),
+        path: valid.then_some(resolved_str),
+        exists,
+        is_dir,
+    }
+}
+
+/// Read workspace data (list of specs)
+#[tauri::command]
+pub fn read_workspace(
+    app: AppHandle,
+    working_directory: Option<String>,
+) -> Result<WorkspaceData, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    let specs = list_specs_internal(&cwd, follow_symlinks_setting(&app))?;
 
     Ok(WorkspaceData {
         specs,
@@ -245,401 +938,4251 @@ pub fn read_workspace(working_directory: Option<String>) -> Result<WorkspaceData
 
 /// List all specs in .specstudio/specs/
 #[tauri::command]
-pub fn list_specs(working_directory: Option<String>) -> Result<Vec<Spec>, String> {
+pub fn list_specs(
+    app: AppHandle,
+    working_directory: Option<String>,
+) -> Result<Vec<Spec>, AppError> {
     let cwd = working_directory
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    list_specs_internal(&cwd)
+    list_specs_internal(&cwd, follow_symlinks_setting(&app))
 }
 
-/// Read a specific spec file
+/// List all specs along with summaries of their companion plans
+/// A malformed plan is flagged on its spec instead of failing the whole call
 #[tauri::command]
-pub fn read_spec(filename: String, working_directory: Option<String>) -> Result<SpecContent, String> {
+pub fn list_specs_with_plans(
+    app: AppHandle,
+    working_directory: Option<String>,
+) -> Result<Vec<SpecWithPlan>, AppError> {
     let cwd = working_directory
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
+    specs_with_plans(&cwd, follow_symlinks_setting(&app))
+}
+
+fn specs_with_plans(cwd: &Path, follow_symlinks: bool) -> Result<Vec<SpecWithPlan>, AppError> {
     let specs_dir = cwd.join(SPECS_DIR);
-    let spec_path = specs_dir.join(&filename);
+    let specs = list_specs_internal(cwd, follow_symlinks)?;
 
-    if !spec_path.exists() {
-        return Err(format!("Spec file not found: {}", filename));
+    Ok(specs
+        .into_iter()
+        .map(|spec| {
+            let plan_path = specs_dir.join(companion_plan_filename(&spec.filename));
+            let plan = plan_path.is_file().then(|| {
+                fs::read_to_string(&plan_path)
+                    .map_err(|e| format!("Failed to read plan file: {}", e))
+                    .and_then(|content| summarize_plan(&content))
+            });
+
+            match plan {
+                None => SpecWithPlan {
+                    spec,
+                    has_plan: false,
+                    plan: None,
+                    plan_error: None,
+                },
+                Some(Ok(summary)) => SpecWithPlan {
+                    spec,
+                    has_plan: true,
+                    plan: Some(summary),
+                    plan_error: None,
+                },
+                Some(Err(error)) => SpecWithPlan {
+                    spec,
+                    has_plan: true,
+                    plan: None,
+                    plan_error: Some(error),
+                },
+            }
+        })
+        .collect())
+}
+
+/// Lint a spec's companion `.plan.json` against the development plan schema
+/// Also reports empty phases and duplicate ticket ids
+#[tauri::command]
+pub fn validate_plan(
+    spec_filename: String,
+    working_directory: Option<String>,
+) -> Result<PlanValidationResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    let filename = companion_plan_filename(&spec_filename);
+    let plan_path = resolve_spec_path(&cwd.join(SPECS_DIR), &filename)?;
+    if !plan_path.is_file() {
+        return Err(AppError::NotFound(format!(
+            "Plan file not found: {}",
+            filename
+        )));
     }
 
-    let content = fs::read_to_string(&spec_path)
-        .map_err(|e| format!("Failed to read spec file: {}", e))?;
+    let content =
+        fs::read_to_string(&plan_path).map_err(|e| AppError::io("Failed to read plan file", e))?;
+    let errors = plan_validation_errors(&content);
 
-    Ok(SpecContent { filename, content })
+    Ok(PlanValidationResult {
+        filename,
+        valid: errors.is_empty(),
+        errors,
+    })
 }
 
-/// Save a spec file to .specstudio/specs/
+/// Give every ticket in a spec's plan a sequential, unique `<prefix>-NNN` id
+/// Rewrites the companion `.plan.json` in place; everything else is preserved
 #[tauri::command]
-pub fn save_spec(filename: String, content: String, working_directory: Option<String>) -> Result<SaveResult, String> {
+pub fn renumber_plan_tickets(
+    spec_filename: String,
+    working_directory: Option<String>,
+    prefix: String,
+) -> Result<RenumberResult, AppError> {
     let cwd = working_directory
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    let specs_dir = cwd.join(SPECS_DIR);
+    let prefix = prefix.trim().trim_end_matches('-');
+    if prefix.is_empty()
+        || !prefix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid ticket id prefix: {:?}",
+            prefix
+        )));
+    }
 
-    // Ensure the .specstudio/specs directory exists
-    if !specs_dir.exists() {
-        fs::create_dir_all(&specs_dir)
-            .map_err(|e| format!("Failed to create specs directory: {}", e))?;
+    let filename = companion_plan_filename(&spec_filename);
+    let plan_path = resolve_spec_path(&cwd.join(SPECS_DIR), &filename)?;
+    if !plan_path.is_file() {
+        return Err(AppError::NotFound(format!(
+            "Plan file not found: {}",
+            filename
+        )));
     }
 
-    let spec_path = specs_dir.join(&filename);
+    let content =
+        fs::read_to_string(&plan_path).map_err(|e| AppError::io("Failed to read plan file", e))?;
+    let mut plan: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidInput(format!("Invalid plan JSON: {}", e)))?;
 
-    fs::write(&spec_path, &content)
-        .map_err(|e| format!("Failed to save spec file: {}", e))?;
+    let changes = renumber_tickets(&mut plan, prefix)?;
 
-    Ok(SaveResult { success: true })
+    let content = serde_json::to_string_pretty(&plan)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize plan: {}", e)))?;
+    crate::fsutil::write_atomic(&plan_path, &content)?;
+
+    Ok(RenumberResult { filename, changes })
 }
 
-/// Delete a spec file
+/// Read a specific spec file
 #[tauri::command]
-pub fn delete_spec(filename: String, working_directory: Option<String>) -> Result<SaveResult, String> {
+pub fn read_spec(
+    filename: String,
+    working_directory: Option<String>,
+) -> Result<SpecContent, AppError> {
     let cwd = working_directory
         .map(PathBuf::from)
         .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
     let specs_dir = cwd.join(SPECS_DIR);
-    let spec_path = specs_dir.join(&filename);
+    let spec_path = resolve_spec_path(&specs_dir, &filename)?;
 
     if !spec_path.exists() {
-        return Err(format!("Spec file not found: {}", filename));
+        return Err(AppError::NotFound(format!(
+            "Spec file not found: {}",
+            filename
+        )));
     }
 
-    fs::remove_file(&spec_path)
-        .map_err(|e| format!("Failed to delete spec file: {}", e))?;
+    let content =
+        read_spec_file(&spec_path).map_err(|e| AppError::io("Failed to read spec file", e))?;
 
-    // Also delete companion plan file if it exists (prevent orphaned plans)
-    let plan_filename = filename.replace(".md", ".plan.json");
-    let plan_path = specs_dir.join(&plan_filename);
-    if plan_path.exists() {
-        let _ = fs::remove_file(&plan_path); // Ignore errors if plan doesn't exist
-        println!("[delete_spec] Cleaned up companion plan file: {}", plan_filename);
+    Ok(SpecContent { filename, content })
+}
+
+/// List a spec's companion files: its `.plan.json`, its chat transcript
+/// (`.specstudio/chats/<spec>.json`) and the workspace's project summary
+#[tauri::command]
+pub fn spec_artifacts(
+    spec_filename: String,
+    working_directory: Option<String>,
+) -> Result<SpecArtifacts, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    let specs_dir = cwd.join(SPECS_DIR);
+    if !resolve_spec_path(&specs_dir, &spec_filename)?.exists() {
+        return Err(AppError::NotFound(format!(
+            "Spec file not found: {}",
+            spec_filename
+        )));
     }
 
-    Ok(SaveResult { success: true })
+    let candidates = [
+        (
+            "plan",
+            format!("{}/{}", SPECS_DIR, companion_plan_filename(&spec_filename)),
+        ),
+        (
+            "chat",
+            format!("{}/{}.json", CHATS_DIR, spec_stem(&spec_filename)),
+        ),
+        ("summary", PROJECT_SUMMARY_FILE.to_string()),
+    ];
+    let artifacts = candidates
+        .into_iter()
+        .filter_map(|(kind, path)| {
+            let metadata = fs::metadata(cwd.join(&path)).ok().filter(|m| m.is_file())?;
+            let modified_at = metadata.modified().ok().map(|t| {
+                let datetime: chrono::DateTime<chrono::Utc> = t.into();
+                datetime.timestamp_millis()
+            });
+            Some(SpecArtifact {
+                kind: kind.to_string(),
+                path,
+                size: metadata.len(),
+                modified_at,
+            })
+        })
+        .collect();
+
+    Ok(SpecArtifacts {
+        filename: spec_filename,
+        artifacts,
+    })
 }
 
-/// Factory reset - clear all stores and return success
-/// Frontend should clear localStorage and relaunch the app
+/// Save a spec file to .specstudio/specs/
+/// When `normalize` is true, filenames not matching YYYYMMDD-name.md are
+/// rewritten from the spec's first heading (or the given filename)
 #[tauri::command]
-pub fn factory_reset(app: AppHandle) -> Result<(), String> {
-    use tauri_plugin_store::StoreExt;
+pub fn save_spec(
+    filename: String,
+    content: String,
+    working_directory: Option<String>,
+    normalize: Option<bool>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    println!("[factory_reset] Starting factory reset...");
+    // Nested specs keep their subdirectory; only the file name itself is normalized
+    let (parent, base_name) = match filename.rsplit_once('/') {
+        Some((parent, base_name)) => (Some(parent), base_name),
+        None => (None, filename.as_str()),
+    };
 
-    // Clear settings.json store - ignore errors if store doesn't exist
-    println!("[factory_reset] Attempting to clear settings store...");
-    match app.store("settings.json") {
-        Ok(settings_store) => {
-            println!("[factory_reset] Settings store opened, clearing...");
-            settings_store.clear();
-            if let Err(e) = settings_store.save() {
-                eprintln!("[factory_reset] Warning: Failed to save settings store: {}", e);
-                // Continue anyway - we're resetting
-            } else {
-                println!("[factory_reset] Settings store cleared successfully");
-            }
-        }
-        Err(e) => {
-            eprintln!("[factory_reset] Note: Could not open settings store (may not exist): {}", e);
-            // Continue - store might not exist yet
+    let filename = if normalize.unwrap_or(false) && !is_valid_spec_filename_internal(base_name) {
+        let title = extract_first_heading(&content)
+            .unwrap_or_else(|| base_name.trim_end_matches(".md").to_string());
+        let normalized = normalize_spec_filename(title);
+        match parent {
+            Some(parent) => format!("{}/{}", parent, normalized),
+            None => normalized,
         }
+    } else {
+        filename.clone()
+    };
+
+    let specs_dir = cwd.join(SPECS_DIR);
+
+    // Ensure the .specstudio/specs directory exists
+    if !specs_dir.exists() {
+        fs::create_dir_all(&specs_dir)
+            .map_err(|e| AppError::io("Failed to create specs directory", e))?;
     }
 
-    // Clear auth.json store - ignore errors if store doesn't exist
-    println!("[factory_reset] Attempting to clear auth store...");
-    match app.store("auth.json") {
-        Ok(auth_store) => {
-            println!("[factory_reset] Auth store opened, clearing...");
-            auth_store.clear();
-            if let Err(e) = auth_store.save() {
-                eprintln!("[factory_reset] Warning: Failed to save auth store: {}", e);
-                // Continue anyway - we're resetting
-            } else {
-                println!("[factory_reset] Auth store cleared successfully");
-            }
-        }
-        Err(e) => {
-            eprintln!("[factory_reset] Note: Could not open auth store (may not exist): {}", e);
-            // Continue - store might not exist yet
-        }
+    let spec_path = resolve_spec_path(&specs_dir, &filename)?;
+
+    if let Some(spec_parent) = spec_path.parent() {
+        fs::create_dir_all(spec_parent)
+            .map_err(|e| AppError::io("Failed to create spec directory", e))?;
     }
 
-    println!("[factory_reset] Factory reset completed");
-    Ok(())
+    fs::write(&spec_path, &content).map_err(|e| AppError::io("Failed to save spec file", e))?;
+
+    Ok(SaveResult {
+        success: true,
+        filename: Some(filename),
+    })
 }
 
-/// Read workspace files for AI context (with exclusions)
+/// Append markdown to a spec (after a `---` separator), creating the spec if missing
+/// The file is replaced atomically, so readers never see a partial append
 #[tauri::command]
-pub fn read_workspace_context(working_directory: String) -> Result<WorkspaceContext, String> {
-    let cwd = PathBuf::from(&working_directory);
+pub fn append_to_spec(
+    spec_filename: String,
+    content: String,
+    working_directory: Option<String>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    if !cwd.exists() || !cwd.is_dir() {
-        return Err("Working directory does not exist".to_string());
+    if spec_filename.ends_with(GZIPPED_SPEC_SUFFIX) {
+        return Err(AppError::InvalidInput(format!(
+            "Cannot append to a compressed spec: {}",
+            spec_filename
+        )));
     }
 
-    let mut files: Vec<FileContent> = Vec::new();
-    let mut total_size: usize = 0;
-    let mut truncated = false;
+    let spec_path = resolve_spec_path(&cwd.join(SPECS_DIR), &spec_filename)?;
+    if let Some(parent) = spec_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create spec directory", e))?;
+    }
 
-    collect_files(&cwd, &cwd, &mut files, &mut total_size, &mut truncated)?;
+    let _guard = SPEC_APPEND_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let existing = match fs::read_to_string(&spec_path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(AppError::io("Failed to read spec file", e)),
+    };
 
-    let total_files = files.len();
+    let updated = if existing.trim().is_empty() {
+        content
+    } else {
+        format!(
+            "{}{}{}",
+            existing.trim_end(),
+            SPEC_APPEND_SEPARATOR,
+            content
+        )
+    };
+    crate::fsutil::write_atomic(&spec_path, &updated)?;
 
-    Ok(WorkspaceContext {
-        files,
-        total_files,
-        total_size,
-        truncated,
+    Ok(SaveResult {
+        success: true,
+        filename: Some(spec_filename),
     })
 }
 
-fn collect_files(
-    base: &Path,
-    dir: &Path,
-    files: &mut Vec<FileContent>,
-    total_size: &mut usize,
-    truncated: &mut bool,
-) -> Result<(), String> {
-    if *total_size >= MAX_TOTAL_SIZE {
-        *truncated = true;
-        return Ok(());
-    }
+/// Combine several specs into a new spec, one `##` section per source
+/// Source headings are shifted below their section; `archive_sources` moves the sources to the archive
+#[tauri::command]
+pub fn merge_specs(
+    source_filenames: Vec<String>,
+    target_filename: String,
+    working_directory: Option<String>,
+    archive_sources: Option<bool>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-    let entries = fs::read_dir(dir)
-        .map_err(|e| format!("Failed to read directory: {}", e))?;
+    if source_filenames.len() < 2 {
+        return Err(AppError::InvalidInput(
+            "At least two specs are needed to merge".to_string(),
+        ));
+    }
+    if !target_filename.ends_with(".md") {
+        return Err(AppError::InvalidInput(format!(
+            "Merged spec must be a .md file: {}",
+            target_filename
+        )));
+    }
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        let file_name = path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("");
+    let specs_dir = cwd.join(SPECS_DIR);
+    let target_path = resolve_spec_path(&specs_dir, &target_filename)?;
+    if target_path.exists() {
+        return Err(AppError::InvalidInput(format!(
+            "A spec named {} already exists",
+            target_filename
+        )));
+    }
 
-        // Skip excluded directories
-        if path.is_dir() {
-            if EXCLUDED_DIRS.contains(&file_name) {
-                continue;
-            }
-            // Recurse into subdirectory
-            collect_files(base, &path, files, total_size, truncated)?;
-            continue;
+    // Everything is validated before the target is written, so a failed archive move
+    // can't leave a merged spec next to its unarchived sources
+    let archive_dir = cwd.join(ARCHIVE_DIR);
+    let archive = archive_sources.unwrap_or(false);
+    let mut seen_paths = HashSet::new();
+    let mut sources = Vec::with_capacity(source_filenames.len());
+    for filename in &source_filenames {
+        let path = resolve_spec_path(&specs_dir, filename)?;
+        if !seen_paths.insert(path.clone()) {
+            return Err(AppError::InvalidInput(format!(
+                "Spec listed more than once: {}",
+                filename
+            )));
         }
-
-        // Skip non-files
-        if !path.is_file() {
-            continue;
+        if archive && resolve_spec_path(&archive_dir, filename)?.exists() {
+            return Err(AppError::InvalidInput(format!(
+                "A spec named {} already exists in {}",
+                filename, ARCHIVE_DIR
+            )));
         }
-
-        // Skip by extension
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            if EXCLUDED_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
-                continue;
-            }
+        if !path.exists() {
+            return Err(AppError::NotFound(format!(
+                "Spec file not found: {}",
+                filename
+            )));
         }
+        let content =
+            read_spec_file(&path).map_err(|e| AppError::io("Failed to read spec file", e))?;
+        let base_name = filename.rsplit('/').next().unwrap_or(filename);
+        let title =
+            extract_first_heading(&content).unwrap_or_else(|| spec_stem(base_name).to_string());
+        sources.push((title, content));
+    }
 
-        // Skip files that are too large
-        let metadata = match fs::metadata(&path) {
-            Ok(m) => m,
-            Err(_) => continue,
-        };
-
-        if metadata.len() > MAX_FILE_SIZE {
-            continue;
-        }
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create spec directory", e))?;
+    }
+    fs::write(&target_path, merge_spec_contents(&sources))
+        .map_err(|e| AppError::io("Failed to save spec file", e))?;
 
-        // Check if adding this file would exceed total limit
-        if *total_size + metadata.len() as usize > MAX_TOTAL_SIZE {
-            *truncated = true;
-            continue;
+    if archive {
+        for filename in &source_filenames {
+            move_spec(&specs_dir, &archive_dir, filename)?;
         }
-
-        // Read file content
-        let content = match fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue, // Skip binary files that can't be read as UTF-8
-        };
-
-        // Get relative path
-        let relative_path = path.strip_prefix(base)
-            .unwrap_or(&path)
-            .to_string_lossy()
-            .to_string();
-
-        *total_size += content.len();
-        files.push(FileContent {
-            path: relative_path,
-            content,
-        });
     }
 
-    Ok(())
+    Ok(SaveResult {
+        success: true,
+        filename: Some(target_filename),
+    })
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+/// Diff a spec against its version at a git ref (unified diff, old -> current)
+#[tauri::command]
+pub fn diff_spec(
+    spec_filename: String,
+    git_ref: String,
+    working_directory: Option<String>,
+) -> Result<SpecDiff, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-fn list_specs_internal(cwd: &Path) -> Result<Vec<Spec>, String> {
     let specs_dir = cwd.join(SPECS_DIR);
+    let spec_path = resolve_spec_path(&specs_dir, &spec_filename)?;
 
-    if !specs_dir.exists() {
-        fs::create_dir_all(&specs_dir)
-            .map_err(|e| format!("Failed to create specs directory: {}", e))?;
-        return Ok(Vec::new());
+    if !spec_path.exists() {
+        return Err(AppError::NotFound(format!(
+            "Spec file not found: {}",
+            spec_filename
+        )));
     }
 
-    let entries = fs::read_dir(&specs_dir)
-        .map_err(|e| format!("Failed to read specs directory: {}", e))?;
-
-    let mut specs: Vec<Spec> = Vec::new();
-
-    for entry in entries.flatten() {
-        let path = entry.path();
+    let current =
+        read_spec_file(&spec_path).map_err(|e| AppError::io("Failed to read spec file", e))?;
 
-        if path.extension().map_or(false, |ext| ext == "md") {
-            let filename = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("")
-                .to_string();
+    // "./" makes git resolve the path relative to cwd rather than the repo root
+    let relative_path = format!("./{}/{}", SPECS_DIR, spec_filename);
+    let previous = git::show_file_bytes_at_ref(&cwd, &git_ref, &relative_path)?
+        .map(|bytes| decode_spec_bytes(&bytes))
+        .transpose()
+        .map_err(|e| AppError::io("Failed to decompress spec at ref", e))?;
+    let existed_at_ref = previous.is_some();
+    let previous = previous.unwrap_or_default();
 
-            if let Ok(content) = fs::read_to_string(&path) {
-                let title = extract_first_heading(&content)
-                    .unwrap_or_else(|| filename.trim_end_matches(".md").to_string());
-
-                // Extract date from filename if present (YYYYMMDD-name.md format)
-                let created_at = extract_date_from_filename(&filename)
-                    .unwrap_or_else(|| {
-                        // Fallback to file modification time
-                        path.metadata()
-                            .and_then(|m| m.modified())
-                            .ok()
-                            .map(|t| {
-                                let datetime: chrono::DateTime<chrono::Utc> = t.into();
-                                datetime.format("%Y-%m-%d").to_string()
-                            })
-                            .unwrap_or_else(|| "Unknown".to_string())
-                    });
-
-                specs.push(Spec {
-                    filename,
-                    title,
-                    created_at,
-                });
-            }
-        }
-    }
+    let old_label = if existed_at_ref {
+        format!("a/{}", spec_filename)
+    } else {
+        "/dev/null".to_string()
+    };
+    let diff = similar::TextDiff::from_lines(&previous, &current)
+        .unified_diff()
+        .header(&old_label, &format!("b/{}", spec_filename))
+        .to_string();
 
-    // Sort by filename (which includes date prefix) descending
-    specs.sort_by(|a, b| b.filename.cmp(&a.filename));
-    Ok(specs)
+    Ok(SpecDiff {
+        filename: spec_filename,
+        git_ref,
+        has_changes: previous != current,
+        diff,
+        existed_at_ref,
+    })
 }
 
-fn extract_first_heading(content: &str) -> Option<String> {
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with("# ") {
-            return Some(trimmed[2..].trim().to_string());
+/// Unified diff between two texts (line-based), without touching disk or git
+#[tauri::command]
+pub fn diff_text(
+    original: String,
+    modified: String,
+    context_lines: Option<usize>,
+) -> TextDiffResult {
+    let diff = similar::TextDiff::from_lines(&original, &modified);
+
+    let (mut insertions, mut deletions) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            similar::ChangeTag::Insert => insertions += 1,
+            similar::ChangeTag::Delete => deletions += 1,
+            similar::ChangeTag::Equal => {}
         }
     }
-    None
-}
 
-fn extract_date_from_filename(filename: &str) -> Option<String> {
-    // Expected format: YYYYMMDD-feature-name.md
-    if filename.len() >= 8 {
-        let date_part = &filename[..8];
-        if date_part.chars().all(|c| c.is_ascii_digit()) {
-            // Format as YYYY-MM-DD
-            return Some(format!(
-                "{}-{}-{}",
-                &date_part[..4],
-                &date_part[4..6],
-                &date_part[6..8]
-            ));
-        }
+    let mut unified = diff.unified_diff();
+    unified
+        .context_radius(context_lines.unwrap_or(DEFAULT_DIFF_CONTEXT_LINES))
+        .header("a", "b");
+
+    TextDiffResult {
+        hunks: unified.iter_hunks().count(),
+        diff: unified.to_string(),
+        insertions,
+        deletions,
     }
-    None
 }
 
-// ============================================================================
-// Tests
-// ============================================================================
+/// Delete a spec file
+#[tauri::command]
+pub fn delete_spec(
+    filename: String,
+    working_directory: Option<String>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    delete_spec_files(&cwd.join(SPECS_DIR), &filename)?;
 
-    #[test]
-    fn test_extract_first_heading() {
-        let content = "# My Title\n\nSome content";
-        assert_eq!(extract_first_heading(content), Some("My Title".to_string()));
+    Ok(SaveResult {
+        success: true,
+        filename: None,
+    })
+}
 
-        let content_no_heading = "Just some text\nNo heading here";
-        assert_eq!(extract_first_heading(content_no_heading), None);
+/// Delete several specs (and their companion plans) in one call
+/// Each file is validated and deleted independently; failures are reported, not rolled back
+#[tauri::command]
+pub fn delete_specs(
+    filenames: Vec<String>,
+    working_directory: Option<String>,
+) -> DeleteSpecsResult {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let specs_dir = cwd.join(SPECS_DIR);
 
-        let content_with_spaces = "#    Spaced Title   \n";
-        assert_eq!(extract_first_heading(content_with_spaces), Some("Spaced Title".to_string()));
+    let results: Vec<SpecDeleteResult> = filenames
+        .into_iter()
+        .map(|filename| {
+            let error = delete_spec_files(&specs_dir, &filename)
+                .err()
+                .map(|e| e.to_string());
+            SpecDeleteResult {
+                filename,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect();
 
-        let content_h2 = "## Second Level\n";
-        assert_eq!(extract_first_heading(content_h2), None);
+    let deleted = results.iter().filter(|r| r.success).count();
+    DeleteSpecsResult {
+        deleted,
+        failed: results.len() - deleted,
+        results,
     }
+}
 
-    #[test]
-    fn test_extract_date_from_filename() {
-        assert_eq!(
-            extract_date_from_filename("20260131-feature.md"),
-            Some("2026-01-31".to_string())
-        );
+/// Move a spec (and its companion plan) into `.specstudio/archive/`
+#[tauri::command]
+pub fn archive_spec(
+    spec_filename: String,
+    working_directory: Option<String>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-        assert_eq!(
-            extract_date_from_filename("20251225-christmas.md"),
-            Some("2025-12-25".to_string())
-        );
+    move_spec(&cwd.join(SPECS_DIR), &cwd.join(ARCHIVE_DIR), &spec_filename)?;
 
-        assert_eq!(extract_date_from_filename("feature.md"), None);
+    Ok(SaveResult {
+        success: true,
+        filename: Some(spec_filename),
+    })
+}
 
-        assert_eq!(extract_date_from_filename("202601-incomplete.md"), None);
+/// List archived specs (same shape as list_specs)
+#[tauri::command]
+pub fn list_archived_specs(
+    app: AppHandle,
+    working_directory: Option<String>,
+) -> Result<Vec<Spec>, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    list_archived_specs_internal(&cwd, follow_symlinks_setting(&app))
+}
+
+/// Move an archived spec (and its companion plan) back into the specs directory
+#[tauri::command]
+pub fn unarchive_spec(
+    spec_filename: String,
+    working_directory: Option<String>,
+) -> Result<SaveResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    move_spec(&cwd.join(ARCHIVE_DIR), &cwd.join(SPECS_DIR), &spec_filename)?;
+
+    Ok(SaveResult {
+        success: true,
+        filename: Some(spec_filename),
+    })
+}
+
+/// Bundle all specs into `.specstudio/exports/` for sharing or backup
+/// `format` is "markdown" (one document with a table of contents) or "zip" (spec files
+/// plus their companion plans). Archived specs are only included with `include_archived`.
+#[tauri::command]
+pub fn export_specs(
+    app: AppHandle,
+    working_directory: Option<String>,
+    format: String,
+    include_archived: Option<bool>,
+) -> Result<SpecExport, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+
+    export_specs_internal(
+        &cwd,
+        &format,
+        include_archived.unwrap_or(false),
+        follow_symlinks_setting(&app),
+        &timestamp,
+    )
+}
+
+/// Copy `.md` files from a directory (searched recursively) or a `.zip` archive into
+/// the specs directory. Names that don't follow YYYYMMDD-name.md get today's date
+/// prefix, and a numeric suffix is added instead of overwriting an existing spec.
+#[tauri::command]
+pub fn import_specs(
+    app: AppHandle,
+    working_directory: Option<String>,
+    source: String,
+) -> Result<ImportSpecsResult, AppError> {
+    let cwd = working_directory
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    let date = chrono::Local::now().format("%Y%m%d").to_string();
+
+    import_specs_internal(
+        &cwd,
+        Path::new(&source),
+        follow_symlinks_setting(&app),
+        &date,
+    )
+}
+
+/// Build a date-prefixed spec filename (YYYYMMDD-slug.md) from a title
+#[tauri::command]
+pub fn normalize_spec_filename(title: String) -> String {
+    let date = chrono::Local::now().format("%Y%m%d").to_string();
+    format!("{}-{}.md", date, slugify(&title))
+}
+
+/// Check that a filename follows the YYYYMMDD-name.md convention
+#[tauri::command]
+pub fn is_valid_spec_filename(filename: String) -> bool {
+    is_valid_spec_filename_internal(&filename)
+}
+
+/// Factory reset - clear all stores and return a summary of what was cleared
+/// Requires `confirmation_token` to equal FACTORY_RESET_CONFIRMATION so it can't fire by accident
+/// Stores are first copied to timestamped `.bak` files; restore_from_backup undoes the reset
+/// Optionally deletes the workspace's `.specstudio/chats` and the backend log files
+/// Frontend should clear localStorage and relaunch the app
+#[tauri::command]
+pub fn factory_reset(
+    app: AppHandle,
+    confirmation_token: String,
+    working_directory: Option<String>,
+    clear_chats: Option<bool>,
+    clear_logs: Option<bool>,
+) -> Result<FactoryResetSummary, AppError> {
+    if confirmation_token != FACTORY_RESET_CONFIRMATION {
+        return Err(AppError::InvalidInput(
+            "Factory reset requires a confirmation token".to_string(),
+        ));
+    }
+
+    log::info!("[factory_reset] Starting factory reset...");
+
+    // Flush pending writes so the backups match what the app currently holds
+    for store_name in RESET_STORES {
+        if let Ok(store) = app.store(*store_name) {
+            let _ = store.save();
+        }
+    }
+    let data_dir = app_data_dir(&app)?;
+    let backup_timestamp = chrono::Utc::now().timestamp_millis().to_string();
+    let stores_backed_up = crate::settings::backup_stores(&data_dir, &backup_timestamp)?;
+    log::info!(
+        "[factory_reset] Backed up {:?} to {} (timestamp {})",
+        stores_backed_up,
+        data_dir.display(),
+        backup_timestamp
+    );
+
+    let mut summary = FactoryResetSummary {
+        stores_cleared: Vec::new(),
+        backup_timestamp,
+        backup_dir: data_dir.to_string_lossy().to_string(),
+        stores_backed_up,
+        chat_files_removed: 0,
+        log_files_removed: 0,
+    };
+
+    // settings.json holds preferences and the Gemini key, auth.json the OAuth credentials.
+    // Missing stores are skipped - we're resetting anyway
+    for store_name in RESET_STORES.iter().copied() {
+        match app.store(store_name) {
+            Ok(store) => {
+                store.clear();
+                if let Err(e) = store.save() {
+                    log::warn!("[factory_reset] Failed to save {}: {}", store_name, e);
+                } else {
+                    log::info!("[factory_reset] Cleared {}", store_name);
+                    summary.stores_cleared.push(store_name.to_string());
+                }
+            }
+            Err(e) => {
+                log::info!(
+                    "[factory_reset] Could not open {} (may not exist): {}",
+                    store_name,
+                    e
+                );
+            }
+        }
+    }
+
+    if clear_chats.unwrap_or(false) {
+        if let Some(cwd) = working_directory.map(PathBuf::from) {
+            let chats_dir = cwd.join(CHATS_DIR);
+            summary.chat_files_removed = clear_directory(&chats_dir)?;
+            if chats_dir.exists() {
+                fs::remove_dir(&chats_dir)
+                    .map_err(|e| AppError::io("Failed to remove chats directory", e))?;
+            }
+            log::info!(
+                "[factory_reset] Removed {} chat files",
+                summary.chat_files_removed
+            );
+        }
+    }
+
+    if clear_logs.unwrap_or(false) {
+        if let Some(log_dir) = crate::logs::log_directory() {
+            summary.log_files_removed = clear_directory(&log_dir)?;
+        }
+        crate::logs::clear_buffer();
+        log::info!(
+            "[factory_reset] Removed {} log files",
+            summary.log_files_removed
+        );
+    }
+
+    log::info!("[factory_reset] Factory reset completed");
+    Ok(summary)
+}
+
+/// Undo a factory reset by restoring the store backups it took
+/// `timestamp` is the `backupTimestamp` returned by factory_reset; returns the restored stores
+#[tauri::command]
+pub fn restore_from_backup(app: AppHandle, timestamp: String) -> Result<Vec<String>, AppError> {
+    let data_dir = app_data_dir(&app)?;
+    let restored = crate::settings::restore_stores(&data_dir, &timestamp)?;
+
+    // Open stores still hold the cleared contents in memory
+    for store_name in &restored {
+        if let Ok(store) = app.store(store_name) {
+            if let Err(e) = store.reload() {
+                log::warn!(
+                    "[restore_from_backup] Failed to reload {}: {}",
+                    store_name,
+                    e
+                );
+            }
+        }
+    }
+    crate::settings::apply_http_timeout(&app);
+
+    log::info!(
+        "[restore_from_backup] Restored {:?} from {}",
+        restored,
+        timestamp
+    );
+    Ok(restored)
+}
+
+/// Get recently opened workspaces, most recent first
+/// Entries whose directories no longer exist are pruned from the store
+#[tauri::command]
+pub fn get_recent_workspaces(app: AppHandle) -> Result<Vec<RecentWorkspace>, AppError> {
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| AppError::Internal(format!("Failed to open settings store: {}", e)))?;
+
+    let stored = read_recent_workspaces(store.get(RECENT_WORKSPACES_KEY));
+    let recent = prune_missing_workspaces(stored.clone());
+
+    if recent != stored {
+        store.set(RECENT_WORKSPACES_KEY, serde_json::to_value(&recent)?);
+        store
+            .save()
+            .map_err(|e| AppError::Internal(format!("Failed to save settings store: {}", e)))?;
+    }
+
+    Ok(recent)
+}
+
+/// Record a workspace as the most recently opened one
+/// Returns the updated list
+#[tauri::command]
+pub fn add_recent_workspace(
+    app: AppHandle,
+    path: String,
+) -> Result<Vec<RecentWorkspace>, AppError> {
+    let path = validate_recent_workspace_path(&path)?;
+
+    let store = app
+        .store(SETTINGS_STORE)
+        .map_err(|e| AppError::Internal(format!("Failed to open settings store: {}", e)))?;
+
+    let stored = read_recent_workspaces(store.get(RECENT_WORKSPACES_KEY));
+    let recent = push_recent_workspace(
+        prune_missing_workspaces(stored),
+        &path,
+        chrono::Utc::now().timestamp_millis(),
+    );
+
+    store.set(RECENT_WORKSPACES_KEY, serde_json::to_value(&recent)?);
+    store
+        .save()
+        .map_err(|e| AppError::Internal(format!("Failed to save settings store: {}", e)))?;
+
+    Ok(recent)
+}
+
+/// Read any file inside the workspace for the file viewer
+/// Non-UTF-8 files are returned lossily with `is_utf8: false`
+#[tauri::command]
+pub fn read_workspace_file(
+    working_directory: String,
+    relative_path: String,
+) -> Result<WorkspaceFile, AppError> {
+    read_workspace_file_internal(Path::new(&working_directory), &relative_path)
+}
+
+/// Read lines `start_line..=end_line` (1-indexed) of a workspace file
+/// Out-of-range bounds are clamped to the file instead of failing
+#[tauri::command]
+pub fn read_file_range(
+    working_directory: String,
+    relative_path: String,
+    start_line: usize,
+    end_line: usize,
+) -> Result<FileRange, AppError> {
+    let file = read_workspace_file_internal(Path::new(&working_directory), &relative_path)?;
+    Ok(slice_lines(file.path, &file.content, start_line, end_line))
+}
+
+/// Write any file inside the workspace, creating parent directories
+/// Written atomically (temp file + rename) so readers never see partial content
+#[tauri::command]
+pub fn write_workspace_file(
+    working_directory: String,
+    relative_path: String,
+    content: String,
+) -> Result<WriteFileResult, AppError> {
+    write_workspace_file_internal(Path::new(&working_directory), &relative_path, &content)
+}
+
+/// Canonical absolute path of an existing workspace entry, e.g. for tooltips or
+/// handing to external tools. Fails if the path (or a symlink in it) leads outside
+/// the workspace.
+#[tauri::command]
+pub fn resolve_path(working_directory: String, relative_path: String) -> Result<String, AppError> {
+    let path = resolve_workspace_path(Path::new(&working_directory), &relative_path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Drop cached file contents so the next context read re-reads every file
+/// Returns the number of files evicted
+#[tauri::command]
+pub fn invalidate_context_cache(app: AppHandle) -> usize {
+    app.state::<ContextCache>().clear()
+}
+
+/// Read workspace files for AI context (with exclusions)
+/// Emits throttled `context:progress` events while scanning
+/// Symlinks are skipped unless the `followSymlinks` setting is enabled
+/// Unchanged files are served from the context cache (see `cachedFiles`)
+/// `line_numbers` prefixes every line with its number so the AI can cite locations
+/// (costs extra tokens; size limits apply to the raw content)
+/// Pass a `read_id` to allow stopping the walk via `cancel_context_read`
+/// Runs off the main thread so the cancel can arrive while the walk is in flight
+#[tauri::command(async)]
+pub fn read_workspace_context(
+    app: AppHandle,
+    working_directory: String,
+    line_numbers: Option<bool>,
+    read_id: Option<String>,
+) -> Result<WorkspaceContext, AppError> {
+    let cwd = PathBuf::from(&working_directory);
+
+    let (mut extra_dirs, mut extra_extensions) = (Vec::new(), Vec::new());
+    if let Ok(store) = app.store(SETTINGS_STORE) {
+        extra_dirs = get_string_list(store.get(keys::EXTRA_EXCLUDED_DIRS));
+        extra_extensions = get_string_list(store.get(keys::EXTRA_EXCLUDED_EXTENSIONS));
+    }
+    let follow_symlinks = follow_symlinks_setting(&app);
+
+    // Context reads share the search registry (in their own id space), so abort_all
+    // stops them too
+    let mut context = with_cancellation(&app, CONTEXT_READ_ID_KIND, read_id, |cancel| {
+        read_workspace_context_internal(
+            &cwd,
+            &extra_dirs,
+            &extra_extensions,
+            follow_symlinks,
+            Some(&app.state::<ContextCache>()),
+            cancel,
+            &mut |progress| {
+                let _ = app.emit("context:progress", progress);
+            },
+        )
+    })?;
+
+    if line_numbers.unwrap_or(false) {
+        for file in &mut context.files {
+            file.content = number_lines(&file.content);
+        }
+    }
+    Ok(context)
+}
+
+/// Request cancellation of an in-progress read_workspace_context
+/// Returns false if no read with this id is running
+#[tauri::command(async)]
+pub fn cancel_context_read(app: AppHandle, read_id: String) -> bool {
+    app.state::<SearchRegistry>()
+        .cancel(&registry_key(CONTEXT_READ_ID_KIND, &read_id))
+}
+
+fn read_workspace_file_internal(
+    cwd: &Path,
+    relative_path: &str,
+) -> Result<WorkspaceFile, AppError> {
+    let path = resolve_workspace_path(cwd, relative_path)?;
+
+    let metadata =
+        fs::metadata(&path).map_err(|e| AppError::io("Failed to read file metadata", e))?;
+    if !metadata.is_file() {
+        return Err(AppError::InvalidInput(format!(
+            "Not a file: {}",
+            relative_path
+        )));
+    }
+    if metadata.len() > MAX_VIEW_FILE_SIZE {
+        return Err(AppError::InvalidInput(format!(
+            "File is too large to view ({} bytes): {}",
+            metadata.len(),
+            relative_path
+        )));
+    }
+
+    let bytes = fs::read(&path).map_err(|e| AppError::io("Failed to read file", e))?;
+    let (content, is_utf8) = match String::from_utf8(bytes) {
+        Ok(content) => (content, true),
+        Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), false),
+    };
+
+    let modified_at = metadata.modified().ok().map(|t| {
+        let datetime: chrono::DateTime<chrono::Utc> = t.into();
+        datetime.timestamp_millis()
+    });
+
+    Ok(WorkspaceFile {
+        path: relative_path.to_string(),
+        content,
+        is_utf8,
+        size: metadata.len(),
+        modified_at,
+    })
+}
+
+fn slice_lines(path: String, content: &str, start_line: usize, end_line: usize) -> FileRange {
+    let lines: Vec<&str> = content.lines().collect();
+    let total_lines = lines.len();
+    let start_line = start_line.clamp(1, total_lines + 1);
+    let end_line = end_line.min(total_lines).max(start_line - 1);
+
+    FileRange {
+        path,
+        start_line,
+        end_line,
+        total_lines,
+        content: lines[start_line - 1..end_line].join("\n"),
+    }
+}
+
+fn write_workspace_file_internal(
+    cwd: &Path,
+    relative_path: &str,
+    content: &str,
+) -> Result<WriteFileResult, AppError> {
+    let path = resolve_writable_workspace_path(cwd, relative_path)?;
+    if path.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Path is a directory: {}",
+            relative_path
+        )));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create directories", e))?;
+    }
+    crate::fsutil::write_atomic(&path, content)?;
+
+    Ok(WriteFileResult {
+        path: relative_path.to_string(),
+        bytes_written: content.len(),
+    })
+}
+
+fn read_workspace_context_internal(
+    cwd: &Path,
+    extra_dirs: &[String],
+    extra_extensions: &[String],
+    follow_symlinks: bool,
+    cache: Option<&ContextCache>,
+    cancel: Option<&AtomicBool>,
+    on_progress: &mut dyn FnMut(&ContextProgress),
+) -> Result<WorkspaceContext, AppError> {
+    if !cwd.exists() || !cwd.is_dir() {
+        return Err(AppError::NotFound(
+            "Working directory does not exist".to_string(),
+        ));
+    }
+
+    let mut extra_dirs = extra_dirs.to_vec();
+    let mut extra_extensions = extra_extensions.to_vec();
+    if let Ok(content) = fs::read_to_string(cwd.join(CONTEXT_IGNORE_FILE)) {
+        let (dirs, extensions) = ContextExclusions::parse_ignore_file(&content);
+        extra_dirs.extend(dirs);
+        extra_extensions.extend(extensions);
+    }
+    let mut exclusions = ContextExclusions::new(&extra_dirs, &extra_extensions);
+    exclusions.push_ignore_file(cwd);
+
+    let mut files: Vec<FileContent> = Vec::new();
+    let mut total_size: usize = 0;
+    let mut truncated = false;
+    let mut progress = ProgressReporter::new(on_progress);
+    let mut walk = ContextWalk::new(cwd, follow_symlinks, cache, cancel)?;
+
+    collect_files(
+        &mut walk,
+        cwd,
+        &mut files,
+        &mut total_size,
+        &mut truncated,
+        &mut progress,
+        &mut exclusions,
+    )?;
+    progress.finish(total_size);
+    walk.prune_cache();
+
+    let total_files = files.len();
+
+    Ok(WorkspaceContext {
+        files,
+        total_files,
+        total_size,
+        truncated,
+        cached_files: walk.cached_files,
+        cancelled: walk.is_cancelled(),
+    })
+}
+
+fn collect_files(
+    walk: &mut ContextWalk,
+    dir: &Path,
+    files: &mut Vec<FileContent>,
+    total_size: &mut usize,
+    truncated: &mut bool,
+    progress: &mut ProgressReporter,
+    exclusions: &mut ContextExclusions,
+) -> Result<(), AppError> {
+    if *total_size >= MAX_TOTAL_SIZE {
+        *truncated = true;
+        return Ok(());
+    }
+    if !walk.enter_dir(dir) {
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| AppError::io("Failed to read directory", e))?;
+
+    for entry in entries.flatten() {
+        // Checked per entry so a cancelled read returns promptly with partial results
+        if walk.is_cancelled() {
+            return Ok(());
+        }
+        let path = entry.path();
+        let file_name = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
+
+        let is_symlink = entry.file_type().is_ok_and(|t| t.is_symlink());
+        if is_symlink && !walk.admits_symlink(&path) {
+            continue;
+        }
+
+        // Skip excluded directories
+        if path.is_dir() {
+            if exclusions.is_excluded_dir(file_name) || exclusions.is_ignored(&path, true) {
+                continue;
+            }
+            // Recurse into subdirectory, with its own .specstudioignore layered on top
+            let layered = exclusions.push_ignore_file(&path);
+            collect_files(
+                walk, &path, files, total_size, truncated, progress, exclusions,
+            )?;
+            if layered {
+                exclusions.pop_ignore_file();
+            }
+            continue;
+        }
+
+        // Skip non-files
+        if !path.is_file() {
+            continue;
+        }
+        progress.file_scanned(*total_size);
+
+        // Skip by extension or .specstudioignore pattern
+        if exclusions.is_excluded_file(file_name) || exclusions.is_ignored(&path, false) {
+            continue;
+        }
+
+        // Skip files that are too large
+        let metadata = match fs::metadata(&path) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        if metadata.len() > MAX_FILE_SIZE {
+            continue;
+        }
+
+        // Check if adding this file would exceed total limit
+        if *total_size + metadata.len() as usize > MAX_TOTAL_SIZE {
+            *truncated = true;
+            continue;
+        }
+
+        // Read file content
+        let content = match walk.read_file(&path, &metadata) {
+            Some(c) => c,
+            None => continue, // Skip binary files that can't be read as UTF-8
+        };
+
+        // Get relative path
+        let relative_path = path
+            .strip_prefix(&walk.base)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+
+        *total_size += content.len();
+        files.push(FileContent {
+            path: relative_path,
+            content,
+        });
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Cached project summary and when it was written, if one exists
+pub(crate) fn read_project_summary(cwd: &Path) -> Option<(String, SystemTime)> {
+    let path = cwd.join(PROJECT_SUMMARY_FILE);
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let content = fs::read_to_string(&path).ok()?;
+    Some((content, modified))
+}
+
+/// Write the summary along with the list of context files it was generated from
+pub(crate) fn write_project_summary(
+    cwd: &Path,
+    content: &str,
+    files: &[FileContent],
+) -> Result<(), AppError> {
+    let path = cwd.join(PROJECT_SUMMARY_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create directories", e))?;
+    }
+    crate::fsutil::write_atomic(&cwd.join(PROJECT_SUMMARY_FILES), &summary_file_list(files))?;
+    crate::fsutil::write_atomic(&path, content)
+}
+
+/// A summary is stale once the set of context files differs from the one it was
+/// generated from, or any context file was modified after it was written
+/// Files that can no longer be read count as changed
+pub(crate) fn is_summary_stale(
+    cwd: &Path,
+    summary_modified: SystemTime,
+    files: &[FileContent],
+) -> bool {
+    let recorded = fs::read_to_string(cwd.join(PROJECT_SUMMARY_FILES)).ok();
+    if recorded.as_deref() != Some(summary_file_list(files).as_str()) {
+        return true;
+    }
+    files.iter().any(|file| {
+        fs::metadata(cwd.join(&file.path))
+            .and_then(|m| m.modified())
+            .map_or(true, |modified| modified > summary_modified)
+    })
+}
+
+/// Sorted context file paths, one per line
+fn summary_file_list(files: &[FileContent]) -> String {
+    let mut paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    paths.sort_unstable();
+    paths.iter().map(|path| format!("{}\n", path)).collect()
+}
+
+/// The `followSymlinks` setting (off when unset or unreadable)
+fn follow_symlinks_setting(app: &AppHandle) -> bool {
+    app.store(SETTINGS_STORE)
+        .ok()
+        .and_then(|store| store.get(keys::FOLLOW_SYMLINKS))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::Internal(format!("Failed to resolve app data directory: {}", e)))
+}
+
+/// Prefix each line with its 1-indexed number, right-aligned to the widest number
+/// Line endings are preserved, so a trailing empty line is numbered too
+fn number_lines(content: &str) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let width = lines.len().to_string().len();
+
+    let mut numbered = String::with_capacity(content.len() + lines.len() * (width + 3));
+    for (index, line) in lines.iter().enumerate() {
+        numbered.push_str(&format!("{:>width$} | {}", index + 1, line, width = width));
+    }
+    numbered
+}
+
+/// Read a string array setting, ignoring non-string entries
+fn get_string_list(value: Option<serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+/// Parse the stored recent workspaces list, dropping malformed entries
+fn read_recent_workspaces(value: Option<serde_json::Value>) -> Vec<RecentWorkspace> {
+    value
+        .and_then(|v| v.as_array().cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| serde_json::from_value(v).ok())
+        .collect()
+}
+
+/// Require an absolute path to an existing directory, normalizing trailing separators
+fn validate_recent_workspace_path(path: &str) -> Result<PathBuf, AppError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::InvalidInput("Path is required".to_string()));
+    }
+
+    let path = PathBuf::from(trimmed);
+    if !path.is_absolute() {
+        return Err(AppError::InvalidInput(format!(
+            "Workspace path must be absolute: {}",
+            trimmed
+        )));
+    }
+    if !path.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Workspace path is not a directory: {}",
+            trimmed
+        )));
+    }
+
+    Ok(path.components().collect())
+}
+
+/// Move `path` to the front of the list, de-duplicating and capping its length
+fn push_recent_workspace(
+    mut recent: Vec<RecentWorkspace>,
+    path: &Path,
+    now: i64,
+) -> Vec<RecentWorkspace> {
+    let path_str = path.to_string_lossy().to_string();
+    recent.retain(|w| w.path != path_str);
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.clone());
+
+    recent.insert(
+        0,
+        RecentWorkspace {
+            path: path_str,
+            name,
+            last_opened: now,
+        },
+    );
+    recent.truncate(MAX_RECENT_WORKSPACES);
+    recent
+}
+
+/// Drop entries whose directories no longer exist
+fn prune_missing_workspaces(recent: Vec<RecentWorkspace>) -> Vec<RecentWorkspace> {
+    recent
+        .into_iter()
+        .filter(|w| Path::new(&w.path).is_dir())
+        .collect()
+}
+
+fn list_specs_internal(cwd: &Path, follow_symlinks: bool) -> Result<Vec<Spec>, AppError> {
+    let specs_dir = cwd.join(SPECS_DIR);
+
+    if !specs_dir.exists() {
+        fs::create_dir_all(&specs_dir)
+            .map_err(|e| AppError::io("Failed to create specs directory", e))?;
+        return Ok(Vec::new());
+    }
+
+    read_specs_in(&specs_dir, follow_symlinks)
+}
+
+fn list_archived_specs_internal(cwd: &Path, follow_symlinks: bool) -> Result<Vec<Spec>, AppError> {
+    let archive_dir = cwd.join(ARCHIVE_DIR);
+    if !archive_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    read_specs_in(&archive_dir, follow_symlinks)
+}
+
+/// Read spec metadata for every markdown file under `specs_dir`
+fn read_specs_in(specs_dir: &Path, follow_symlinks: bool) -> Result<Vec<Spec>, AppError> {
+    let mut spec_paths: Vec<PathBuf> = Vec::new();
+    collect_spec_paths(specs_dir, follow_symlinks, &mut spec_paths)?;
+
+    let mut specs: Vec<Spec> = Vec::new();
+
+    for path in spec_paths {
+        // Nested specs are identified by their path relative to the specs dir
+        let filename = path
+            .strip_prefix(specs_dir)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let base_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        if let Ok(content) = read_spec_file(&path) {
+            let title = extract_first_heading(&content)
+                .unwrap_or_else(|| spec_stem(&base_name).to_string());
+
+            // Extract date from filename if present (YYYYMMDD-name.md format)
+            let created_at = extract_date_from_filename(&base_name).unwrap_or_else(|| {
+                // Fallback to file modification time
+                path.metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .map(|t| {
+                        let datetime: chrono::DateTime<chrono::Utc> = t.into();
+                        datetime.format("%Y-%m-%d").to_string()
+                    })
+                    .unwrap_or_else(|| "Unknown".to_string())
+            });
+
+            specs.push(Spec {
+                filename,
+                title,
+                created_at,
+                word_count: content.split_whitespace().count(),
+                line_count: content.lines().count(),
+                char_count: content.chars().count(),
+                sub_path: extract_frontmatter_value(&content, "sub_path"),
+            });
+        }
+    }
+
+    // Newest first by base filename (which carries the date prefix), so specs in
+    // subfolders interleave with top-level ones; the full path breaks ties
+    let base_name = |spec: &Spec| -> String {
+        spec.filename
+            .rsplit('/')
+            .next()
+            .unwrap_or(&spec.filename)
+            .to_string()
+    };
+    specs.sort_by(|a, b| {
+        base_name(b)
+            .cmp(&base_name(a))
+            .then_with(|| b.filename.cmp(&a.filename))
+    });
+    Ok(specs)
+}
+
+/// Assign `<prefix>-001`, `<prefix>-002`, ... across all phases in plan order
+fn renumber_tickets(
+    plan: &mut serde_json::Value,
+    prefix: &str,
+) -> Result<Vec<TicketIdChange>, AppError> {
+    let invalid = |what: &str| AppError::InvalidInput(format!("Plan is malformed: {}", what));
+
+    let phases = plan
+        .get_mut("phases")
+        .and_then(|p| p.as_array_mut())
+        .ok_or_else(|| invalid("missing phases"))?;
+
+    let mut tickets = Vec::new();
+    for (phase_index, phase) in phases.iter_mut().enumerate() {
+        let phase_tickets = phase
+            .get_mut("tickets")
+            .and_then(|t| t.as_array_mut())
+            .ok_or_else(|| invalid(&format!("phases[{}] has no tickets array", phase_index)))?;
+        for (ticket_index, ticket) in phase_tickets.iter_mut().enumerate() {
+            let ticket = ticket.as_object_mut().ok_or_else(|| {
+                invalid(&format!(
+                    "phases[{}].tickets[{}] is not an object",
+                    phase_index, ticket_index
+                ))
+            })?;
+            tickets.push((phase_index, ticket_index, ticket));
+        }
+    }
+
+    // Pad to at least three digits, wider for very large plans
+    let width = tickets.len().to_string().len().max(3);
+    let changes = tickets
+        .into_iter()
+        .enumerate()
+        .map(|(n, (phase_index, ticket_index, ticket))| {
+            let new_id = format!("{}-{:0width$}", prefix, n + 1, width = width);
+            let old_id = ticket
+                .insert("id".to_string(), serde_json::Value::String(new_id.clone()))
+                .and_then(|id| id.as_str().map(String::from));
+            TicketIdChange {
+                phase_index,
+                ticket_index,
+                old_id,
+                new_id,
+            }
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+/// Schema violations plus semantic problems in a plan document
+fn plan_validation_errors(content: &str) -> Vec<PlanValidationError> {
+    let plan: serde_json::Value = match serde_json::from_str(content) {
+        Ok(plan) => plan,
+        Err(e) => {
+            return vec![PlanValidationError {
+                path: String::new(),
+                message: format!("Invalid JSON: {}", e),
+            }]
+        }
+    };
+
+    let mut errors = Vec::new();
+    check_against_schema(
+        &plan,
+        &crate::gemini::get_development_plan_schema(),
+        "",
+        &mut errors,
+    );
+
+    // Shapes the schema already rejected can't be decoded; those errors suffice
+    let Ok(document) = serde_json::from_value::<PlanDocument>(plan) else {
+        return errors;
+    };
+    let mut error =
+        |path: String, message: String| errors.push(PlanValidationError { path, message });
+
+    if document.phases.is_empty() {
+        error("phases".to_string(), "Plan has no phases".to_string());
+    }
+    let mut seen_ids: HashMap<String, String> = HashMap::new();
+    for (phase_index, phase) in document.phases.iter().enumerate() {
+        let tickets = phase.tickets.as_deref().unwrap_or_default();
+        if phase.tickets.as_ref().is_some_and(|t| t.is_empty()) {
+            error(
+                format!("phases[{}].tickets", phase_index),
+                "Phase has no tickets".to_string(),
+            );
+        }
+        for (ticket_index, ticket) in tickets.iter().enumerate() {
+            let Some(id) = ticket.id.as_deref() else {
+                continue;
+            };
+            let path = format!("phases[{}].tickets[{}].id", phase_index, ticket_index);
+            match seen_ids.get(id) {
+                Some(first) => error(
+                    path,
+                    format!("Duplicate ticket id {:?} (first used at {})", id, first),
+                ),
+                None => {
+                    seen_ids.insert(id.to_string(), path);
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Check types and required fields against the subset of JSON Schema the plan schema uses
+fn check_against_schema(
+    value: &serde_json::Value,
+    schema: &serde_json::Value,
+    path: &str,
+    errors: &mut Vec<PlanValidationError>,
+) {
+    let expected = schema["type"].as_str().unwrap_or_default();
+    let type_matches = match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        _ => true,
+    };
+    if !type_matches {
+        errors.push(PlanValidationError {
+            path: path.to_string(),
+            message: format!("Expected {}", expected),
+        });
+        return;
+    }
+
+    let field_path = |field: &str| {
+        if path.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", path, field)
+        }
+    };
+
+    if let Some(object) = value.as_object() {
+        let required = schema["required"].as_array().into_iter().flatten();
+        for field in required.filter_map(|f| f.as_str()) {
+            if !object.contains_key(field) {
+                errors.push(PlanValidationError {
+                    path: field_path(field),
+                    message: "Missing required field".to_string(),
+                });
+            }
+        }
+        if let Some(properties) = schema["properties"].as_object() {
+            for (name, property_schema) in properties {
+                if let Some(property) = object.get(name) {
+                    check_against_schema(property, property_schema, &field_path(name), errors);
+                }
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            check_against_schema(item, item_schema, &format!("{}[{}]", path, index), errors);
+        }
+    }
+}
+
+/// Title, phase count and ticket count of a development plan
+fn summarize_plan(content: &str) -> Result<PlanSummary, String> {
+    let plan: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid plan JSON: {}", e))?;
+    let phases = plan["phases"]
+        .as_array()
+        .ok_or_else(|| "Plan JSON is missing a phases array".to_string())?;
+
+    Ok(PlanSummary {
+        title: plan["title"].as_str().map(|t| t.to_string()),
+        phase_count: phases.len(),
+        ticket_count: phases
+            .iter()
+            .filter_map(|phase| phase["tickets"].as_array())
+            .map(|tickets| tickets.len())
+            .sum(),
+    })
+}
+
+/// Recursively collect markdown files under a specs directory
+/// Hidden subdirectories are skipped. Symlinks are skipped unless `follow_symlinks`
+/// is set, and each real directory is walked once so link cycles terminate.
+fn collect_spec_paths(
+    dir: &Path,
+    follow_symlinks: bool,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
+    let mut visited_dirs = HashSet::new();
+    if let Ok(real) = dir.canonicalize() {
+        visited_dirs.insert(real);
+    }
+    collect_spec_paths_in(dir, follow_symlinks, &mut visited_dirs, paths)
+}
+
+fn collect_spec_paths_in(
+    dir: &Path,
+    follow_symlinks: bool,
+    visited_dirs: &mut HashSet<PathBuf>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| AppError::io("Failed to read specs directory", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let (is_dir, is_file) = if file_type.is_symlink() {
+            if !follow_symlinks {
+                continue;
+            }
+            (path.is_dir(), path.is_file())
+        } else {
+            (file_type.is_dir(), file_type.is_file())
+        };
+
+        if is_dir {
+            let is_hidden = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.'));
+            let first_visit = path
+                .canonicalize()
+                .is_ok_and(|real| visited_dirs.insert(real));
+            if !is_hidden && first_visit {
+                collect_spec_paths_in(&path, follow_symlinks, visited_dirs, paths)?;
+            }
+        } else if is_file
+            && (path.extension().is_some_and(|ext| ext == "md") || is_gzipped_spec(&path))
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// A spec read for export, named by its path under the specs (or archive) directory
+struct ExportedSpec {
+    filename: String,
+    path: PathBuf,
+    archived: bool,
+}
+
+fn export_specs_internal(
+    cwd: &Path,
+    format: &str,
+    include_archived: bool,
+    follow_symlinks: bool,
+    timestamp: &str,
+) -> Result<SpecExport, AppError> {
+    let extension = match format {
+        "markdown" => "md",
+        "zip" => "zip",
+        _ => {
+            return Err(AppError::InvalidInput(format!(
+                "Unknown export format: {} (expected \"markdown\" or \"zip\")",
+                format
+            )))
+        }
+    };
+
+    let mut specs = exported_specs_in(&cwd.join(SPECS_DIR), false, follow_symlinks)?;
+    if include_archived {
+        specs.extend(exported_specs_in(
+            &cwd.join(ARCHIVE_DIR),
+            true,
+            follow_symlinks,
+        )?);
+    }
+
+    let exports_dir = cwd.join(EXPORTS_DIR);
+    fs::create_dir_all(&exports_dir)
+        .map_err(|e| AppError::io("Failed to create exports directory", e))?;
+    let output = exports_dir.join(format!("specs-{}.{}", timestamp, extension));
+
+    if format == "zip" {
+        write_specs_zip(&output, &specs)?;
+    } else {
+        let mut sections = Vec::new();
+        for spec in &specs {
+            let content = read_spec_file(&spec.path)
+                .map_err(|e| AppError::io(&format!("Failed to read {}", spec.filename), e))?;
+            sections.push((spec, content));
+        }
+        fs::write(&output, render_specs_bundle(&sections))
+            .map_err(|e| AppError::io("Failed to write export", e))?;
+    }
+
+    let size = fs::metadata(&output)
+        .map_err(|e| AppError::io("Failed to read export", e))?
+        .len();
+    Ok(SpecExport {
+        path: output.to_string_lossy().to_string(),
+        format: format.to_string(),
+        spec_count: specs.len(),
+        size,
+    })
+}
+
+/// Specs under `dir` in filename order; a missing directory has none
+fn exported_specs_in(
+    dir: &Path,
+    archived: bool,
+    follow_symlinks: bool,
+) -> Result<Vec<ExportedSpec>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = Vec::new();
+    collect_spec_paths(dir, follow_symlinks, &mut paths)?;
+    let mut specs: Vec<ExportedSpec> = paths
+        .into_iter()
+        .map(|path| ExportedSpec {
+            filename: path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            path,
+            archived,
+        })
+        .collect();
+    specs.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(specs)
+}
+
+/// One markdown document: a table of contents linking to each spec, then the
+/// specs themselves separated by horizontal rules
+fn render_specs_bundle(sections: &[(&ExportedSpec, String)]) -> String {
+    let mut bundle = String::from("# Specs\n\n## Contents\n\n");
+    for (index, (spec, content)) in sections.iter().enumerate() {
+        let base_name = spec.filename.rsplit('/').next().unwrap_or(&spec.filename);
+        let title =
+            extract_first_heading(content).unwrap_or_else(|| spec_stem(base_name).to_string());
+        let archived = if spec.archived { " (archived)" } else { "" };
+        bundle.push_str(&format!(
+            "{}. [{}](#spec-{}) — `{}`{}\n",
+            index + 1,
+            title,
+            index + 1,
+            spec.filename,
+            archived
+        ));
+    }
+
+    for (index, (_, content)) in sections.iter().enumerate() {
+        bundle.push_str(SPEC_APPEND_SEPARATOR);
+        bundle.push_str(&format!("<a id=\"spec-{}\"></a>\n\n", index + 1));
+        bundle.push_str(content.trim_end());
+        bundle.push('\n');
+    }
+
+    bundle
+}
+
+/// Zip the specs as stored (gzipped ones stay gzipped) with their companion plans,
+/// under `specs/` or `archive/`
+fn write_specs_zip(output: &Path, specs: &[ExportedSpec]) -> Result<(), AppError> {
+    let zip_error = |e: zip::result::ZipError| {
+        AppError::Internal(format!("Failed to write zip archive: {}", e))
+    };
+    let file = fs::File::create(output).map_err(|e| AppError::io("Failed to create export", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for spec in specs {
+        let root = if spec.archived { "archive" } else { "specs" };
+        let plan_filename = companion_plan_filename(&spec.filename);
+        let plan_path = spec
+            .path
+            .with_file_name(Path::new(&plan_filename).file_name().unwrap_or_default());
+
+        let mut entries = vec![(spec.filename.clone(), spec.path.clone())];
+        if plan_path.is_file() {
+            entries.push((plan_filename, plan_path));
+        }
+        for (name, path) in entries {
+            let bytes = fs::read(&path)
+                .map_err(|e| AppError::io(&format!("Failed to read {}", name), e))?;
+            zip.start_file(format!("{}/{}", root, name), options)
+                .map_err(zip_error)?;
+            zip.write_all(&bytes)
+                .map_err(|e| AppError::io("Failed to write zip archive", e))?;
+        }
+    }
+
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
+
+/// A file found by import_specs: its path in the source, and its content or why it
+/// couldn't be read
+type ImportFile = (String, Result<String, String>);
+
+fn import_specs_internal(
+    cwd: &Path,
+    source: &Path,
+    follow_symlinks: bool,
+    date: &str,
+) -> Result<ImportSpecsResult, AppError> {
+    let files = if source.is_dir() {
+        read_import_dir(source, follow_symlinks)?
+    } else if source.is_file() && source.extension().is_some_and(|ext| ext == "zip") {
+        read_import_zip(source)?
+    } else if source.exists() {
+        return Err(AppError::InvalidInput(format!(
+            "Import source must be a directory or a .zip archive: {}",
+            source.display()
+        )));
+    } else {
+        return Err(AppError::NotFound(format!(
+            "Import source does not exist: {}",
+            source.display()
+        )));
+    };
+
+    let specs_dir = cwd.join(SPECS_DIR);
+    fs::create_dir_all(&specs_dir)
+        .map_err(|e| AppError::io("Failed to create specs directory", e))?;
+
+    let results: Vec<SpecImportResult> = files
+        .into_iter()
+        .map(|(source, content)| {
+            let imported = content.and_then(|content| {
+                let filename =
+                    unique_spec_filename(&specs_dir, &import_spec_filename(&source, date));
+                fs::write(specs_dir.join(&filename), content)
+                    .map_err(|e| format!("Failed to write {}: {}", filename, e))?;
+                Ok(filename)
+            });
+            match imported {
+                Ok(filename) => SpecImportResult {
+                    source,
+                    filename: Some(filename),
+                    error: None,
+                },
+                Err(error) => SpecImportResult {
+                    source,
+                    filename: None,
+                    error: Some(error),
+                },
+            }
+        })
+        .collect();
+
+    let imported = results.iter().filter(|r| r.filename.is_some()).count();
+    Ok(ImportSpecsResult {
+        imported,
+        skipped: results.len() - imported,
+        results,
+    })
+}
+
+/// Markdown files under `dir` (hidden directories skipped), sorted
+fn read_import_dir(dir: &Path, follow_symlinks: bool) -> Result<Vec<ImportFile>, AppError> {
+    let mut paths = Vec::new();
+    collect_spec_paths(dir, follow_symlinks, &mut paths)?;
+    paths.retain(|path| !is_gzipped_spec(path));
+    paths.sort();
+
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let source = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .to_string();
+            let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read: {}", e));
+            (source, content)
+        })
+        .collect())
+}
+
+/// Markdown entries of a zip archive, in archive order; other entries are ignored
+fn read_import_zip(path: &Path) -> Result<Vec<ImportFile>, AppError> {
+    let zip_error = |e: zip::result::ZipError| {
+        AppError::InvalidInput(format!("Failed to read zip archive: {}", e))
+    };
+    let file = fs::File::open(path).map_err(|e| AppError::io("Failed to open archive", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+
+    let mut files = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(zip_error)?;
+        let source = entry.name().to_string();
+        if entry.is_dir() || !source.ends_with(".md") {
+            continue;
+        }
+        // Entries with absolute or `..` paths are reported rather than trusted
+        if entry.enclosed_name().is_none() {
+            files.push((source, Err("Unsafe path in archive".to_string())));
+            continue;
+        }
+        let mut content = String::new();
+        let content = entry
+            .read_to_string(&mut content)
+            .map(|_| content)
+            .map_err(|e| format!("Failed to read: {}", e));
+        files.push((source, content));
+    }
+
+    Ok(files)
+}
+
+/// Spec filename for an imported file: kept if it already follows YYYYMMDD-name.md,
+/// otherwise `<date>-<slug of the file stem>.md`
+fn import_spec_filename(source: &str, date: &str) -> String {
+    let base_name = source.rsplit(['/', '\\']).next().unwrap_or(source);
+    if is_valid_spec_filename_internal(base_name) {
+        return base_name.to_string();
+    }
+    format!("{}-{}.md", date, slugify(spec_stem(base_name)))
+}
+
+/// `filename`, or `<stem>-2.md`, `<stem>-3.md`, ... if a spec by that name exists
+fn unique_spec_filename(specs_dir: &Path, filename: &str) -> String {
+    let stem = spec_stem(filename);
+    let mut candidate = filename.to_string();
+    let mut suffix = 2;
+    while specs_dir.join(&candidate).exists() {
+        candidate = format!("{}-{}.md", stem, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+fn is_gzipped_spec(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(GZIPPED_SPEC_SUFFIX))
+}
+
+/// Read a spec's markdown, decompressing `.md.gz` files
+fn read_spec_file(path: &Path) -> std::io::Result<String> {
+    if !is_gzipped_spec(path) {
+        return fs::read_to_string(path);
+    }
+    let mut content = String::new();
+    GzDecoder::new(fs::File::open(path)?).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Spec markdown from raw bytes, decompressing gzip data (as stored for `.md.gz`)
+fn decode_spec_bytes(bytes: &[u8]) -> std::io::Result<String> {
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return Ok(String::from_utf8_lossy(bytes).to_string());
+    }
+    let mut content = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Spec filename without its `.md` / `.md.gz` extension
+pub(crate) fn spec_stem(filename: &str) -> &str {
+    filename
+        .strip_suffix(GZIPPED_SPEC_SUFFIX)
+        .or_else(|| filename.strip_suffix(".md"))
+        .unwrap_or(filename)
+}
+
+/// Delete a spec and, if present, its companion plan (so no plan is orphaned)
+fn delete_spec_files(specs_dir: &Path, filename: &str) -> Result<(), AppError> {
+    let spec_path = resolve_spec_path(specs_dir, filename)?;
+
+    if !spec_path.exists() {
+        return Err(AppError::NotFound(format!(
+            "Spec file not found: {}",
+            filename
+        )));
+    }
+
+    fs::remove_file(&spec_path).map_err(|e| AppError::io("Failed to delete spec file", e))?;
+
+    let plan_filename = companion_plan_filename(filename);
+    let plan_path = resolve_spec_path(specs_dir, &plan_filename)?;
+    if plan_path.exists() {
+        let _ = fs::remove_file(&plan_path); // Ignore errors if plan doesn't exist
+        log::info!(
+            "[delete_spec] Cleaned up companion plan file: {}",
+            plan_filename
+        );
+    }
+    Ok(())
+}
+
+/// Companion plan of a spec: `x.md` and `x.md.gz` both map to `x.plan.json`
+fn companion_plan_filename(filename: &str) -> String {
+    format!("{}.plan.json", spec_stem(filename))
+}
+
+/// Remove everything inside `dir` (but not `dir` itself), returning the number of files removed
+/// A missing directory counts as already clear
+fn clear_directory(dir: &Path) -> Result<usize, AppError> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    let entries = fs::read_dir(dir).map_err(|e| AppError::io("Failed to read directory", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            removed += clear_directory(&path)?;
+            fs::remove_dir(&path).map_err(|e| AppError::io("Failed to remove directory", e))?;
+        } else {
+            fs::remove_file(&path).map_err(|e| AppError::io("Failed to remove file", e))?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Move a spec between the specs and archive directories, keeping its companion plan
+/// Refuses to overwrite an existing spec at the destination
+fn move_spec(from_dir: &Path, to_dir: &Path, filename: &str) -> Result<(), AppError> {
+    let from_path = resolve_spec_path(from_dir, filename)?;
+    let to_path = resolve_spec_path(to_dir, filename)?;
+
+    if !from_path.exists() {
+        return Err(AppError::NotFound(format!(
+            "Spec file not found: {}",
+            filename
+        )));
+    }
+    if to_path.exists() {
+        return Err(AppError::InvalidInput(format!(
+            "A spec named {} already exists in {}",
+            filename,
+            to_dir.file_name().unwrap_or_default().to_string_lossy()
+        )));
+    }
+
+    if let Some(parent) = to_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io("Failed to create directory", e))?;
+    }
+    fs::rename(&from_path, &to_path).map_err(|e| AppError::io("Failed to move spec file", e))?;
+
+    // Move the companion plan file too, so archived specs keep their plans
+    let plan_filename = companion_plan_filename(filename);
+    let from_plan = resolve_spec_path(from_dir, &plan_filename)?;
+    if from_plan.exists() {
+        let to_plan = resolve_spec_path(to_dir, &plan_filename)?;
+        fs::rename(&from_plan, &to_plan)
+            .map_err(|e| AppError::io("Failed to move plan file", e))?;
+    }
+
+    Ok(())
+}
+
+/// Absolute, non-system workspace path (canonicalized when it exists) and its string form
+fn resolve_workspace_candidate(input_path: &str) -> Result<(PathBuf, String), String> {
+    if input_path.is_empty() {
+        return Err("Path is required".to_string());
+    }
+
+    let path = Path::new(input_path);
+    if !path.is_absolute() {
+        return Err("Path must be absolute (e.g., /home/user/projects/my-app)".to_string());
+    }
+
+    let resolved_path = path
+        .canonicalize()
+        .unwrap_or_else(|_| PathBuf::from(input_path));
+    let resolved_str = resolved_path.to_string_lossy().to_string();
+
+    if is_forbidden_path(&resolved_str, cfg!(windows)) {
+        return Err("Cannot use system directories as workspace".to_string());
+    }
+
+    Ok((resolved_path, resolved_str))
+}
+
+/// Whether a resolved path is (or is inside) a system directory for the given platform
+fn is_forbidden_path(resolved: &str, windows: bool) -> bool {
+    if !windows {
+        return FORBIDDEN_PATHS.iter().any(|forbidden| {
+            resolved == *forbidden || resolved.starts_with(&format!("{}/", forbidden))
+        });
+    }
+
+    // canonicalize() yields verbatim paths (\\?\C:\...) on Windows
+    let normalized = resolved
+        .trim_start_matches("\\\\?\\")
+        .replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase();
+    WINDOWS_FORBIDDEN_PATHS.iter().any(|forbidden| {
+        let forbidden = forbidden.to_lowercase();
+        normalized == forbidden || normalized.starts_with(&format!("{}\\", forbidden))
+    })
+}
+
+/// Warning for workspace roots that are allowed but likely to be huge (home or cloud-sync roots)
+fn workspace_warning(resolved: &Path, home: Option<&Path>) -> Option<String> {
+    let home = home?;
+    if resolved == home {
+        return Some(
+            "This is your home directory; scanning it as a workspace may be slow".to_string(),
+        );
+    }
+    SYNC_ROOT_DIRS
+        .iter()
+        .find(|dir| resolved == home.join(dir))
+        .map(|dir| {
+            format!(
+                "{} is a cloud-sync root; scanning it as a workspace may be slow",
+                dir
+            )
+        })
+}
+
+/// Resolve a (possibly nested) spec filename inside the specs directory
+/// Rejects absolute paths and `..` components so names can't escape it
+fn resolve_spec_path(specs_dir: &Path, filename: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(filename);
+    let is_safe = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)));
+
+    if filename.is_empty() || !is_safe {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid spec filename: {}",
+            filename
+        )));
+    }
+
+    Ok(specs_dir.join(relative))
+}
+
+/// Resolve a possibly non-existent path for writing inside the workspace
+/// Rejects `..`, absolute paths, protected directories and symlinked escapes
+fn resolve_writable_workspace_path(cwd: &Path, relative_path: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(relative_path);
+    if relative_path.trim().is_empty() {
+        return Err(AppError::InvalidInput("Path is required".to_string()));
+    }
+
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => {
+                if WRITE_PROTECTED_DIRS.iter().any(|d| name == *d) {
+                    return Err(AppError::PermissionDenied(format!(
+                        "Writing to {} is not allowed: {}",
+                        name.to_string_lossy(),
+                        relative_path
+                    )));
+                }
+            }
+            Component::CurDir => {}
+            _ => {
+                return Err(AppError::InvalidInput(format!(
+                    "Path must stay inside the workspace: {}",
+                    relative_path
+                )));
+            }
+        }
+    }
+
+    let root = cwd
+        .canonicalize()
+        .map_err(|e| AppError::io("Failed to resolve working directory", e))?;
+    let path = root.join(relative);
+
+    // The deepest existing ancestor must still be inside the workspace (symlinks)
+    if let Some(existing) = path.ancestors().find(|p| p.exists()) {
+        let existing = existing
+            .canonicalize()
+            .map_err(|e| AppError::io("Failed to resolve path", e))?;
+        if !existing.starts_with(&root) {
+            return Err(AppError::InvalidInput(format!(
+                "Path is outside the workspace: {}",
+                relative_path
+            )));
+        }
+    }
+
+    Ok(path)
+}
+
+/// Resolve an existing path relative to the workspace root
+/// Canonicalizes both sides so `..` and symlinks can't escape the workspace
+pub(crate) fn resolve_workspace_path(cwd: &Path, relative_path: &str) -> Result<PathBuf, AppError> {
+    if Path::new(relative_path).is_absolute() {
+        return Err(AppError::InvalidInput(format!(
+            "Path must be relative to the workspace: {}",
+            relative_path
+        )));
+    }
+
+    let root = cwd
+        .canonicalize()
+        .map_err(|e| AppError::io("Failed to resolve working directory", e))?;
+    let path = root.join(relative_path);
+
+    if !path.exists() {
+        return Err(AppError::NotFound(format!(
+            "Path does not exist: {}",
+            relative_path
+        )));
+    }
+
+    let path = path
+        .canonicalize()
+        .map_err(|e| AppError::io("Failed to resolve path", e))?;
+    if !path.starts_with(&root) {
+        return Err(AppError::InvalidInput(format!(
+            "Path is outside the workspace: {}",
+            relative_path
+        )));
+    }
+
+    Ok(path)
+}
+
+fn is_valid_spec_filename_internal(filename: &str) -> bool {
+    let stem = match filename.strip_suffix(".md") {
+        Some(stem) => stem,
+        None => return false,
+    };
+
+    let (date_part, name) = match stem.split_once('-') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    if date_part.len() != 8 || chrono::NaiveDate::parse_from_str(date_part, "%Y%m%d").is_err() {
+        return false;
+    }
+
+    !name.is_empty() && slugify(name) == name
+}
+
+/// Lowercase a title and collapse anything that isn't alphanumeric into single dashes
+/// Unicode letters are kept as-is so non-English titles stay readable
+fn slugify(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "untitled".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn extract_first_heading(content: &str) -> Option<String> {
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("# ") {
+            return Some(trimmed[2..].trim().to_string());
+        }
+    }
+    None
+}
+
+/// Split a leading `---` frontmatter block (delimiters included) from the body
+fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = content.len() - rest.len();
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim() == "---" {
+            return (Some(&content[..offset]), &content[offset..]);
+        }
+    }
+    (None, content)
+}
+
+/// Level of an ATX heading line (`## Title` -> 2)
+fn heading_level(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then_some(level)
+}
+
+/// Apply `f` to every line, telling it whether the line sits inside a fenced code block
+fn map_markdown_lines(content: &str, mut f: impl FnMut(&str, bool) -> Option<String>) -> String {
+    let mut fence: Option<&str> = None;
+    let mut out = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        let in_code = fence.is_some() || marker.is_some();
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            _ => {}
+        }
+        if let Some(line) = f(line, in_code) {
+            out.push(line);
+        }
+    }
+    out.join("\n")
+}
+
+/// Merged spec body: the first source's frontmatter, a combined title, then one
+/// `## <title>` section per source with its own headings shifted to level 3 and below
+fn merge_spec_contents(sources: &[(String, String)]) -> String {
+    let mut merged = String::new();
+    if let Some((frontmatter, _)) = sources.first().map(|(_, c)| split_frontmatter(c)) {
+        merged.push_str(frontmatter.unwrap_or_default());
+    }
+    let titles: Vec<&str> = sources.iter().map(|(title, _)| title.as_str()).collect();
+    merged.push_str(&format!("# {}\n", titles.join(" & ")));
+
+    for (title, content) in sources {
+        let (_, body) = split_frontmatter(content);
+
+        // The source's own title becomes the section heading
+        let mut title_dropped = false;
+        let body = map_markdown_lines(body, |line, in_code| {
+            if !in_code && !title_dropped && heading_level(line) == Some(1) {
+                title_dropped = true;
+                return None;
+            }
+            Some(line.to_string())
+        });
+
+        let shallowest = body
+            .lines()
+            .zip(code_flags(&body))
+            .filter(|(_, in_code)| !in_code)
+            .filter_map(|(line, _)| heading_level(line))
+            .min();
+        let body = match shallowest {
+            Some(shallowest) => map_markdown_lines(&body, |line, in_code| {
+                let level = heading_level(line).filter(|_| !in_code);
+                Some(match level {
+                    Some(level) => {
+                        let new_level = (level + 3 - shallowest).min(6);
+                        let text = line.trim_start()[level..].trim();
+                        format!("{} {}", "#".repeat(new_level), text)
+                            .trim_end()
+                            .to_string()
+                    }
+                    None => line.to_string(),
+                })
+            }),
+            None => body,
+        };
+
+        merged.push_str(&format!("\n## {}\n\n{}\n", title, body.trim()));
+    }
+    merged
+}
+
+/// Per-line "inside a fenced code block" flags, matching `map_markdown_lines`
+fn code_flags(content: &str) -> Vec<bool> {
+    let mut flags = Vec::new();
+    map_markdown_lines(content, |_, in_code| {
+        flags.push(in_code);
+        None
+    });
+    flags
+}
+
+/// Read a `key: value` entry from a leading `---` frontmatter block
+fn extract_frontmatter_value(content: &str, key: &str) -> Option<String> {
+    let mut lines = content.lines();
+    if lines.next()?.trim() != "---" {
+        return None;
+    }
+
+    for line in lines {
+        let line = line.trim();
+        if line == "---" {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            if k.trim() == key {
+                let value = v.trim().trim_matches(|c| c == '"' || c == '\'');
+                return (!value.is_empty()).then(|| value.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Effective working directory for runs tied to a spec
+/// The spec's `sub_path` is joined onto the workspace root and must stay inside it
+pub(crate) fn resolve_spec_working_directory(
+    root: &Path,
+    spec_content: &str,
+) -> Result<PathBuf, AppError> {
+    let Some(sub_path) = extract_frontmatter_value(spec_content, "sub_path") else {
+        return Ok(root.to_path_buf());
+    };
+
+    let path = resolve_workspace_path(root, &sub_path)?;
+    if !path.is_dir() {
+        return Err(AppError::InvalidInput(format!(
+            "Spec sub_path is not a directory: {}",
+            sub_path
+        )));
+    }
+    Ok(path)
+}
+
+fn extract_date_from_filename(filename: &str) -> Option<String> {
+    // Expected format: YYYYMMDD-feature-name.md
+    if filename.len() >= 8 {
+        let date_part = &filename[..8];
+        if date_part.chars().all(|c| c.is_ascii_digit()) {
+            // Format as YYYY-MM-DD
+            return Some(format!(
+                "{}-{}-{}",
+                &date_part[..4],
+                &date_part[4..6],
+                &date_part[6..8]
+            ));
+        }
+    }
+    None
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_first_heading() {
+        let content = "# My Title\n\nSome content";
+        assert_eq!(extract_first_heading(content), Some("My Title".to_string()));
+
+        let content_no_heading = "Just some text\nNo heading here";
+        assert_eq!(extract_first_heading(content_no_heading), None);
+
+        let content_with_spaces = "#    Spaced Title   \n";
+        assert_eq!(extract_first_heading(content_with_spaces), Some("Spaced Title".to_string()));
+
+        let content_h2 = "## Second Level\n";
+        assert_eq!(extract_first_heading(content_h2), None);
+    }
+
+    #[test]
+    fn test_extract_date_from_filename() {
+        assert_eq!(
+            extract_date_from_filename("20260131-feature.md"),
+            Some("2026-01-31".to_string())
+        );
+
+        assert_eq!(
+            extract_date_from_filename("20251225-christmas.md"),
+            Some("2025-12-25".to_string())
+        );
+
+        assert_eq!(extract_date_from_filename("feature.md"), None);
+
+        assert_eq!(extract_date_from_filename("202601-incomplete.md"), None);
 
         assert_eq!(extract_date_from_filename("notadate-file.md"), None);
     }
 
     #[test]
-    fn test_validate_workspace() {
-        // Test empty path
-        let result = validate_workspace("".to_string());
-        assert!(!result.valid);
-        assert_eq!(result.error, Some("Path is required".to_string()));
+    fn test_list_specs_counts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(
+            specs_dir.join("20260131-counts.md"),
+            "# Counts\n\nThree word line\nÜber café\n",
+        )
+        .unwrap();
+
+        let specs = list_specs_internal(temp_dir.path(), false).unwrap();
+
+        assert_eq!(specs.len(), 1);
+        assert_eq!(specs[0].title, "Counts");
+        assert_eq!(specs[0].word_count, 7);
+        assert_eq!(specs[0].line_count, 4);
+        assert_eq!(specs[0].char_count, 36);
+    }
+
+    #[test]
+    fn test_list_specs_orders_by_date_across_folders() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(specs_dir.join("billing")).unwrap();
+        fs::write(specs_dir.join("20260301-newest.md"), "# Newest").unwrap();
+        fs::write(specs_dir.join("billing/20260201-refunds.md"), "# Refunds").unwrap();
+        fs::write(specs_dir.join("20260101-oldest.md"), "# Oldest").unwrap();
+
+        let specs = list_specs_internal(temp_dir.path(), false).unwrap();
+        let filenames: Vec<&str> = specs.iter().map(|s| s.filename.as_str()).collect();
+        assert_eq!(
+            filenames,
+            vec![
+                "20260301-newest.md",
+                "billing/20260201-refunds.md",
+                "20260101-oldest.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_spec_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+
+        save_spec(
+            "payments/20260201-refunds.md".to_string(),
+            "# Refunds\n".to_string(),
+            Some(cwd.clone()),
+            None,
+        )
+        .unwrap();
+        save_spec(
+            "20260101-top-level.md".to_string(),
+            "# Top Level\n".to_string(),
+            Some(cwd.clone()),
+            None,
+        )
+        .unwrap();
+        fs::write(specs_dir.join("payments/20260201-refunds.plan.json"), "{}").unwrap();
+
+        let specs = list_specs_internal(temp_dir.path(), false).unwrap();
+        let filenames: Vec<&str> = specs.iter().map(|s| s.filename.as_str()).collect();
+        assert_eq!(
+            filenames,
+            vec!["payments/20260201-refunds.md", "20260101-top-level.md"]
+        );
+        assert_eq!(specs[0].title, "Refunds");
+        assert_eq!(specs[0].created_at, "2026-02-01");
+
+        let spec = read_spec(specs[0].filename.clone(), Some(cwd.clone())).unwrap();
+        assert_eq!(spec.content, "# Refunds\n");
+
+        delete_spec(specs[0].filename.clone(), Some(cwd.clone())).unwrap();
+        assert!(!specs_dir.join("payments/20260201-refunds.md").exists());
+        assert!(!specs_dir
+            .join("payments/20260201-refunds.plan.json")
+            .exists());
+    }
+
+    #[test]
+    fn test_resolve_spec_path_rejects_traversal() {
+        let specs_dir = Path::new("/workspace/.specstudio/specs");
+
+        assert_eq!(
+            resolve_spec_path(specs_dir, "team/20260101-a.md").unwrap(),
+            specs_dir.join("team/20260101-a.md")
+        );
+        assert!(resolve_spec_path(specs_dir, "../secrets.md").is_err());
+        assert!(resolve_spec_path(specs_dir, "team/../../x.md").is_err());
+        assert!(resolve_spec_path(specs_dir, "/etc/passwd").is_err());
+        assert!(resolve_spec_path(specs_dir, "").is_err());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("User Authentication"), "user-authentication");
+        assert_eq!(slugify("  API: v2 / OAuth!! "), "api-v2-oauth");
+        assert_eq!(slugify("Café Über Straße"), "café-über-straße");
+        assert_eq!(slugify("日本語 の 仕様"), "日本語-の-仕様");
+        assert_eq!(slugify("C++ & Rust"), "c-rust");
+        assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+        assert_eq!(slugify("!!!"), "untitled");
+        assert_eq!(slugify(""), "untitled");
+    }
+
+    #[test]
+    fn test_normalize_spec_filename() {
+        let filename = normalize_spec_filename("My New Feature!".to_string());
+        assert!(filename.ends_with("-my-new-feature.md"));
+        assert!(is_valid_spec_filename_internal(&filename));
+        assert!(extract_date_from_filename(&filename).is_some());
+    }
+
+    #[test]
+    fn test_is_valid_spec_filename() {
+        assert!(is_valid_spec_filename_internal("20260131-feature.md"));
+        assert!(is_valid_spec_filename_internal("20260131-café-menu.md"));
+        assert!(!is_valid_spec_filename_internal("feature.md"));
+        assert!(!is_valid_spec_filename_internal("20260131-feature.txt"));
+        assert!(!is_valid_spec_filename_internal("20261399-feature.md"));
+        assert!(!is_valid_spec_filename_internal("20260131-.md"));
+        assert!(!is_valid_spec_filename_internal("20260131-Feature Name.md"));
+        assert!(!is_valid_spec_filename_internal("20260131-../escape.md"));
+    }
+
+    #[test]
+    fn test_validate_workspace() {
+        // Test empty path
+        let result = validate_workspace("".to_string());
+        assert!(!result.valid);
+        assert_eq!(result.error, Some("Path is required".to_string()));
+
+        // Test relative path (not allowed)
+        let result = validate_workspace("./relative/path".to_string());
+        assert!(!result.valid);
+        assert!(result.error.as_ref().unwrap().contains("must be absolute"));
+
+        // Test that FORBIDDEN_PATHS list is not empty and contains expected paths
+        assert!(FORBIDDEN_PATHS.contains(&"/etc"));
+        assert!(FORBIDDEN_PATHS.contains(&"/usr"));
+    }
+
+    #[test]
+    fn test_excluded_dirs_contains_specstudio() {
+        // CRITICAL: Ensure .specstudio is excluded to prevent AI from reading its own plans
+        assert!(EXCLUDED_DIRS.contains(&".specstudio"));
+    }
+
+    #[test]
+    fn test_max_limits() {
+        // Ensure reasonable limits are set
+        assert_eq!(MAX_FILE_SIZE, 1024 * 1024); // 1MB
+        assert_eq!(MAX_TOTAL_SIZE, 5 * 1024 * 1024); // 5MB
+    }
+
+    #[test]
+    fn test_specs_dir_constant() {
+        assert_eq!(SPECS_DIR, ".specstudio/specs");
+    }
+
+    #[test]
+    fn test_read_workspace_context_reports_progress() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Readme").unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let mut events: Vec<ContextProgress> = Vec::new();
+        let context = read_workspace_context_internal(
+            temp_dir.path(),
+            &[],
+            &[],
+            false,
+            None,
+            None,
+            &mut |p| events.push(p.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(context.total_files, 2);
+        let last = events.last().expect("final progress event");
+        assert!(last.done);
+        assert_eq!(last.files_scanned, 2);
+        assert_eq!(last.bytes_collected, context.total_size);
+    }
+
+    #[test]
+    fn test_read_workspace_context_custom_exclusions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".terraform")).unwrap();
+        fs::create_dir_all(root.join(".mypy_cache")).unwrap();
+        fs::create_dir_all(root.join(".specstudio")).unwrap();
+        fs::write(root.join(".terraform/state.tf"), "state").unwrap();
+        fs::write(root.join(".mypy_cache/cache.json"), "{}").unwrap();
+        fs::write(root.join("api.pb.go"), "package api").unwrap();
+        fs::write(root.join("main.go"), "package main").unwrap();
+        fs::write(
+            root.join(CONTEXT_IGNORE_FILE),
+            "# generated\n.mypy_cache/\n*.pb.go\n",
+        )
+        .unwrap();
+
+        let extra_dirs = vec![".terraform".to_string(), ".specstudio".to_string()];
+        let context =
+            read_workspace_context_internal(root, &extra_dirs, &[], false, None, None, &mut |_| {})
+                .unwrap();
+
+        let paths: Vec<&str> = context.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["main.go"]);
+    }
+
+    #[test]
+    fn test_context_exclusions_keep_specstudio_forced() {
+        let exclusions = ContextExclusions::new(&[], &["*.PB.GO".to_string()]);
+        assert!(exclusions.is_excluded_dir(".specstudio"));
+        assert!(exclusions.is_excluded_dir("node_modules"));
+        assert!(exclusions.is_excluded_file("api.pb.go"));
+        assert!(exclusions.is_excluded_file("logo.PNG"));
+        assert!(!exclusions.is_excluded_file("main.go"));
+    }
+
+    #[test]
+    fn test_diff_spec_against_git_ref() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test User"]);
+
+        let specs_dir = cwd.join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("20260101-a.md"), "# A\n\nold line\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "init"]);
+
+        fs::write(specs_dir.join("20260101-a.md"), "# A\n\nnew line\n").unwrap();
+        fs::write(specs_dir.join("20260102-b.md"), "# B\n").unwrap();
+        let working_directory = Some(cwd.to_string_lossy().to_string());
+
+        let diff = diff_spec(
+            "20260101-a.md".to_string(),
+            "HEAD".to_string(),
+            working_directory.clone(),
+        )
+        .unwrap();
+        assert!(diff.existed_at_ref);
+        assert!(diff.has_changes);
+        assert!(diff.diff.contains("--- a/20260101-a.md"));
+        assert!(diff.diff.contains("-old line"));
+        assert!(diff.diff.contains("+new line"));
+
+        // Spec created after the ref diffs against an empty file
+        let diff = diff_spec(
+            "20260102-b.md".to_string(),
+            "HEAD".to_string(),
+            working_directory,
+        )
+        .unwrap();
+        assert!(!diff.existed_at_ref);
+        assert!(diff.diff.contains("--- /dev/null"));
+        assert!(diff.diff.contains("+# B"));
+
+        // Compressed specs are diffed as markdown on both sides
+        let gzip = |content: &str| {
+            use flate2::{write::GzEncoder, Compression};
+            use std::io::Write;
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(content.as_bytes()).unwrap();
+            encoder.finish().unwrap()
+        };
+        fs::write(specs_dir.join("20260103-c.md.gz"), gzip("# C\n\nold\n")).unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-m", "compressed"]);
+        fs::write(specs_dir.join("20260103-c.md.gz"), gzip("# C\n\nnew\n")).unwrap();
+        let diff = diff_spec(
+            "20260103-c.md.gz".to_string(),
+            "HEAD".to_string(),
+            Some(cwd.to_string_lossy().to_string()),
+        )
+        .unwrap();
+        assert!(diff.existed_at_ref);
+        assert!(diff.diff.contains("-old"));
+        assert!(diff.diff.contains("+new"));
+
+        let invalid = diff_spec(
+            "20260103-c.md.gz".to_string(),
+            "--output=/tmp/x".to_string(),
+            Some(cwd.to_string_lossy().to_string()),
+        );
+        assert_eq!(invalid.unwrap_err().code(), "INVALID_INPUT");
+    }
+
+    #[test]
+    fn test_read_workspace_context_respects_specstudioignore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("fixtures")).unwrap();
+        fs::write(root.join("fixtures/data.json"), "{}").unwrap();
+        fs::write(root.join("secrets.txt"), "token").unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(
+            root.join(SPECSTUDIO_IGNORE_FILE),
+            "fixtures/\nsecrets.txt\n",
+        )
+        .unwrap();
+
+        let context =
+            read_workspace_context_internal(root, &[], &[], false, None, None, &mut |_| {})
+                .unwrap();
+
+        let mut paths: Vec<&str> = context.files.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec![SPECSTUDIO_IGNORE_FILE, "main.rs"]);
+    }
+
+    #[test]
+    fn test_read_workspace_context_respects_nested_specstudioignore() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("app/snapshots")).unwrap();
+        fs::write(root.join("app/snapshots/home.snap"), "snap").unwrap();
+        fs::write(root.join("app/notes.txt"), "notes").unwrap();
+        fs::write(root.join("app/keep.txt"), "keep").unwrap();
+        fs::write(root.join("app/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("debug.txt"), "debug").unwrap();
+        fs::write(root.join("snapshots.md"), "# Snapshots").unwrap();
+        fs::write(root.join(SPECSTUDIO_IGNORE_FILE), "*.txt\n").unwrap();
+        // Patterns are relative to their own directory, and the deeper file wins
+        fs::write(
+            root.join("app").join(SPECSTUDIO_IGNORE_FILE),
+            "snapshots/\n!keep.txt\n",
+        )
+        .unwrap();
+
+        let context =
+            read_workspace_context_internal(root, &[], &[], false, None, None, &mut |_| {})
+                .unwrap();
+
+        let mut paths: Vec<&str> = context.files.iter().map(|f| f.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                SPECSTUDIO_IGNORE_FILE,
+                "app/.specstudioignore",
+                "app/keep.txt",
+                "app/main.rs",
+                "snapshots.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_workspace_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+
+        let resolved = resolve_workspace_path(root, "src/main.rs").unwrap();
+        assert!(resolved.ends_with("src/main.rs"));
+        assert!(resolve_workspace_path(root, "").unwrap().is_dir());
+
+        let missing = resolve_workspace_path(root, "src/missing.rs").unwrap_err();
+        assert_eq!(missing.code(), "NOT_FOUND");
+
+        let parent_name = root.file_name().unwrap().to_string_lossy().to_string();
+        let escaped = resolve_workspace_path(&root.join("src"), &format!("../../{}", parent_name));
+        assert_eq!(escaped.unwrap_err().code(), "INVALID_INPUT");
+        assert_eq!(
+            resolve_workspace_path(root, "/etc").unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_rejects_symlink_escapes() {
+        use std::os::unix::fs::symlink;
+
+        let outside = tempfile::TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        symlink(outside.path(), root.join("outside")).unwrap();
+        symlink(root.join("src"), root.join("src-link")).unwrap();
+
+        let working_directory = root.to_string_lossy().to_string();
+        let resolve =
+            |relative: &str| resolve_path(working_directory.clone(), relative.to_string());
+
+        let canonical_root = root.canonicalize().unwrap();
+        assert_eq!(
+            resolve("src/./main.rs").unwrap(),
+            canonical_root.join("src/main.rs").to_string_lossy()
+        );
+        // Links that stay inside resolve to their target
+        assert_eq!(
+            resolve("src-link/main.rs").unwrap(),
+            canonical_root.join("src/main.rs").to_string_lossy()
+        );
+
+        assert_eq!(resolve("outside").unwrap_err().code(), "INVALID_INPUT");
+        assert_eq!(
+            resolve("outside/secret.txt").unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+        assert_eq!(resolve("src/../../x").unwrap_err().code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_push_recent_workspace_dedupes_and_caps() {
+        let mut recent = Vec::new();
+        for i in 0..12 {
+            recent = push_recent_workspace(recent, Path::new(&format!("/tmp/project-{}", i)), i);
+        }
+        assert_eq!(recent.len(), MAX_RECENT_WORKSPACES);
+        assert_eq!(recent[0].path, "/tmp/project-11");
+        assert_eq!(recent[0].name, "project-11");
+
+        // Re-adding an existing entry moves it to the front without duplicating
+        let recent = push_recent_workspace(recent, Path::new("/tmp/project-5"), 100);
+        assert_eq!(recent.len(), MAX_RECENT_WORKSPACES);
+        assert_eq!(recent[0].path, "/tmp/project-5");
+        assert_eq!(recent[0].last_opened, 100);
+        assert_eq!(
+            recent.iter().filter(|w| w.path == "/tmp/project-5").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_recent_workspaces_validation_and_pruning() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let existing = temp.path().to_string_lossy().to_string();
+        let missing = temp.path().join("gone").to_string_lossy().to_string();
+        fs::write(temp.path().join("file.txt"), "x").unwrap();
+
+        assert!(validate_recent_workspace_path(&existing).is_ok());
+        assert_eq!(
+            validate_recent_workspace_path(&format!("{}/", existing)).unwrap(),
+            temp.path()
+        );
+        assert_eq!(
+            validate_recent_workspace_path("relative/dir")
+                .unwrap_err()
+                .code(),
+            "INVALID_INPUT"
+        );
+        assert_eq!(
+            validate_recent_workspace_path(&missing).unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+        let file = temp.path().join("file.txt").to_string_lossy().to_string();
+        assert_eq!(
+            validate_recent_workspace_path(&file).unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+
+        let stored = serde_json::json!([
+            { "path": existing, "name": "a", "lastOpened": 2 },
+            { "path": missing, "name": "gone", "lastOpened": 1 },
+            { "bogus": true }
+        ]);
+        let recent = prune_missing_workspaces(read_recent_workspaces(Some(stored)));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].path, existing);
+    }
+
+    #[test]
+    fn test_read_workspace_file_utf8() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/main.rs"), "fn main() {} // héllo").unwrap();
+
+        let file = read_workspace_file_internal(temp.path(), "src/main.rs").unwrap();
+        assert_eq!(file.path, "src/main.rs");
+        assert_eq!(file.content, "fn main() {} // héllo");
+        assert!(file.is_utf8);
+        assert_eq!(file.size, "fn main() {} // héllo".len() as u64);
+        assert!(file.modified_at.is_some());
+    }
+
+    #[test]
+    fn test_number_lines() {
+        assert_eq!(number_lines("fn main() {\n}\n"), "1 | fn main() {\n2 | }\n");
+        // Empty trailing lines keep their numbers
+        assert_eq!(number_lines("a\n\n\n"), "1 | a\n2 | \n3 | \n");
+        assert_eq!(number_lines("no newline"), "1 | no newline");
+        assert_eq!(number_lines(""), "");
+
+        let ten = "x\n".repeat(10);
+        let numbered = number_lines(&ten);
+        assert!(numbered.starts_with(" 1 | x\n"));
+        assert!(numbered.ends_with("10 | x\n"));
+    }
+
+    #[test]
+    fn test_slice_lines() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let range = |start, end| slice_lines("f.txt".to_string(), content, start, end);
+
+        let normal = range(2, 3);
+        assert_eq!(normal.content, "two\nthree");
+        assert_eq!((normal.start_line, normal.end_line), (2, 3));
+        assert_eq!(normal.total_lines, 4);
+
+        // Bounds past either end are clamped to the file
+        let clamped = range(0, 100);
+        assert_eq!(clamped.content, "one\ntwo\nthree\nfour");
+        assert_eq!((clamped.start_line, clamped.end_line), (1, 4));
+
+        // Inverted or out-of-file ranges are empty rather than errors
+        for (start, end) in [(3, 2), (10, 20)] {
+            let empty = range(start, end);
+            assert_eq!(empty.content, "");
+            assert!(empty.end_line < empty.start_line);
+        }
+        assert_eq!(slice_lines("e".to_string(), "", 1, 5).total_lines, 0);
+    }
+
+    #[test]
+    fn test_read_file_range() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join("lib.rs"), "a\nb\nc").unwrap();
+        let cwd = temp.path().to_string_lossy().to_string();
+
+        let range = read_file_range(cwd.clone(), "lib.rs".to_string(), 2, 2).unwrap();
+        assert_eq!(range.path, "lib.rs");
+        assert_eq!(range.content, "b");
+        assert_eq!(range.total_lines, 3);
+        assert!(read_file_range(cwd, "../lib.rs".to_string(), 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_read_workspace_file_non_utf8() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join("latin1.txt"), b"caf\xe9").unwrap();
+
+        let file = read_workspace_file_internal(temp.path(), "latin1.txt").unwrap();
+        assert!(!file.is_utf8);
+        assert_eq!(file.content, "caf\u{FFFD}");
+        assert_eq!(file.size, 4);
+    }
+
+    #[test]
+    fn test_read_workspace_file_rejects_traversal() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("root");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+
+        let err = read_workspace_file_internal(&root, "../secret.txt").unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        let secret = temp.path().join("secret.txt").to_string_lossy().to_string();
+        assert_eq!(
+            read_workspace_file_internal(&root, &secret)
+                .unwrap_err()
+                .code(),
+            "INVALID_INPUT"
+        );
+        assert_eq!(
+            read_workspace_file_internal(&root, ".").unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+        assert_eq!(
+            read_workspace_file_internal(&root, "missing.txt")
+                .unwrap_err()
+                .code(),
+            "NOT_FOUND"
+        );
+    }
+
+    #[test]
+    fn test_write_workspace_file_atomic() {
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let result =
+            write_workspace_file_internal(temp.path(), "src/nested/new.ts", "export {};\n")
+                .unwrap();
+        assert_eq!(result.bytes_written, 11);
+        assert_eq!(
+            fs::read_to_string(temp.path().join("src/nested/new.ts")).unwrap(),
+            "export {};\n"
+        );
+
+        // Overwrite replaces the whole file and leaves no temp files behind
+        write_workspace_file_internal(temp.path(), "src/nested/new.ts", "x").unwrap();
+        assert_eq!(
+            fs::read_to_string(temp.path().join("src/nested/new.ts")).unwrap(),
+            "x"
+        );
+        assert_eq!(
+            fs::read_dir(temp.path().join("src/nested"))
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_write_workspace_file_rejects_traversal_and_protected_dirs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("root");
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let code = |path: &str| {
+            write_workspace_file_internal(&root, path, "x")
+                .unwrap_err()
+                .code()
+        };
+        assert_eq!(code("../escape.txt"), "INVALID_INPUT");
+        assert_eq!(code("src/../../escape.txt"), "INVALID_INPUT");
+        assert_eq!(
+            code(&temp.path().join("abs.txt").to_string_lossy()),
+            "INVALID_INPUT"
+        );
+        assert_eq!(code(".git/config"), "PERMISSION_DENIED");
+        assert_eq!(code(".specstudio/specs/a.md"), "PERMISSION_DENIED");
+        assert_eq!(code("src"), "INVALID_INPUT");
+        assert!(!temp.path().join("escape.txt").exists());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp.path(), root.join("link")).unwrap();
+            assert_eq!(code("link/escape.txt"), "INVALID_INPUT");
+            assert!(!temp.path().join("escape.txt").exists());
+        }
+    }
+
+    #[test]
+    fn test_archive_spec_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        let archive_dir = temp_dir.path().join(ARCHIVE_DIR);
+        fs::create_dir_all(specs_dir.join("billing")).unwrap();
+        fs::write(specs_dir.join("billing/20260201-refunds.md"), "# Refunds\n").unwrap();
+        fs::write(specs_dir.join("billing/20260201-refunds.plan.json"), "{}").unwrap();
+        fs::write(specs_dir.join("20260202-keep.md"), "# Keep\n").unwrap();
+
+        let filename = "billing/20260201-refunds.md".to_string();
+        archive_spec(filename.clone(), Some(cwd.clone())).unwrap();
+
+        assert!(archive_dir.join("billing/20260201-refunds.md").exists());
+        assert!(archive_dir
+            .join("billing/20260201-refunds.plan.json")
+            .exists());
+        assert!(!specs_dir
+            .join("billing/20260201-refunds.plan.json")
+            .exists());
+
+        let active = list_specs_internal(temp_dir.path(), false).unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].filename, "20260202-keep.md");
+
+        let archived = list_archived_specs_internal(temp_dir.path(), false).unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].filename, filename);
+        assert_eq!(archived[0].title, "Refunds");
+
+        // Archiving again fails; unarchiving restores spec and plan
+        assert_eq!(
+            archive_spec(filename.clone(), Some(cwd.clone()))
+                .unwrap_err()
+                .code(),
+            "NOT_FOUND"
+        );
+        unarchive_spec(filename.clone(), Some(cwd.clone())).unwrap();
+        assert!(specs_dir.join("billing/20260201-refunds.md").exists());
+        assert!(specs_dir
+            .join("billing/20260201-refunds.plan.json")
+            .exists());
+        assert!(list_archived_specs_internal(temp_dir.path(), false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            list_specs_internal(temp_dir.path(), false).unwrap().len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_archive_spec_refuses_to_overwrite() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+        fs::create_dir_all(temp_dir.path().join(SPECS_DIR)).unwrap();
+        fs::create_dir_all(temp_dir.path().join(ARCHIVE_DIR)).unwrap();
+        fs::write(temp_dir.path().join(SPECS_DIR).join("a.md"), "new").unwrap();
+        fs::write(temp_dir.path().join(ARCHIVE_DIR).join("a.md"), "old").unwrap();
 
-        // Test relative path (not allowed)
-        let result = validate_workspace("./relative/path".to_string());
+        let err = archive_spec("a.md".to_string(), Some(cwd)).unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join(ARCHIVE_DIR).join("a.md")).unwrap(),
+            "old"
+        );
+    }
+
+    #[test]
+    fn test_clear_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chats_dir = temp_dir.path().join(CHATS_DIR);
+        fs::create_dir_all(chats_dir.join("archived")).unwrap();
+        fs::write(chats_dir.join("chat_1.json"), "{}").unwrap();
+        fs::write(chats_dir.join("chat_2.json"), "{}").unwrap();
+        fs::write(chats_dir.join("archived/chat_0.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("keep.txt"), "keep").unwrap();
+
+        assert_eq!(clear_directory(&chats_dir).unwrap(), 3);
+        assert!(chats_dir.exists());
+        assert_eq!(fs::read_dir(&chats_dir).unwrap().count(), 0);
+        assert!(temp_dir.path().join("keep.txt").exists());
+
+        // Clearing again (or a missing directory) is a no-op
+        assert_eq!(clear_directory(&chats_dir).unwrap(), 0);
+        assert_eq!(
+            clear_directory(&temp_dir.path().join("missing")).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_extract_frontmatter_value() {
+        let content = "---\nsub_path: \"packages/api\"\nowner: web\n---\n# Title\n";
+        assert_eq!(
+            extract_frontmatter_value(content, "sub_path"),
+            Some("packages/api".to_string())
+        );
+        assert_eq!(extract_frontmatter_value(content, "missing"), None);
+        // Keys after the closing fence or without frontmatter are ignored
+        assert_eq!(
+            extract_frontmatter_value("# Title\nsub_path: x\n", "sub_path"),
+            None
+        );
+        assert_eq!(
+            extract_frontmatter_value("---\n---\nsub_path: x\n", "sub_path"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_spec_working_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("packages/api")).unwrap();
+        fs::write(root.join("packages/README.md"), "docs").unwrap();
+        let canonical_root = root.canonicalize().unwrap();
+
+        assert_eq!(
+            resolve_spec_working_directory(root, "# No frontmatter").unwrap(),
+            root
+        );
+        assert_eq!(
+            resolve_spec_working_directory(root, "---\nsub_path: packages/api\n---\n").unwrap(),
+            canonical_root.join("packages/api")
+        );
+
+        for sub_path in [
+            "../outside",
+            "packages/../../outside",
+            "/etc",
+            "packages/README.md",
+        ] {
+            let spec = format!("---\nsub_path: {}\n---\n", sub_path);
+            assert!(
+                resolve_spec_working_directory(root, &spec).is_err(),
+                "{} should be rejected",
+                sub_path
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_workspace_context_symlink_policy() {
+        use std::os::unix::fs::symlink;
+
+        let outside = tempfile::TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        symlink(outside.path(), root.join("outside")).unwrap();
+        symlink(outside.path().join("secret.txt"), root.join("secret.txt")).unwrap();
+        symlink(root.join("src"), root.join("src-link")).unwrap();
+        symlink(root, root.join("src/loop")).unwrap();
+
+        let paths = |follow_symlinks: bool| -> Vec<String> {
+            let context = read_workspace_context_internal(
+                root,
+                &[],
+                &[],
+                follow_symlinks,
+                None,
+                None,
+                &mut |_| {},
+            )
+            .unwrap();
+            let mut paths: Vec<String> = context.files.into_iter().map(|f| f.path).collect();
+            paths.sort();
+            paths
+        };
+
+        // Links are skipped by default
+        assert_eq!(paths(false), vec!["src/main.rs"]);
+
+        // Opting in follows links inside the workspace once; escapes are still excluded
+        let followed = paths(true);
+        assert_eq!(followed.len(), 1);
+        assert!(followed.iter().all(|p| !p.contains("secret")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_spec_paths_symlink_cycle() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("billing")).unwrap();
+        fs::write(root.join("20260101-top.md"), "# Top").unwrap();
+        fs::write(root.join("billing/20260102-refunds.md"), "# Refunds").unwrap();
+        symlink(root, root.join("billing/loop")).unwrap();
+        symlink(root.join("20260101-top.md"), root.join("20260103-link.md")).unwrap();
+
+        let names = |follow_symlinks: bool| -> Vec<String> {
+            let mut paths = Vec::new();
+            collect_spec_paths(root, follow_symlinks, &mut paths).unwrap();
+            let mut names: Vec<String> = paths
+                .iter()
+                .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Links are skipped by default; following them still terminates on the cycle
+        assert_eq!(
+            names(false),
+            vec!["20260101-top.md", "billing/20260102-refunds.md"]
+        );
+        assert_eq!(
+            names(true),
+            vec![
+                "20260101-top.md",
+                "20260103-link.md",
+                "billing/20260102-refunds.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_cache_reuses_unmodified_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.txt"), "alpha").unwrap();
+        fs::write(root.join("b.txt"), "bravo").unwrap();
+        let cache = ContextCache::new();
+
+        let read = || {
+            read_workspace_context_internal(root, &[], &[], false, Some(&cache), None, &mut |_| {})
+                .unwrap()
+        };
+        let content = |context: &WorkspaceContext, path: &str| {
+            context
+                .files
+                .iter()
+                .find(|f| f.path == path)
+                .unwrap()
+                .content
+                .clone()
+        };
+
+        assert_eq!(read().cached_files, 0);
+        assert_eq!(read().cached_files, 2);
+
+        // Same size and mtime: the cached content is served without re-reading
+        let a_path = root.join("a.txt");
+        let modified = fs::metadata(&a_path).unwrap().modified().unwrap();
+        fs::write(&a_path, "ALPHA").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&a_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let context = read();
+        assert_eq!(context.cached_files, 2);
+        assert_eq!(content(&context, "a.txt"), "alpha");
+
+        // A size change invalidates the entry
+        fs::write(root.join("b.txt"), "bravo two").unwrap();
+        let context = read();
+        assert_eq!(context.cached_files, 1);
+        assert_eq!(content(&context, "b.txt"), "bravo two");
+
+        // Deleted files are dropped from the cache on the next complete walk
+        fs::remove_file(root.join("b.txt")).unwrap();
+        assert_eq!(read().cached_files, 1);
+
+        assert_eq!(cache.clear(), 1);
+        assert_eq!(read().cached_files, 0);
+    }
+
+    #[test]
+    fn test_context_cache_respects_capacity() {
+        let cache = ContextCache::with_capacity(8);
+        let modified = SystemTime::now();
+
+        // Files larger than the whole cache are never stored
+        cache.insert(PathBuf::from("/big"), modified, 9, "123456789");
+        assert!(cache.get(Path::new("/big"), modified, 9).is_none());
+
+        cache.insert(PathBuf::from("/a"), modified, 3, "abc");
+        cache.insert(PathBuf::from("/b"), modified, 3, "def");
+        assert!(cache.get(Path::new("/a"), modified, 3).is_some());
+
+        // Full: the least recently used entry (/b) makes room
+        cache.insert(PathBuf::from("/c"), modified, 3, "ghi");
+        assert!(cache.get(Path::new("/a"), modified, 3).is_some());
+        assert!(cache.get(Path::new("/b"), modified, 3).is_none());
+        assert!(cache.get(Path::new("/c"), modified, 3).is_some());
+
+        // Replacing an entry frees its old size first
+        cache.insert(PathBuf::from("/a"), modified, 5, "abcde");
+        assert_eq!(
+            cache.get(Path::new("/a"), modified, 5).as_deref(),
+            Some("abcde")
+        );
+        assert!(cache.get(Path::new("/a"), modified, 3).is_none());
+        assert!(cache.get(Path::new("/c"), modified, 3).is_some());
+    }
+
+    #[test]
+    fn test_list_specs_with_plans() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("20260101-alpha.md"), "# Alpha").unwrap();
+        fs::write(specs_dir.join("20260102-beta.md"), "# Beta").unwrap();
+        fs::write(specs_dir.join("20260103-gamma.md"), "# Gamma").unwrap();
+        fs::write(
+            specs_dir.join("20260101-alpha.plan.json"),
+            r#"{ "title": "Alpha plan", "phases": [
+                { "title": "One", "tickets": [{ "id": "1" }, { "id": "2" }] },
+                { "title": "Two", "tickets": [{ "id": "3" }] }
+            ] }"#,
+        )
+        .unwrap();
+        fs::write(specs_dir.join("20260102-beta.plan.json"), "{ not json").unwrap();
+
+        let specs = specs_with_plans(temp_dir.path(), false).unwrap();
+        let by_name = |name: &str| specs.iter().find(|s| s.spec.filename == name).unwrap();
+
+        let alpha = by_name("20260101-alpha.md");
+        assert!(alpha.has_plan);
+        assert_eq!(
+            alpha.plan,
+            Some(PlanSummary {
+                title: Some("Alpha plan".to_string()),
+                phase_count: 2,
+                ticket_count: 3,
+            })
+        );
+
+        let beta = by_name("20260102-beta.md");
+        assert!(beta.has_plan);
+        assert!(beta.plan.is_none());
+        assert!(beta
+            .plan_error
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid plan JSON"));
+
+        let gamma = by_name("20260103-gamma.md");
+        assert!(!gamma.has_plan);
+        assert!(gamma.plan_error.is_none());
+    }
+
+    #[test]
+    fn test_gzipped_spec_round_trip() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("20260102-plain.md"), "# Plain").unwrap();
+
+        let content = "# Archived Feature\n\nLots of detail.\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        fs::write(
+            specs_dir.join("20260101-archived.md.gz"),
+            encoder.finish().unwrap(),
+        )
+        .unwrap();
+
+        let specs = list_specs_internal(temp_dir.path(), false).unwrap();
+        let archived = specs
+            .iter()
+            .find(|s| s.filename == "20260101-archived.md.gz")
+            .unwrap();
+        assert_eq!(archived.title, "Archived Feature");
+        assert_eq!(archived.created_at, "2026-01-01");
+        assert_eq!(archived.line_count, 3);
+        assert_eq!(specs.len(), 2);
+
+        let read = read_spec(
+            "20260101-archived.md.gz".to_string(),
+            Some(temp_dir.path().to_string_lossy().to_string()),
+        )
+        .unwrap();
+        assert_eq!(read.content, content);
+
+        assert_eq!(
+            companion_plan_filename("20260101-archived.md.gz"),
+            "20260101-archived.plan.json"
+        );
+        assert_eq!(
+            companion_plan_filename("team/20260101-a.md"),
+            "team/20260101-a.plan.json"
+        );
+    }
+
+    fn error_paths(content: &str) -> Vec<String> {
+        plan_validation_errors(content)
+            .into_iter()
+            .map(|e| e.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_validation_accepts_valid_plan() {
+        let plan = r#"{
+            "title": "Plan", "overview": "Overview",
+            "phases": [{
+                "title": "Phase 1", "description": "First",
+                "tickets": [
+                    { "id": "SPEC-001", "title": "A", "requirements": [], "acceptance_criteria": ["done"] },
+                    { "id": "SPEC-002", "title": "B", "requirements": ["x"], "acceptance_criteria": [] }
+                ]
+            }]
+        }"#;
+        assert_eq!(plan_validation_errors(plan), Vec::new());
+    }
+
+    #[test]
+    fn test_plan_validation_reports_malformed_plans() {
+        assert_eq!(error_paths("{ \"title\": "), vec![""]);
+        assert!(plan_validation_errors("[]")[0]
+            .message
+            .contains("Expected object"));
+
+        // Missing top-level fields and a wrongly typed ticket field
+        let plan = r#"{
+            "title": "Plan",
+            "phases": [{
+                "title": "Phase 1", "description": "First",
+                "tickets": [{ "id": "SPEC-001", "title": 7, "requirements": [] }]
+            }]
+        }"#;
+        assert_eq!(
+            error_paths(plan),
+            vec![
+                "overview",
+                "phases[0].tickets[0].acceptance_criteria",
+                "phases[0].tickets[0].title",
+            ]
+        );
+
+        // Empty phases
+        let plan = r#"{ "title": "Plan", "overview": "O", "phases": [] }"#;
+        assert_eq!(
+            plan_validation_errors(plan)[0].message,
+            "Plan has no phases"
+        );
+
+        // Empty phase and duplicate ticket ids across phases
+        let ticket =
+            r#"{ "id": "SPEC-001", "title": "A", "requirements": [], "acceptance_criteria": [] }"#;
+        let plan = format!(
+            r#"{{ "title": "Plan", "overview": "O", "phases": [
+                {{ "title": "1", "description": "d", "tickets": [{ticket}] }},
+                {{ "title": "2", "description": "d", "tickets": [] }},
+                {{ "title": "3", "description": "d", "tickets": [{ticket}] }}
+            ] }}"#
+        );
+        let errors = plan_validation_errors(&plan);
+        assert_eq!(
+            errors.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            vec!["phases[1].tickets", "phases[2].tickets[0].id"]
+        );
+        assert!(errors[1]
+            .message
+            .contains("first used at phases[0].tickets[0].id"));
+    }
+
+    #[test]
+    fn test_validate_plan_reads_companion_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        let working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+
+        assert!(matches!(
+            validate_plan("20260101-a.md".to_string(), working_directory.clone()),
+            Err(AppError::NotFound(_))
+        ));
+
+        fs::write(
+            specs_dir.join("20260101-a.plan.json"),
+            r#"{ "title": "Plan" }"#,
+        )
+        .unwrap();
+        let result = validate_plan("20260101-a.md".to_string(), working_directory).unwrap();
+        assert_eq!(result.filename, "20260101-a.plan.json");
         assert!(!result.valid);
-        assert!(result.error.as_ref().unwrap().contains("must be absolute"));
+        assert_eq!(result.errors.len(), 2);
+    }
 
-        // Test that FORBIDDEN_PATHS list is not empty and contains expected paths
-        assert!(FORBIDDEN_PATHS.contains(&"/etc"));
-        assert!(FORBIDDEN_PATHS.contains(&"/usr"));
+    #[test]
+    fn test_renumber_tickets_unique_and_ordered() {
+        let mut plan = serde_json::json!({
+            "title": "Plan",
+            "phases": [
+                { "title": "1", "tickets": [
+                    { "id": "SPEC-001", "title": "A", "notes": "kept" },
+                    { "id": "SPEC-001", "title": "B" }
+                ] },
+                { "title": "2", "tickets": [] },
+                { "title": "3", "tickets": [{ "title": "C" }, { "id": "X-9", "title": "D" }] }
+            ]
+        });
+
+        let changes = renumber_tickets(&mut plan, "AUTH").unwrap();
+        let new_ids: Vec<&str> = changes.iter().map(|c| c.new_id.as_str()).collect();
+        assert_eq!(
+            new_ids,
+            vec!["AUTH-001", "AUTH-002", "AUTH-003", "AUTH-004"]
+        );
+        assert_eq!(changes[1].old_id.as_deref(), Some("SPEC-001"));
+        assert_eq!(changes[2].old_id, None);
+        assert_eq!((changes[3].phase_index, changes[3].ticket_index), (2, 1));
+
+        // Ids are written back in plan order, other fields untouched
+        assert_eq!(plan["phases"][0]["tickets"][1]["id"], "AUTH-002");
+        assert_eq!(plan["phases"][2]["tickets"][0]["id"], "AUTH-003");
+        assert_eq!(plan["phases"][0]["tickets"][0]["notes"], "kept");
+        assert_eq!(plan["phases"][2]["tickets"][1]["title"], "D");
+
+        // Renumbering again is stable
+        let again = renumber_tickets(&mut plan, "AUTH").unwrap();
+        assert!(again
+            .iter()
+            .all(|c| c.old_id.as_deref() == Some(c.new_id.as_str())));
+
+        assert!(renumber_tickets(&mut serde_json::json!({ "title": "x" }), "AUTH").is_err());
     }
 
     #[test]
-    fn test_excluded_dirs_contains_specstudio() {
-        // CRITICAL: Ensure .specstudio is excluded to prevent AI from reading its own plans
-        assert!(EXCLUDED_DIRS.contains(&".specstudio"));
+    fn test_renumber_plan_tickets_rewrites_companion_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        let working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        let plan_path = specs_dir.join("20260101-a.plan.json");
+        fs::write(
+            &plan_path,
+            r#"{ "title": "Plan", "overview": "O", "phases": [
+                { "title": "1", "description": "d", "tickets": [{ "id": "SPEC-001" }, { "id": "SPEC-001" }] }
+            ] }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            renumber_plan_tickets(
+                "20260101-a.md".to_string(),
+                working_directory.clone(),
+                " ".to_string()
+            ),
+            Err(AppError::InvalidInput(_))
+        ));
+
+        let result = renumber_plan_tickets(
+            "20260101-a.md".to_string(),
+            working_directory,
+            "PAY-".to_string(),
+        )
+        .unwrap();
+        assert_eq!(result.filename, "20260101-a.plan.json");
+        assert_eq!(result.changes.len(), 2);
+
+        let plan: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&plan_path).unwrap()).unwrap();
+        assert_eq!(plan["phases"][0]["tickets"][0]["id"], "PAY-001");
+        assert_eq!(plan["phases"][0]["tickets"][1]["id"], "PAY-002");
+        assert_eq!(plan["overview"], "O");
     }
 
     #[test]
-    fn test_max_limits() {
-        // Ensure reasonable limits are set
-        assert_eq!(MAX_FILE_SIZE, 1024 * 1024); // 1MB
-        assert_eq!(MAX_TOTAL_SIZE, 5 * 1024 * 1024); // 5MB
+    fn test_merge_spec_contents_normalizes_headings() {
+        let auth = "---\nsub_path: apps/web\n---\n# Auth\n\nLogin flow.\n\n## Requirements\n- SSO\n\n### Edge cases\nNone.\n";
+        let billing = "Intro without a title.\n\n#### Deep heading\n```md\n# not a heading\n```\n";
+        let merged = merge_spec_contents(&[
+            ("Auth".to_string(), auth.to_string()),
+            ("Billing".to_string(), billing.to_string()),
+        ]);
+
+        assert_eq!(
+            merged,
+            "---\nsub_path: apps/web\n---\n# Auth & Billing\n\
+             \n## Auth\n\nLogin flow.\n\n### Requirements\n- SSO\n\n#### Edge cases\nNone.\n\
+             \n## Billing\n\nIntro without a title.\n\n### Deep heading\n```md\n# not a heading\n```\n"
+        );
+        assert_eq!(
+            extract_first_heading(&merged),
+            Some("Auth & Billing".to_string())
+        );
+        assert_eq!(
+            extract_frontmatter_value(&merged, "sub_path"),
+            Some("apps/web".to_string())
+        );
     }
 
     #[test]
-    fn test_specs_dir_constant() {
-        assert_eq!(SPECS_DIR, ".specstudio/specs");
+    fn test_merge_specs_writes_target_and_archives_sources() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        let working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+        fs::write(specs_dir.join("20260101-a.md"), "# Alpha\n\nA body\n").unwrap();
+        fs::write(specs_dir.join("20260102-b.md"), "# Beta\n\nB body\n").unwrap();
+        let sources = vec!["20260101-a.md".to_string(), "20260102-b.md".to_string()];
+
+        assert!(merge_specs(
+            sources[..1].to_vec(),
+            "20260103-ab.md".to_string(),
+            working_directory.clone(),
+            None
+        )
+        .is_err());
+        assert!(merge_specs(
+            sources.clone(),
+            "20260101-a.md".to_string(),
+            working_directory.clone(),
+            None
+        )
+        .is_err());
+
+        // Duplicate sources are rejected before anything is written
+        let duplicate = merge_specs(
+            vec!["20260101-a.md".to_string(), "20260101-a.md".to_string()],
+            "20260103-ab.md".to_string(),
+            working_directory.clone(),
+            Some(true),
+        );
+        assert_eq!(duplicate.unwrap_err().code(), "INVALID_INPUT");
+        assert!(!specs_dir.join("20260103-ab.md").exists());
+        assert!(specs_dir.join("20260101-a.md").exists());
+
+        merge_specs(
+            sources,
+            "20260103-ab.md".to_string(),
+            working_directory,
+            Some(true),
+        )
+        .unwrap();
+        let merged = fs::read_to_string(specs_dir.join("20260103-ab.md")).unwrap();
+        assert!(merged.starts_with("# Alpha & Beta\n\n## Alpha\n\nA body\n\n## Beta\n"));
+        assert!(!specs_dir.join("20260101-a.md").exists());
+        assert!(temp_dir
+            .path()
+            .join(ARCHIVE_DIR)
+            .join("20260102-b.md")
+            .exists());
+    }
+
+    #[test]
+    fn test_append_to_spec() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        let working_directory = Some(temp_dir.path().to_string_lossy().to_string());
+
+        // Missing spec (and specs dir) is created with just the content
+        append_to_spec(
+            "team/20260101-new.md".to_string(),
+            "# New\n".to_string(),
+            working_directory.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(specs_dir.join("team/20260101-new.md")).unwrap(),
+            "# New\n"
+        );
+
+        fs::write(specs_dir.join("20260101-a.md"), "# A\n\nBody\n\n").unwrap();
+        append_to_spec(
+            "20260101-a.md".to_string(),
+            "Assistant reply".to_string(),
+            working_directory.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(specs_dir.join("20260101-a.md")).unwrap(),
+            "# A\n\nBody\n\n---\n\nAssistant reply"
+        );
+
+        assert!(matches!(
+            append_to_spec(
+                "../escape.md".to_string(),
+                "x".to_string(),
+                working_directory
+            ),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_text() {
+        let original = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+        let modified = "one\nTWO\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n";
+
+        let result = diff_text(original.to_string(), modified.to_string(), Some(1));
+        assert_eq!((result.insertions, result.deletions), (2, 1));
+        assert_eq!(result.hunks, 2);
+        assert_eq!(
+            result.diff,
+            "--- a\n+++ b\n@@ -1,3 +1,3 @@\n one\n-two\n+TWO\n three\n@@ -10 +10,2 @@\n ten\n+eleven\n"
+        );
+
+        // Wider context joins both changes into one hunk
+        let result = diff_text(original.to_string(), modified.to_string(), None);
+        assert_eq!(result.hunks, 2);
+        let result = diff_text(original.to_string(), modified.to_string(), Some(4));
+        assert_eq!(result.hunks, 1);
+
+        let result = diff_text(original.to_string(), original.to_string(), None);
+        assert_eq!(
+            (result.insertions, result.deletions, result.hunks),
+            (0, 0, 0)
+        );
+        assert!(result.diff.is_empty());
+    }
+
+    #[test]
+    fn test_read_workspace_context_cancellation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("README.md"), "# Readme").unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let context = read_workspace_context_internal(
+            temp_dir.path(),
+            &[],
+            &[],
+            false,
+            None,
+            Some(&cancel),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(context.total_files, 2);
+        assert!(!context.cancelled);
+
+        cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+        let context = read_workspace_context_internal(
+            temp_dir.path(),
+            &[],
+            &[],
+            false,
+            None,
+            Some(&cancel),
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(context.total_files, 0);
+        assert!(context.cancelled);
+    }
+
+    #[test]
+    fn test_check_workspace_creates_nothing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("new/project");
+
+        let check = check_workspace(missing.to_string_lossy().to_string());
+        assert!(check.valid);
+        assert!(!check.exists && !check.is_dir);
+        assert!(!missing.exists());
+        assert!(!temp_dir.path().join("new").exists());
+
+        let check = check_workspace(temp_dir.path().to_string_lossy().to_string());
+        assert!(check.valid && check.exists && check.is_dir);
+
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "x").unwrap();
+        let check = check_workspace(file.to_string_lossy().to_string());
+        assert!(!check.valid);
+        assert!(check.exists && !check.is_dir);
+
+        // Same validation as validate_workspace
+        assert!(!check_workspace("relative/path".to_string()).valid);
+        let check = check_workspace("/etc/specstudio-test".to_string());
+        assert_eq!(
+            check.error.as_deref(),
+            Some("Cannot use system directories as workspace")
+        );
+        assert!(!Path::new("/etc/specstudio-test").exists());
+
+        // The creating variant still creates
+        assert!(validate_workspace(missing.to_string_lossy().to_string()).created == Some(true));
+        assert!(missing.is_dir());
+    }
+
+    #[test]
+    fn test_is_forbidden_path_unix() {
+        assert!(is_forbidden_path("/etc", false));
+        assert!(is_forbidden_path("/usr/local/share", false));
+        assert!(!is_forbidden_path("/etcetera", false));
+        assert!(!is_forbidden_path("/home/user/projects", false));
+        // Windows entries don't apply on Unix
+        assert!(!is_forbidden_path("C:\\Windows", false));
+    }
+
+    #[test]
+    fn test_is_forbidden_path_windows() {
+        assert!(is_forbidden_path("C:\\Windows", true));
+        assert!(is_forbidden_path("c:\\windows\\System32", true));
+        assert!(is_forbidden_path("C:/Program Files/App", true));
+        assert!(is_forbidden_path("\\\\?\\C:\\Program Files (x86)\\", true));
+        assert!(!is_forbidden_path("C:\\Windows.old", true));
+        assert!(!is_forbidden_path("C:\\Users\\me\\projects", true));
+        // Unix entries don't apply on Windows
+        assert!(!is_forbidden_path("/etc", true));
+    }
+
+    #[test]
+    fn test_workspace_warning_for_home_and_sync_roots() {
+        let home = Path::new("/home/user");
+        assert!(workspace_warning(home, Some(home))
+            .unwrap()
+            .contains("home directory"));
+        let dropbox = home.join("Dropbox");
+        assert!(workspace_warning(&dropbox, Some(home))
+            .unwrap()
+            .contains("Dropbox"));
+        assert!(workspace_warning(&dropbox.join("project"), Some(home)).is_none());
+        assert!(workspace_warning(&home.join("projects"), Some(home)).is_none());
+        assert!(workspace_warning(home, None).is_none());
+    }
+
+    #[test]
+    fn test_project_summary_staleness() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("lib.rs"), "").unwrap();
+        let file = |path: &str| FileContent {
+            path: path.to_string(),
+            content: String::new(),
+        };
+        let files = vec![file("main.rs"), file("lib.rs")];
+
+        assert!(read_project_summary(root).is_none());
+        write_project_summary(root, "# Summary", &files).unwrap();
+        let (content, written) = read_project_summary(root).unwrap();
+        assert_eq!(content, "# Summary");
+
+        // Unchanged since the summary was written
+        let set_mtime = |modified: SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(root.join("main.rs"))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+        set_mtime(written - Duration::from_secs(60));
+        fs::File::options()
+            .write(true)
+            .open(root.join("lib.rs"))
+            .unwrap()
+            .set_modified(written - Duration::from_secs(60))
+            .unwrap();
+        assert!(!is_summary_stale(root, written, &files));
+        assert!(!is_summary_stale(
+            root,
+            written,
+            &[file("lib.rs"), file("main.rs")]
+        ));
+
+        // A context file was deleted, or a new one added
+        assert!(is_summary_stale(root, written, &files[..1]));
+        assert!(is_summary_stale(root, written, &[]));
+        assert!(is_summary_stale(
+            root,
+            written,
+            &[file("main.rs"), file("lib.rs"), file("new.rs")]
+        ));
+
+        // Edited afterwards
+        set_mtime(written + Duration::from_secs(60));
+        assert!(is_summary_stale(root, written, &files));
+
+        // Deleted since the context was read
+        fs::remove_file(root.join("main.rs")).unwrap();
+        assert!(is_summary_stale(root, written, &files));
+    }
+
+    #[test]
+    fn test_spec_artifacts_omits_missing_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(specs_dir.join("billing")).unwrap();
+        fs::write(specs_dir.join("billing/20260101-refunds.md"), "# Refunds").unwrap();
+        fs::write(specs_dir.join("billing/20260101-refunds.plan.json"), "{}").unwrap();
+
+        // Plan but no chat (and no project summary)
+        let result =
+            spec_artifacts("billing/20260101-refunds.md".to_string(), Some(cwd.clone())).unwrap();
+        assert_eq!(result.filename, "billing/20260101-refunds.md");
+        assert_eq!(result.artifacts.len(), 1);
+        let plan = &result.artifacts[0];
+        assert_eq!(plan.kind, "plan");
+        assert_eq!(
+            plan.path,
+            ".specstudio/specs/billing/20260101-refunds.plan.json"
+        );
+        assert_eq!(plan.size, 2);
+        assert!(plan.modified_at.is_some());
+
+        // A chat transcript shows up once it exists
+        let chats_dir = temp_dir.path().join(CHATS_DIR).join("billing");
+        fs::create_dir_all(&chats_dir).unwrap();
+        fs::write(chats_dir.join("20260101-refunds.json"), "[]").unwrap();
+        let result =
+            spec_artifacts("billing/20260101-refunds.md".to_string(), Some(cwd.clone())).unwrap();
+        let kinds: Vec<&str> = result.artifacts.iter().map(|a| a.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["plan", "chat"]);
+
+        let err = spec_artifacts("20260101-missing.md".to_string(), Some(cwd)).unwrap_err();
+        assert_eq!(err.code(), "NOT_FOUND");
+    }
+
+    #[test]
+    fn test_delete_specs_reports_each_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("20260101-a.md"), "# A").unwrap();
+        fs::write(specs_dir.join("20260101-a.plan.json"), "{}").unwrap();
+        fs::write(specs_dir.join("20260102-b.md"), "# B").unwrap();
+        fs::write(specs_dir.join("20260103-keep.md"), "# Keep").unwrap();
+
+        let result = delete_specs(
+            vec![
+                "20260101-a.md".to_string(),
+                "20260109-missing.md".to_string(),
+                "../../secrets.md".to_string(),
+                "20260102-b.md".to_string(),
+            ],
+            Some(cwd),
+        );
+
+        assert_eq!(result.deleted, 2);
+        assert_eq!(result.failed, 2);
+        let outcomes: Vec<(&str, bool)> = result
+            .results
+            .iter()
+            .map(|r| (r.filename.as_str(), r.success))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("20260101-a.md", true),
+                ("20260109-missing.md", false),
+                ("../../secrets.md", false),
+                ("20260102-b.md", true),
+            ]
+        );
+        assert!(result.results[1]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("not found"));
+        assert!(result.results[2]
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Invalid spec filename"));
+
+        // Companion plans go with their specs; untouched specs stay
+        assert!(!specs_dir.join("20260101-a.md").exists());
+        assert!(!specs_dir.join("20260101-a.plan.json").exists());
+        assert!(!specs_dir.join("20260102-b.md").exists());
+        assert!(specs_dir.join("20260103-keep.md").exists());
+    }
+
+    #[test]
+    fn test_export_specs_markdown_bundle() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let specs_dir = root.join(SPECS_DIR);
+        fs::create_dir_all(specs_dir.join("team")).unwrap();
+        fs::write(
+            specs_dir.join("20260101-alpha.md"),
+            "# Alpha\n\nFirst spec\n",
+        )
+        .unwrap();
+        fs::write(specs_dir.join("team/20260102-beta.md"), "No heading here").unwrap();
+        fs::create_dir_all(root.join(ARCHIVE_DIR)).unwrap();
+        fs::write(root.join(ARCHIVE_DIR).join("20250101-old.md"), "# Old\n").unwrap();
+
+        let export = export_specs_internal(root, "markdown", false, false, "1000").unwrap();
+        assert_eq!(export.spec_count, 2);
+        assert!(export.path.ends_with("specs-1000.md"));
+        let bundle = fs::read_to_string(&export.path).unwrap();
+        assert_eq!(
+            bundle,
+            "# Specs\n\n## Contents\n\n\
+             1. [Alpha](#spec-1) — `20260101-alpha.md`\n\
+             2. [20260102-beta](#spec-2) — `team/20260102-beta.md`\n\
+             \n\n---\n\n<a id=\"spec-1\"></a>\n\n# Alpha\n\nFirst spec\n\
+             \n\n---\n\n<a id=\"spec-2\"></a>\n\nNo heading here\n"
+        );
+
+        // Archived specs only on request, and flagged in the contents
+        let export = export_specs_internal(root, "markdown", true, false, "2000").unwrap();
+        assert_eq!(export.spec_count, 3);
+        let bundle = fs::read_to_string(&export.path).unwrap();
+        assert!(bundle.contains("3. [Old](#spec-3) — `20250101-old.md` (archived)\n"));
+
+        // Zip keeps the files as stored, with companion plans
+        fs::write(specs_dir.join("20260101-alpha.plan.json"), "{}").unwrap();
+        let export = export_specs_internal(root, "zip", false, false, "3000").unwrap();
+        assert_eq!(export.spec_count, 2);
+        let archive = zip::ZipArchive::new(fs::File::open(&export.path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "specs/20260101-alpha.md",
+                "specs/20260101-alpha.plan.json",
+                "specs/team/20260102-beta.md",
+            ]
+        );
+
+        let invalid = export_specs_internal(root, "pdf", false, false, "4000").unwrap_err();
+        assert_eq!(invalid.code(), "INVALID_INPUT");
+    }
+
+    #[test]
+    fn test_import_specs_from_directory() {
+        let source = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(source.path().join("guides")).unwrap();
+        fs::create_dir_all(source.path().join(".hidden")).unwrap();
+        fs::write(source.path().join("20260101-alpha.md"), "# Alpha").unwrap();
+        fs::write(source.path().join("guides/Getting Started.md"), "# Start").unwrap();
+        fs::write(source.path().join("guides/binary.md"), [0xff, 0xfe]).unwrap();
+        fs::write(source.path().join(".hidden/secret.md"), "# Hidden").unwrap();
+        fs::write(source.path().join("notes.txt"), "not a spec").unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let specs_dir = temp_dir.path().join(SPECS_DIR);
+        fs::create_dir_all(&specs_dir).unwrap();
+        fs::write(specs_dir.join("20260101-alpha.md"), "# Existing").unwrap();
+
+        let result =
+            import_specs_internal(temp_dir.path(), source.path(), false, "20260315").unwrap();
+        assert_eq!(result.imported, 2);
+        assert_eq!(result.skipped, 1);
+
+        let outcome = |source: &str| {
+            result
+                .results
+                .iter()
+                .find(|r| Path::new(&r.source) == Path::new(source))
+                .unwrap()
+        };
+        // Collisions get a suffix instead of overwriting
+        assert_eq!(
+            outcome("20260101-alpha.md").filename.as_deref(),
+            Some("20260101-alpha-2.md")
+        );
+        assert_eq!(
+            fs::read_to_string(specs_dir.join("20260101-alpha.md")).unwrap(),
+            "# Existing"
+        );
+        // Other names are normalized to the date-prefixed convention
+        assert_eq!(
+            outcome("guides/Getting Started.md").filename.as_deref(),
+            Some("20260315-getting-started.md")
+        );
+        assert_eq!(
+            fs::read_to_string(specs_dir.join("20260315-getting-started.md")).unwrap(),
+            "# Start"
+        );
+        assert!(outcome("guides/binary.md").error.is_some());
+
+        // Zips written by export_specs import back, minus their plans
+        let export = export_specs_internal(temp_dir.path(), "zip", false, false, "1000").unwrap();
+        let other = tempfile::TempDir::new().unwrap();
+        let result =
+            import_specs_internal(other.path(), Path::new(&export.path), false, "20260315")
+                .unwrap();
+        assert_eq!(result.imported, 3);
+        assert!(other
+            .path()
+            .join(SPECS_DIR)
+            .join("20260101-alpha-2.md")
+            .exists());
+
+        let missing = import_specs_internal(
+            temp_dir.path(),
+            &source.path().join("nope"),
+            false,
+            "20260315",
+        );
+        assert_eq!(missing.unwrap_err().code(), "NOT_FOUND");
     }
 }
@@ -30,6 +30,7 @@ interface FileNode {
   isDir: boolean;
   children?: FileNode[];
   modified?: boolean;
+  contentHash?: string; // Only present when requested with includeHashes
 }
 
 interface FileTreeResult {
@@ -11,7 +11,7 @@ import { invoke } from '@tauri-apps/api/core'
 import { listen, UnlistenFn } from '@tauri-apps/api/event'
 import { useWorkspace } from '@/hooks/use-workspace'
 import { Workspace } from '@/hooks/use-workspace-target'
-import { RpcAction, StreamEvent } from '@/types'
+import { RpcAction, StoreRecoveredEvent, StreamEvent } from '@/types'
 import { SpecSidebar } from './spec-sidebar'
 import { ControlBar } from './control-bar'
 import { OutputConsole } from './output-console'
@@ -161,6 +161,21 @@ export function IdeLayout({
 		}
 	}, [])
 
+	// Report stores that were reset at startup because they were corrupt
+	useEffect(() => {
+		invoke<StoreRecoveredEvent[]>('take_store_recoveries')
+			.then((recoveries) => {
+				for (const { store, backupPath } of recoveries) {
+					appendConsoleOutput({
+						type: 'error',
+						data: `${store} was corrupt and has been reset; the old file was saved to ${backupPath}`,
+						timestamp: Date.now(),
+					})
+				}
+			})
+			.catch(() => {})
+	}, [appendConsoleOutput])
+
 	// Check git status for the current workspace
 	const checkGitStatus = useCallback(async () => {
 		if (!activeWorkspace) return
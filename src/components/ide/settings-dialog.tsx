@@ -172,7 +172,7 @@ export function SettingsDialog({
 
       // Clear backend stores (settings.json, auth.json)
       console.log('[factory_reset] Calling backend factory_reset command...');
-      await invoke('factory_reset');
+      await invoke('factory_reset', { confirmationToken: 'RESET' });
       console.log('[factory_reset] Backend stores cleared successfully');
 
       // Clear all localStorage keys
@@ -10,13 +10,15 @@
 import { useState, useEffect, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
-import { AuthStatus, AuthProvider } from '@/types';
+import { startAnthropicOAuth } from '@/lib/streams';
+import { AuthStatus, AuthProvider, StreamEvent } from '@/types';
 
 interface UseAuthReturn {
   status: AuthStatus;
   isLoading: boolean;
   isAuthenticated: boolean;
-  login: (provider: AuthProvider) => Promise<void>;
+  // onOutput receives the Claude CLI's output during an Anthropic login
+  login: (provider: AuthProvider, onOutput?: (event: StreamEvent) => void) => Promise<void>;
   logout: (provider: AuthProvider) => Promise<void>;
   checkAuth: () => Promise<void>;
   error: string | null;
@@ -59,13 +61,19 @@ export function useAuth(): UseAuthReturn {
     }
   }, []);
 
-  const login = useCallback(async (provider: AuthProvider) => {
+  const login = useCallback(async (
+    provider: AuthProvider,
+    onOutput?: (event: StreamEvent) => void
+  ) => {
     setIsLoading(true);
     setError(null);
 
     try {
-      const command = provider === 'google' ? 'start_google_oauth' : 'start_anthropic_oauth';
-      const result = await invoke<{ success: boolean; message: string }>(command);
+      type LoginResult = { success: boolean; message: string };
+      const result =
+        provider === 'google'
+          ? await invoke<LoginResult>('start_google_oauth')
+          : await startAnthropicOAuth<LoginResult>(onOutput ?? (() => {}));
 
       if (result.success) {
         setStatus(prev => ({
@@ -124,6 +132,8 @@ export function useAuth(): UseAuthReturn {
         } else if (authStatus === 'error') {
           setError(message);
           setIsLoading(false);
+        } else if (authStatus === 'cancelled') {
+          setIsLoading(false);
         } else if (authStatus === 'pending') {
           setIsLoading(true);
         }
@@ -14,6 +14,8 @@ import { RpcAction, RpcRequest, RpcResponse, ChatMessage, StreamEvent } from '@/
 interface SpawnResult {
   started: boolean;
   processId: string;
+  permissionMode?: 'plan' | 'skipPermissions' | 'prompt'; // claude actions only
+  permissionNote?: string;
 }
 
 interface UseRpcReturn {
@@ -80,6 +82,8 @@ export function useRpc(): UseRpcReturn {
         // Set up event listener for streaming data
         unlistenRef.current = await listen<StreamEvent>('rpc:stream:data', (event) => {
           const streamEvent = event.payload;
+          // Events of other streams (e.g. a git diff) share the channel
+          if (streamEvent.streamId) return;
           onEventRef.current?.(streamEvent);
 
           if (streamEvent.type === 'complete') {
@@ -96,6 +100,14 @@ export function useRpc(): UseRpcReturn {
           action,
           workingDirectory: payload.workingDirectory,
           specContent: payload.specContent,
+          scriptName: payload.scriptName,
+          planOnly: payload.planOnly,
+          options: {
+            color: payload.color,
+            term: payload.term,
+            initialStdin: payload.initialStdin,
+            maxOutputBytes: payload.maxOutputBytes,
+          },
         });
       } catch (err) {
         // Emit error event
@@ -157,6 +169,8 @@ interface WorkspaceContext {
   totalFiles: number;
   totalSize: number;
   truncated: boolean;
+  cachedFiles: number;
+  cancelled: boolean;
 }
 
 interface UseChatReturn {
@@ -231,6 +245,8 @@ export function useChat(): UseChatReturn {
         // Set up event listener for streaming data
         unlistenRef.current = await listen<StreamEvent>('rpc:stream:data', async (event) => {
           const streamEvent = event.payload;
+          // Events of other streams (e.g. a git diff) share the channel
+          if (streamEvent.streamId) return;
 
           if (streamEvent.type === 'output') {
             currentResponseRef.current += streamEvent.data;
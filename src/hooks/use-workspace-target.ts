@@ -25,6 +25,7 @@ interface ValidateResult {
   path?: string;
   error?: string;
   created?: boolean;
+  warning?: string;
 }
 
 interface UseWorkspaceTargetReturn {
@@ -9,6 +9,7 @@
 import { useState, useCallback, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Spec, StreamEvent, DevelopmentPlan } from '@/types';
+import { getErrorMessage } from '@/lib/utils';
 
 interface WorkspaceData {
   specs: Spec[];
@@ -22,6 +23,7 @@ interface SpecContent {
 
 interface SaveResult {
   success: boolean;
+  filename?: string;
 }
 
 interface UseWorkspaceReturn {
@@ -84,7 +86,7 @@ export function useWorkspace(targetWorkspace: string | null): UseWorkspaceReturn
       setSpecs(data.specs);
       setWorkingDirectory(data.workingDirectory);
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
     } finally {
       setIsLoading(false);
     }
@@ -157,7 +159,7 @@ export function useWorkspace(targetWorkspace: string | null): UseWorkspaceReturn
         // Plan state was already cleared at the start - no action needed
       }
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
       setSpecContentState('');
       // Don't clear plan state here - let UI retain state if spec load fails
     } finally {
@@ -188,7 +190,7 @@ export function useWorkspace(targetWorkspace: string | null): UseWorkspaceReturn
       // Refresh workspace to pick up the new/updated spec
       await refreshWorkspace();
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
       throw err;
     } finally {
       setIsSaving(false);
@@ -255,7 +257,7 @@ Add implementation details, constraints, or architectural decisions.
         await selectSpec(newSpec);
       }
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
       throw err;
     } finally {
       setIsSaving(false);
@@ -289,7 +291,7 @@ Add implementation details, constraints, or architectural decisions.
       // Refresh workspace to update the list
       await refreshWorkspace();
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
       throw err;
     } finally {
       setIsSaving(false);
@@ -355,7 +357,7 @@ Add implementation details, constraints, or architectural decisions.
         workingDirectory: targetWorkspace,
       });
     } catch (err) {
-      setError(err as string);
+      setError(getErrorMessage(err));
       throw err;
     } finally {
       setIsSaving(false);
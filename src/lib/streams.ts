@@ -0,0 +1,47 @@
+// ============================================================================
+// Auxiliary Streams
+// Commands like stream_git_diff and start_anthropic_oauth emit on the shared
+// rpc:stream:data channel, tagging every event with a streamId; listen only to
+// the matching events
+// ============================================================================
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { StreamEvent } from '@/types';
+
+// Stream `git diff` chunks to onEvent; the listener is removed after `complete`
+export async function streamGitDiff(
+  workingDirectory: string,
+  files: string[] | undefined,
+  onEvent: (event: StreamEvent) => void
+): Promise<void> {
+  // Chosen up front so no event is missed before invoke() resolves
+  const streamId = `diff_${Date.now()}_${Math.random().toString(36).slice(2)}`;
+  const unlisten = await listen<StreamEvent>('rpc:stream:data', (event) => {
+    if (event.payload.streamId !== streamId) return;
+    onEvent(event.payload);
+    if (event.payload.type === 'complete') unlisten();
+  });
+
+  try {
+    await invoke<string>('stream_git_diff', { workingDirectory, files, streamId });
+  } catch (err) {
+    unlisten();
+    throw err;
+  }
+}
+
+// Run `claude auth login`, forwarding its CLI output to onEvent
+// The command resolves once the CLI exits, after its last output event
+export async function startAnthropicOAuth<T>(onEvent: (event: StreamEvent) => void): Promise<T> {
+  const streamId = `auth_${Date.now()}_${Math.random().toString(36).slice(2)}`;
+  const unlisten = await listen<StreamEvent>('rpc:stream:data', (event) => {
+    if (event.payload.streamId === streamId) onEvent(event.payload);
+  });
+
+  try {
+    return await invoke<T>('start_anthropic_oauth', { streamId });
+  } finally {
+    unlisten();
+  }
+}
@@ -4,3 +4,12 @@ import { twMerge } from "tailwind-merge"
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
 }
+
+// Extract a display message from an invoke() rejection ({ code, message }, Error or string)
+export function getErrorMessage(err: unknown): string {
+  if (typeof err === "string") return err
+  if (err && typeof err === "object" && "message" in err) {
+    return String((err as { message: unknown }).message)
+  }
+  return String(err)
+}
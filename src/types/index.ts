@@ -15,6 +15,13 @@ export interface AuthResponse {
   message: string;
 }
 
+// Returned by get_google_identity
+export interface GoogleIdentity {
+  email: string | null;
+  name: string | null;
+  picture: string | null;
+}
+
 // RPC Action Types
 export type RpcAction =
   | 'chat'
@@ -23,11 +30,14 @@ export type RpcAction =
   | 'create_code'
   | 'gen_tests'
   | 'run_tests'
-  | 'run_app';
+  | 'run_app'
+  | 'run_script';
 
 export interface ChatMessage {
-  role: 'user' | 'assistant';
-  content: string;
+  role: 'user' | 'assistant' | 'tool_call' | 'tool_result';
+  content: string; // tool_call: `{ name, args }` JSON from the stream event; tool_result: tool output
+  toolName?: string;
+  toolArgs?: Record<string, unknown>;
 }
 
 export interface RpcRequest {
@@ -37,6 +47,12 @@ export interface RpcRequest {
     history?: ChatMessage[];
     specContent?: string;
     workingDirectory?: string;
+    scriptName?: string;
+    planOnly?: boolean; // create_code only: preview proposed changes without writing
+    color?: boolean; // Defaults to on for claude actions, off for package manager runs
+    term?: string; // TERM for the child process
+    initialStdin?: string; // Written to the process's stdin/PTY right after it starts
+    maxOutputBytes?: number; // Output forwarded before the rest is dropped (default 16MB)
   };
 }
 
@@ -48,18 +64,306 @@ export interface RpcResponse {
   stream?: boolean;
 }
 
+// Error Types
+export interface AppError {
+  code: string;
+  message: string;
+}
+
 // Spec Types
 export interface Spec {
   filename: string;
   title: string;
   createdAt: string;
+  wordCount: number;
+  lineCount: number;
+  charCount: number;
+  subPath: string | null; // `sub_path` from spec frontmatter
+}
+
+// Companion plan summary returned by list_specs_with_plans
+export interface PlanSummary {
+  title: string | null;
+  phaseCount: number;
+  ticketCount: number;
+}
+
+export interface SpecWithPlan {
+  spec: Spec;
+  hasPlan: boolean;
+  plan: PlanSummary | null;
+  planError?: string; // Set when the plan file exists but is malformed
+}
+
+export interface PlanValidationError {
+  path: string; // e.g. "phases[0].tickets[2].id"; empty for the whole document
+  message: string;
+}
+
+export interface PlanValidationResult {
+  filename: string;
+  valid: boolean;
+  errors: PlanValidationError[];
+}
+
+export interface TicketIdChange {
+  phaseIndex: number;
+  ticketIndex: number;
+  oldId: string | null; // null when the ticket had no id
+  newId: string;
+}
+
+export interface RenumberResult {
+  filename: string;
+  changes: TicketIdChange[];
+}
+
+export interface SpecDiff {
+  filename: string;
+  gitRef: string;
+  diff: string;
+  existedAtRef: boolean;
+  hasChanges: boolean;
+}
+
+export interface TextDiffResult {
+  diff: string; // Unified diff, empty when the texts are identical
+  insertions: number;
+  deletions: number;
+  hunks: number;
 }
 
 // Stream Event Types
 export interface StreamEvent {
-  type: 'output' | 'error' | 'complete' | 'input' | 'tool_call';
+  type:
+    | 'output'
+    | 'error'
+    | 'complete'
+    | 'input'
+    | 'tool_call'
+    | 'info'
+    | 'prompt'
+    | 'test_summary' // data is a JSON-encoded TestSummary
+    | 'truncated'; // output exceeded maxOutputBytes; later output is dropped
   data: string;
   timestamp: number;
+  sessionId?: string; // Set on Gemini chat events
+  processId?: string; // Set on shell process events
+  stream?: 'stdout' | 'stderr'; // Set on line-buffered package manager output
+  hint?: string; // Actionable suggestion on process startup failures
+  streamId?: string; // Set on auxiliary streams (stream_git_diff); not part of chats or processes
+}
+
+// Buffered process output from get_process_output, for catching up after a reload
+export interface ProcessOutput {
+  processId: string;
+  events: StreamEvent[];
+  firstSeq: number; // Sequence number of events[0]
+  nextSeq: number; // Pass as `since` to fetch only newer events
+  dropped: boolean; // Some requested events were already evicted
+  finished: boolean;
+}
+
+// Parsed from `run_tests` output when the runner (Jest, Vitest, cargo) is recognized
+export interface TestSummary {
+  framework: 'jest' | 'vitest' | 'cargo';
+  passed: number;
+  failed: number;
+  skipped: number;
+  duration: number | null; // seconds
+}
+
+// Payload of `context:progress` events emitted by read_workspace_context
+export interface ContextProgress {
+  filesScanned: number;
+  bytesCollected: number;
+  done: boolean;
+}
+
+// Active Gemini Chat Types (list_chats)
+export interface ChatInfo {
+  sessionId: string;
+  model: string;
+  startedAt: number; // Unix timestamp in milliseconds
+  promptTokens: number;
+  outputTokens: number;
+}
+
+// Settings Types (get_settings / update_settings)
+export interface AppSettings {
+  geminiApiKey?: string;
+  geminiModel?: string;
+  geminiModelFallbacks?: string[];
+  geminiStallTimeoutSecs?: number;
+  geminiRequestsPerMinute?: number;
+  googleClientId?: string;
+  googleClientSecret?: string;
+  packageManager?: 'npm' | 'pnpm' | 'yarn' | 'bun';
+  extraExcludedDirs?: string[];
+  extraExcludedExtensions?: string[];
+  followSymlinks?: boolean;
+  geminiSafetySettings?: Record<string, string>; // e.g. { HARM_CATEGORY_HARASSMENT: 'BLOCK_ONLY_HIGH' }
+  googleScopes?: string[]; // OAuth scopes; defaults to cloud-platform
+  safeMode?: boolean; // claude asks before acting instead of --dangerously-skip-permissions
+  httpTimeoutSecs?: number; // Overall timeout for non-streaming requests (default 30)
+  modelPrices?: Record<string, number>; // USD per million input tokens, keyed by model
+  searchThreads?: number; // Content search threads (default half the available cores)
+}
+
+// Entry of supported_languages: the extension → language table used to tag search results
+export interface LanguageMapping {
+  extension: string; // lowercase, without the leading dot
+  language: string;
+}
+
+// Payload of `file:changed` events emitted for files passed to watch_file
+export interface FileChangedEvent {
+  workingDirectory: string;
+  path: string; // relative to the workspace
+  kind: 'created' | 'modified' | 'removed';
+}
+
+// Result of generate_project_summary (cached in .specstudio/summary.md)
+export interface ProjectSummary {
+  content: string;
+  generatedAt: number; // Unix ms
+  cached: boolean; // served from the cache instead of regenerated
+}
+
+// Result of get_runtime_env: the PATH and tool locations used for spawning
+export interface RuntimeEnv {
+  path: string; // effective PATH given to spawned processes
+  inheritedPath: string; // PATH the app itself was launched with
+  shellPathDiscovered: boolean;
+  binaries: { name: string; path: string; exists: boolean }[];
+}
+
+// Result of cancel_streaming_processes
+export interface CancelResult {
+  success: boolean; // no kill failed (also true when nothing was running)
+  killed: number;
+  outcomes: { processId: string; pid?: number; status: 'killed' | 'failed' | 'noPid' }[];
+}
+
+// Result of spec_artifacts: companion files of a spec that exist on disk
+export interface SpecArtifacts {
+  filename: string;
+  artifacts: {
+    kind: 'plan' | 'chat' | 'summary';
+    path: string; // relative to the workspace
+    size: number;
+    modifiedAt?: number; // Unix ms
+  }[];
+}
+
+// Result of export_specs
+export interface SpecExport {
+  path: string; // absolute path under .specstudio/exports
+  format: 'markdown' | 'zip';
+  specCount: number;
+  size: number;
+}
+
+// Result of import_specs
+export interface ImportSpecsResult {
+  imported: number;
+  skipped: number;
+  results: { source: string; filename?: string; error?: string }[];
+}
+
+// Payload of provider:status events (also returned by startup_auth_check)
+export interface ProviderStatus {
+  provider: 'google' | 'anthropic' | 'gemini';
+  ready: boolean;
+  status: 'ready' | 'not_authenticated' | 'not_configured' | 'invalid' | 'error';
+  message: string;
+}
+
+// Result of estimate_context_cost
+export interface ContextCostEstimate {
+  model: string;
+  tokens: number;
+  method: 'api' | 'heuristic';
+  pricePerMillionTokens?: number;
+  estimatedCostUsd?: number;
+  totalFiles: number;
+  totalSize: number;
+  truncated: boolean;
+}
+
+export interface SettingsFieldError {
+  field: keyof AppSettings;
+  message: string;
+}
+
+export interface UpdateSettingsResult {
+  valid: boolean;
+  errors: SettingsFieldError[];
+  settings: AppSettings;
+}
+
+// A corrupt store that was reset at startup (take_store_recoveries)
+export interface StoreRecoveredEvent {
+  store: string;
+  backupPath: string;
+}
+
+// Backend Log Types (get_backend_logs)
+export interface LogEntry {
+  timestamp: number; // Unix timestamp in milliseconds
+  level: 'ERROR' | 'WARN' | 'INFO' | 'DEBUG' | 'TRACE';
+  target: string;
+  message: string;
+}
+
+// Workspace File Types
+export interface WorkspaceFile {
+  path: string;
+  content: string; // Lossy when isUtf8 is false
+  isUtf8: boolean;
+  size: number;
+  modifiedAt: number | null; // Unix timestamp in milliseconds
+}
+
+// File Range Types (read_file_range)
+export interface FileRange {
+  path: string;
+  startLine: number; // 1-indexed, inclusive; empty when endLine < startLine
+  endLine: number;
+  totalLines: number;
+  content: string;
+}
+
+// Recent Workspace Types
+export interface RecentWorkspace {
+  path: string;
+  name: string;
+  lastOpened: number; // Unix timestamp in milliseconds
+}
+
+// Project Detection Types
+export interface ProjectStack {
+  name: 'node' | 'rust' | 'python' | 'go';
+  marker: string;
+  runCommand: string | null;
+  testCommand: string | null;
+}
+
+export interface ProjectTypeResult {
+  stacks: ProjectStack[];
+}
+
+export interface NpmScript {
+  name: string;
+  command: string;
+}
+
+// Pre-flight result of check_claude_compatibility; a warning, not a hard failure
+export interface ClaudeCompatibility {
+  installed: boolean;
+  version: string | null;
+  compatible: boolean;
+  warning?: string;
 }
 
 // Development Plan Types